@@ -1,17 +1,36 @@
 use cosmwasm_std::{
-    ensure, entry_point, to_binary, Binary, Deps, DepsMut, Env, MessageInfo, StdResult,
+    ensure, entry_point, to_binary, Binary, Deps, DepsMut, Env, MessageInfo, Order, StdResult,
+    Uint128,
 };
-use cw2::set_contract_version;
+use cw2::{get_contract_version, set_contract_version};
+use cw_storage_plus::Bound;
 use sg_std::Response;
 
 use crate::error::ContractError;
-use crate::executes::{add_distribution, claim_rewards, return_unclaimed};
+use crate::executes::{
+    add_collection, add_collections_distribution, add_distribution, add_nft_distribution,
+    add_pool_distribution, amend_distribution, cancel_distribution, cancel_queued_distribution,
+    claim_bonus, claim_collections, claim_from_snapshot, claim_pro_rata, claim_rewards, claim_sets,
+    claim_with_proofs, clear_snapshot, freeze_weights, pause, prune_claims, receive_cw20,
+    receive_nft, return_unclaimed, set_pauser, set_trait_multipliers, top_up_distribution, unpause,
+    upload_snapshot, upload_weights, withdraw_deposits, withdraw_dust, Amendment, Payout,
+};
+use crate::helpers::{
+    is_claimed, load_open_distributions, query_token_traits, token_weight, trait_multiplier,
+};
 use crate::msg::{ExecuteMsg, InstantiateMsg, MigrateMsg, QueryMsg};
-use crate::state::{Config, Distribution, CLAIMS, CONFIG, DISTRIBUTION};
+use crate::state::{
+    Asset, Config, Pauses, QueuedDistribution, BONUS_CLAIMS, COLLECTIONS, COLLECTION_CLAIMS,
+    CONFIG, CW20_DEPOSITS, DISTRIBUTIONS, DUST, NFT_DEPOSITS, PRO_RATA_SHARES, QUEUE, SNAPSHOT,
+    SNAPSHOT_OWNERS, TOKEN_PAID, TOKEN_TRAITS, TRAIT_MULTIPLIERS, WALLET_CLAIMS, WEIGHT_TABLE,
+};
 
 pub const CONTRACT_NAME: &str = "crates.io:cw-nft-reward-distribution";
 pub const CONTRACT_VERSION: &str = env!("CARGO_PKG_VERSION");
 
+const DEFAULT_QUERY_LIMIT: u32 = 100;
+const MAX_QUERY_LIMIT: u32 = 500;
+
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn instantiate(
     deps: DepsMut,
@@ -32,7 +51,15 @@ pub fn instantiate(
         nft_address: deps.api.addr_validate(&init_msg.nft_address)?,
         nft_count: nft_count.count,
         current_dist_end_time: None,
-        current_dist_halted: false,
+        pauser: Some(
+            deps.api
+                .addr_validate(init_msg.pauser.as_deref().unwrap_or(&init_msg.admin))?,
+        ),
+        pauses: Pauses::default(),
+        claim_storage: init_msg.claim_storage.unwrap_or_default(),
+        last_queue_id: 0,
+        last_nft_pool_id: 0,
+        last_snapshot_id: 0,
     };
 
     CONFIG.save(deps.storage, &config)?;
@@ -51,55 +78,449 @@ pub fn execute(
         ExecuteMsg::BulkClaim {
             token_ids,
             start_after,
-        } => claim_rewards(deps, env, info, token_ids, start_after, true),
-        ExecuteMsg::ClaimFive { token_ids } => {
-            claim_rewards(deps, env, info, token_ids, None, false)
-        }
+            dist_ids,
+        } => claim_rewards(deps, env, info, token_ids, start_after, dist_ids, true),
+        ExecuteMsg::ClaimFive {
+            token_ids,
+            dist_ids,
+        } => claim_rewards(deps, env, info, token_ids, None, dist_ids, false),
         ExecuteMsg::Distribute {
             assets_per_nft,
             distribution_end_time,
-            unclaimed_to_distributor,
+            unclaimed_policy,
+            minimum_nfts_to_claim,
+            start_time,
+            max_tokens_per_wallet,
+            max_amount_per_wallet,
+            max_token_id,
+            snapshot_id,
+        } => add_distribution(
+            deps,
+            env,
+            info.sender,
+            info.funds.into_iter().map(Asset::from).collect(),
+            assets_per_nft,
+            distribution_end_time,
+            unclaimed_policy,
+            minimum_nfts_to_claim,
+            start_time,
+            max_token_id,
+            snapshot_id,
+            Payout::Flat {
+                max_tokens_per_wallet,
+                max_amount_per_wallet,
+            },
+        ),
+        ExecuteMsg::TopUp { assets_per_nft } => top_up_distribution(
+            deps,
+            env,
+            info.sender,
+            info.funds.into_iter().map(Asset::from).collect(),
+            assets_per_nft,
+        ),
+        ExecuteMsg::DistributeMerkle {
+            merkle_root,
+            total_assets,
+            distribution_end_time,
+            unclaimed_policy,
+            minimum_nfts_to_claim,
+            start_time,
+        } => add_distribution(
+            deps,
+            env,
+            info.sender,
+            info.funds.into_iter().map(Asset::from).collect(),
+            total_assets,
+            distribution_end_time,
+            unclaimed_policy,
+            minimum_nfts_to_claim,
+            start_time,
+            None,
+            None,
+            Payout::Merkle { merkle_root },
+        ),
+        ExecuteMsg::DistributeWeighted {
+            assets_per_weight,
+            distribution_end_time,
+            unclaimed_policy,
             minimum_nfts_to_claim,
+            start_time,
+            snapshot_id,
         } => add_distribution(
+            deps,
+            env,
+            info.sender,
+            info.funds.into_iter().map(Asset::from).collect(),
+            assets_per_weight,
+            distribution_end_time,
+            unclaimed_policy,
+            minimum_nfts_to_claim,
+            start_time,
+            None,
+            snapshot_id,
+            Payout::Weighted,
+        ),
+        ExecuteMsg::DistributeByTraits {
+            assets_per_weight,
+            total_weight,
+            distribution_end_time,
+            unclaimed_policy,
+            minimum_nfts_to_claim,
+            start_time,
+            snapshot_id,
+        } => add_distribution(
+            deps,
+            env,
+            info.sender,
+            info.funds.into_iter().map(Asset::from).collect(),
+            assets_per_weight,
+            distribution_end_time,
+            unclaimed_policy,
+            minimum_nfts_to_claim,
+            start_time,
+            None,
+            snapshot_id,
+            Payout::Traits { total_weight },
+        ),
+        ExecuteMsg::SetTraitMultipliers { trait_multipliers } => {
+            set_trait_multipliers(deps, info, trait_multipliers)
+        }
+        ExecuteMsg::UploadWeights { weights, ranges } => {
+            upload_weights(deps, info, weights, ranges)
+        }
+        ExecuteMsg::FreezeWeights {} => freeze_weights(deps, info),
+        ExecuteMsg::ClaimWithProofs { dist_id, claims } => {
+            claim_with_proofs(deps, env, info, dist_id, claims)
+        }
+        ExecuteMsg::DistributePool {
+            distribution_end_time,
+            unclaimed_policy,
+            minimum_nfts_to_claim,
+            start_time,
+            snapshot_id,
+        } => add_pool_distribution(
             deps,
             env,
             info,
+            distribution_end_time,
+            unclaimed_policy,
+            minimum_nfts_to_claim,
+            start_time,
+            snapshot_id,
+        ),
+        ExecuteMsg::DistributeCollections {
+            collections,
+            distribution_end_time,
+            unclaimed_policy,
+            start_time,
+        } => add_collections_distribution(
+            deps,
+            env,
+            info,
+            collections,
+            distribution_end_time,
+            unclaimed_policy,
+            start_time,
+        ),
+        ExecuteMsg::DistributeTiered {
             assets_per_nft,
+            tiers,
+            distribution_end_time,
+            unclaimed_policy,
+            minimum_nfts_to_claim,
+            start_time,
+        } => add_distribution(
+            deps,
+            env,
+            info.sender,
+            info.funds.into_iter().map(Asset::from).collect(),
+            assets_per_nft,
+            distribution_end_time,
+            unclaimed_policy,
+            minimum_nfts_to_claim,
+            start_time,
+            None,
+            None,
+            Payout::Tiered { tiers },
+        ),
+        ExecuteMsg::DistributeSets {
+            assets_per_set,
+            components,
+            max_sets,
+            distribution_end_time,
+            unclaimed_policy,
+            start_time,
+        } => add_distribution(
+            deps,
+            env,
+            info.sender,
+            info.funds.into_iter().map(Asset::from).collect(),
+            assets_per_set,
+            distribution_end_time,
+            unclaimed_policy,
+            0,
+            start_time,
+            None,
+            None,
+            Payout::Sets {
+                components,
+                max_sets,
+            },
+        ),
+        ExecuteMsg::ClaimSets { dist_id, sets } => claim_sets(deps, env, info, dist_id, sets),
+        ExecuteMsg::AddCollection { collection } => add_collection(deps, info, collection),
+        ExecuteMsg::ClaimCollections { tokens, dist_ids } => {
+            claim_collections(deps, env, info, tokens, dist_ids)
+        }
+        ExecuteMsg::UploadSnapshot {
+            snapshot_time,
+            owners,
+            merkle_root,
+        } => upload_snapshot(deps, env, info, snapshot_time, owners, merkle_root),
+        ExecuteMsg::ClearSnapshot {} => clear_snapshot(deps, info),
+        ExecuteMsg::ClaimFromSnapshot { dist_id, claims } => {
+            claim_from_snapshot(deps, env, info, dist_id, claims)
+        }
+        ExecuteMsg::ClaimBonus { dist_id, token_ids } => {
+            claim_bonus(deps, env, info, dist_id, token_ids)
+        }
+        ExecuteMsg::ClaimProRata { dist_id } => claim_pro_rata(deps, env, info, dist_id),
+        ExecuteMsg::DistributeNfts {
+            collection,
+            distribution_end_time,
+            minimum_nfts_to_claim,
+            start_time,
+            snapshot_id,
+        } => add_nft_distribution(
+            deps,
+            env,
+            info,
+            collection,
             distribution_end_time,
-            unclaimed_to_distributor,
             minimum_nfts_to_claim,
+            start_time,
+            snapshot_id,
         ),
-        ExecuteMsg::ReturnUnclaimed {} => return_unclaimed(deps, env, info),
-        ExecuteMsg::HaltDistribution {} => {
-            let mut config = CONFIG.load(deps.storage)?;
-            ensure!(info.sender == config.admin, ContractError::Unauthorized {});
-            config.current_dist_halted = !config.current_dist_halted;
-            CONFIG.save(deps.storage, &config)?;
-            Ok(Response::default())
+        ExecuteMsg::CancelQueuedDistribution { queue_id } => {
+            cancel_queued_distribution(deps, env, info, queue_id)
         }
+        ExecuteMsg::CancelDistribution { dist_id } => cancel_distribution(deps, env, info, dist_id),
+        ExecuteMsg::ExtendDistributionWindow { end_time } => {
+            amend_distribution(deps, env, info, Amendment::ExtendWindow { end_time })
+        }
+        ExecuteMsg::CloseDistributionWindow { end_time } => {
+            amend_distribution(deps, env, info, Amendment::CloseWindow { end_time })
+        }
+        ExecuteMsg::SetMinimumNftsToClaim {
+            minimum_nfts_to_claim,
+        } => amend_distribution(
+            deps,
+            env,
+            info,
+            Amendment::MinimumNftsToClaim {
+                minimum_nfts_to_claim,
+            },
+        ),
+        ExecuteMsg::ReturnUnclaimed { dist_id } => return_unclaimed(deps, env, info, dist_id),
+        ExecuteMsg::Pause {
+            scope,
+            reason,
+            until,
+        } => pause(deps, env, info, scope, reason, until),
+        ExecuteMsg::Unpause { scope } => unpause(deps, info, scope),
+        ExecuteMsg::SetPauser { pauser } => set_pauser(deps, info, pauser),
+        ExecuteMsg::PruneClaims { dist_id, limit } => prune_claims(deps, info, dist_id, limit),
+        ExecuteMsg::Receive(cw20_msg) => receive_cw20(deps, info, cw20_msg),
+        ExecuteMsg::ReceiveNft(nft_msg) => receive_nft(deps, info, nft_msg),
+        ExecuteMsg::WithdrawDeposits {} => withdraw_deposits(deps, info),
+        ExecuteMsg::WithdrawDust {} => withdraw_dust(deps, info),
     }
 }
 
 #[cfg_attr(not(feature = "library"), entry_point)]
-pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
+pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
     match msg {
         QueryMsg::GetConfig {} => to_binary(&CONFIG.load(deps.storage)?),
         QueryMsg::GetIfClaimed { token_id } => {
-            to_binary(&CLAIMS.load(deps.storage, &token_id).unwrap_or(false))
+            let config = CONFIG.load(deps.storage)?;
+            to_binary(&is_claimed(
+                deps.storage,
+                &config.claim_storage,
+                config.current_dist_id,
+                &token_id,
+            )?)
         }
         QueryMsg::GetCurrentDistributionInfo {} => {
-            to_binary(&DISTRIBUTION.load(deps.storage).unwrap_or(Distribution {
-                assets: vec![],
-                claimed: 0,
-                unclaimed_to_distributor: false,
-                unclaimed_sent_to_distributor: None,
-                minimum_nfts_to_claim: 0,
-            }))
+            let config = CONFIG.load(deps.storage)?;
+            to_binary(
+                &DISTRIBUTIONS
+                    .may_load(deps.storage, config.current_dist_id)?
+                    .unwrap_or_default(),
+            )
+        }
+        QueryMsg::GetDistribution { dist_id } => {
+            to_binary(&DISTRIBUTIONS.load(deps.storage, dist_id)?)
+        }
+        QueryMsg::GetOpenDistributions {} => to_binary(
+            &load_open_distributions(deps.storage, &env)?
+                .into_iter()
+                .map(|(dist_id, _)| dist_id)
+                .collect::<Vec<u64>>(),
+        ),
+        QueryMsg::GetQueuedDistributions {} => to_binary(
+            &QUEUE
+                .range(deps.storage, None, None, Order::Ascending)
+                .collect::<StdResult<Vec<(u64, QueuedDistribution)>>>()?,
+        ),
+        QueryMsg::GetIfClaimedBonus { dist_id, token_id } => {
+            to_binary(&BONUS_CLAIMS.has(deps.storage, (dist_id, &token_id)))
+        }
+        QueryMsg::GetTokenPaid { dist_id, token_id } => {
+            let config = CONFIG.load(deps.storage)?;
+            let paid = match TOKEN_PAID.may_load(deps.storage, (dist_id, &token_id))? {
+                Some(paid) => paid,
+                // Untracked claimed tokens were paid the amounts before the first top up
+                None if is_claimed(deps.storage, &config.claim_storage, dist_id, &token_id)? => {
+                    let distribution = DISTRIBUTIONS.load(deps.storage, dist_id)?;
+                    distribution.topped_up_from.unwrap_or(distribution.assets)
+                }
+                None => vec![],
+            };
+            to_binary(&paid)
+        }
+        QueryMsg::GetIfClaimedInDistribution { dist_id, token_id } => {
+            let config = CONFIG.load(deps.storage)?;
+            to_binary(&is_claimed(
+                deps.storage,
+                &config.claim_storage,
+                dist_id,
+                &token_id,
+            )?)
+        }
+        QueryMsg::GetDust {} => to_binary(&DUST.may_load(deps.storage)?.unwrap_or_default()),
+        QueryMsg::GetDeposits {} => to_binary(
+            &CW20_DEPOSITS
+                .range(deps.storage, None, None, Order::Ascending)
+                .map(|deposit| {
+                    let (contract_addr, amount) = deposit?;
+                    Ok(Asset::cw20(contract_addr, amount))
+                })
+                .collect::<StdResult<Vec<Asset>>>()?,
+        ),
+        QueryMsg::GetTokenWeight { token_id } => to_binary(&token_weight(deps.storage, &token_id)?),
+        QueryMsg::GetWeightTable {} => {
+            to_binary(&WEIGHT_TABLE.may_load(deps.storage)?.unwrap_or_default())
+        }
+        QueryMsg::GetTraitMultipliers {} => to_binary(
+            &TRAIT_MULTIPLIERS
+                .may_load(deps.storage)?
+                .unwrap_or_default(),
+        ),
+        QueryMsg::GetTokenMultiplier { token_id } => {
+            let config = CONFIG.load(deps.storage)?;
+            let traits = match TOKEN_TRAITS.may_load(deps.storage, &token_id)? {
+                Some(traits) => traits,
+                None => query_token_traits(&deps.querier, config.nft_address.as_str(), &token_id)?,
+            };
+            to_binary(&trait_multiplier(
+                &TRAIT_MULTIPLIERS
+                    .may_load(deps.storage)?
+                    .unwrap_or_default(),
+                &traits,
+            ))
+        }
+        QueryMsg::GetCollections {} => to_binary(
+            &COLLECTIONS
+                .range(deps.storage, None, None, Order::Ascending)
+                .collect::<StdResult<Vec<_>>>()?,
+        ),
+        QueryMsg::GetIfClaimedInCollection {
+            dist_id,
+            collection,
+            token_id,
+        } => {
+            let config = CONFIG.load(deps.storage)?;
+            let collection = deps.api.addr_validate(&collection)?;
+            to_binary(&if collection == config.nft_address {
+                is_claimed(deps.storage, &config.claim_storage, dist_id, &token_id)?
+            } else {
+                COLLECTION_CLAIMS.has(deps.storage, (dist_id, &collection, &token_id))
+            })
+        }
+        QueryMsg::GetSnapshot {} => to_binary(&SNAPSHOT.may_load(deps.storage)?),
+        QueryMsg::GetSnapshotOwner {
+            snapshot_id,
+            token_id,
+        } => {
+            let snapshot_id = match snapshot_id {
+                Some(snapshot_id) => Some(snapshot_id),
+                None => SNAPSHOT
+                    .may_load(deps.storage)?
+                    .map(|snapshot| snapshot.snapshot_id),
+            };
+            to_binary(&match snapshot_id {
+                Some(snapshot_id) => {
+                    SNAPSHOT_OWNERS.may_load(deps.storage, (snapshot_id, &token_id))?
+                }
+                None => None,
+            })
+        }
+        QueryMsg::GetWalletClaim { dist_id, wallet } => to_binary(
+            &WALLET_CLAIMS
+                .may_load(deps.storage, (dist_id, &wallet))?
+                .unwrap_or_default(),
+        ),
+        QueryMsg::GetProRataShare { dist_id, wallet } => {
+            let tokens = WALLET_CLAIMS
+                .may_load(deps.storage, (dist_id, &wallet))?
+                .map(|wallet_claim| Uint128::new(wallet_claim.tokens as u128))
+                .unwrap_or_default();
+            let shares = PRO_RATA_SHARES
+                .may_load(deps.storage, dist_id)?
+                .unwrap_or_default();
+            to_binary(
+                &shares
+                    .into_iter()
+                    .map(|share| Asset {
+                        info: share.info,
+                        amount: share.amount * tokens,
+                    })
+                    .filter(|share| !share.amount.is_zero())
+                    .collect::<Vec<Asset>>(),
+            )
+        }
+        QueryMsg::GetNftDeposits {
+            collection,
+            start_after,
+            limit,
+        } => {
+            let collection = deps.api.addr_validate(&collection)?;
+            let limit = limit.unwrap_or(DEFAULT_QUERY_LIMIT).min(MAX_QUERY_LIMIT) as usize;
+            to_binary(
+                &NFT_DEPOSITS
+                    .prefix(&collection)
+                    .keys(
+                        deps.storage,
+                        start_after.as_deref().map(Bound::exclusive),
+                        None,
+                        Order::Ascending,
+                    )
+                    .take(limit)
+                    .collect::<StdResult<Vec<String>>>()?,
+            )
         }
     }
 }
 
 #[cfg_attr(not(feature = "library"), entry_point)]
-pub fn migrate(_deps: DepsMut, _env: Env, _msg: MigrateMsg) -> Result<Response, ContractError> {
+pub fn migrate(deps: DepsMut, _env: Env, _msg: MigrateMsg) -> Result<Response, ContractError> {
+    // 0.1.x kept a single distribution with claims keyed by token id and a smaller Config, its
+    // storage is not rewritten so those contracts cannot be migrated
+    let version = get_contract_version(deps.storage)?;
+    ensure!(
+        version.contract == CONTRACT_NAME && !version.version.starts_with("0.1."),
+        ContractError::UnsupportedMigration {}
+    );
+    set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
     Ok(Response::default())
 }
@@ -36,8 +36,29 @@ pub enum ContractError {
     #[error("TooManyTokensSent")]
     TooManyTokensSent {},
 
-    #[error("DistributionHalted")]
-    DistributionHalted {},
+    #[error("ClaimsPaused")]
+    ClaimsPaused {},
+
+    #[error("FundingPaused")]
+    FundingPaused {},
+
+    #[error("ReturnsPaused")]
+    ReturnsPaused {},
+
+    #[error("InvalidPause")]
+    InvalidPause {},
+
+    #[error("UnknownSnapshot")]
+    UnknownSnapshot {},
+
+    #[error("BelowLowestTier")]
+    BelowLowestTier {},
+
+    #[error("NotACw20Contract")]
+    NotACw20Contract {},
+
+    #[error("UnsupportedMigration")]
+    UnsupportedMigration {},
 
     #[error("InvalidNftCount")]
     InvalidNftCount {},
@@ -48,9 +69,81 @@ pub enum ContractError {
     #[error("NothingToClaim")]
     NothingToClaim {},
 
+    #[error("AllTokensClaimed")]
+    AllTokensClaimed {},
+
     #[error("InvalidDistributionInputs")]
     InvalidDistributionInputs {},
 
     #[error("InvalidClaimValue")]
     InvalidClaimValue {},
+
+    #[error("CannotPruneCurrentDistribution")]
+    CannotPruneCurrentDistribution {},
+
+    #[error("InvalidDistributionStartTime")]
+    InvalidDistributionStartTime {},
+
+    #[error("QueuedDistributionNotFound")]
+    QueuedDistributionNotFound {},
+
+    #[error("InvalidMerkleRoot")]
+    InvalidMerkleRoot {},
+
+    #[error("InvalidMerkleProof")]
+    InvalidMerkleProof {},
+
+    #[error("NotAMerkleDistribution")]
+    NotAMerkleDistribution {},
+
+    #[error("InvalidWeights")]
+    InvalidWeights {},
+
+    #[error("WeightsFrozen")]
+    WeightsFrozen {},
+
+    #[error("WeightsNotFrozen")]
+    WeightsNotFrozen {},
+
+    #[error("InvalidSnapshot")]
+    InvalidSnapshot {},
+
+    #[error("NotASnapshotDistribution")]
+    NotASnapshotDistribution {},
+
+    #[error("UnknownCollection")]
+    UnknownCollection {},
+
+    #[error("InvalidSet")]
+    InvalidSet {},
+
+    #[error("NotASetDistribution")]
+    NotASetDistribution {},
+
+    #[error("InvalidTiers")]
+    InvalidTiers {},
+
+    #[error("InvalidWalletCap")]
+    InvalidWalletCap {},
+
+    #[error("WalletCapReached")]
+    WalletCapReached {},
+
+    #[error("InvalidBonusRound")]
+    InvalidBonusRound {},
+
+    #[error("NotABonusRoundDistribution")]
+    NotABonusRoundDistribution {},
+
+    #[error("BonusRoundIsNotOpen")]
+    BonusRoundIsNotOpen {},
+
+    #[error("BonusRoundHasNotYetEnded")]
+    BonusRoundHasNotYetEnded {},
+
+    #[error("CannotTopUpDistribution")]
+    CannotTopUpDistribution {},
+
+    #[error("NoActiveDistribution")]
+    NoActiveDistribution {},
 }
@@ -1,16 +1,77 @@
-use cosmwasm_std::{ensure, BankMsg, Coin, DepsMut, Env, MessageInfo, Timestamp, Uint128};
-use sg_std::Response;
+use cosmwasm_std::{
+    ensure, from_binary, to_binary, Addr, DepsMut, Env, Event, MessageInfo, Order, StdResult,
+    Storage, Timestamp, Uint128,
+};
+use cw20::Cw20ReceiveMsg;
+use cw721::Cw721ReceiveMsg;
+use cw_storage_plus::Bound;
+use sg_std::{CosmosMsg, Response};
+use std::collections::BTreeMap;
 
 use crate::error::ContractError;
-use crate::helpers::{create_send_assets_messages, query_owned_tokens, validate_distribution};
-use crate::state::{Distribution, CLAIMS, CONFIG, DISTRIBUTION};
+use crate::helpers::{
+    add_asset, create_send_assets_messages, decode_merkle_root, eligible_tokens, ensure_not_paused,
+    funding_response, highest_tier_multiplier, is_claimed, load_ended_distributions,
+    load_open_distributions, load_token_traits, mark_claimed, numeric_token_id, open_bonus_round,
+    pay_claimed_tokens, pay_top_up_differences, query_asset_balance, query_cw20_token_info,
+    query_highest_token_id, query_nft_count, query_nfts_held, query_owned_tokens,
+    query_token_traits, settle_unclaimed, take_nft_rewards, track_wallet_claim, transfer_asset_msg,
+    transfer_nft_msg, unclaimed_assets, validate_distribution, verify_merkle_leaf,
+    verify_merkle_proof, wallet_allowance, ToPay,
+};
+use crate::msg::{
+    CollectionAssets, CollectionToken, DistributePoolResponse, MerkleClaim, ReceiveMsg,
+    ReceiveNftMsg, RequiredToken, SnapshotClaim, SnapshotOwner, TokenWeight, WeightRange,
+};
+use crate::state::{
+    Asset, AssetInfo, CollectionInfo, CollectionRewards, Config, Distribution, NftRewards, Pause,
+    PauseScope, QueuedDistribution, SetComponent, SetRewards, Snapshot, Tier, TraitMultiplier,
+    UnclaimedPolicy, WalletCap, ACTIVE_DISTRIBUTIONS, BONUS_CLAIMS, CLAIMS, CLAIM_BITMAPS,
+    COLLECTIONS, COLLECTION_CLAIMS, CONFIG, CW20_DEPOSITS, DISTRIBUTIONS, DUST, NFT_DEPOSITS,
+    NFT_POOLS, PRO_RATA_SHARES, QUEUE, SNAPSHOT, SNAPSHOT_OWNERS, TOKEN_PAID, TOKEN_WEIGHTS,
+    TRAIT_MULTIPLIERS, WALLET_CLAIMS, WEIGHT_RANGES, WEIGHT_TABLE,
+};
+
+const DEFAULT_PRUNE_LIMIT: u32 = 100;
+const MAX_PRUNE_LIMIT: u32 = 500;
+const MAX_WEIGHTS_PER_UPLOAD: usize = 500;
+const MAX_OWNERS_PER_UPLOAD: usize = 500;
+
+// How the amount paid to each token is computed
+pub enum Payout {
+    // The assets per token, optionally capped per wallet
+    Flat {
+        max_tokens_per_wallet: Option<u64>,
+        max_amount_per_wallet: Option<Vec<Asset>>,
+    },
+    // The proven amounts, the assets are the committed totals
+    Merkle {
+        merkle_root: String,
+    },
+    // The assets per unit of weight of the frozen weight table
+    Weighted,
+    // The assets times the token's trait multiplier, out of the declared total weight
+    Traits {
+        total_weight: u64,
+    },
+    // The assets times the multiplier of the holder's tier, funded for the highest multiplier
+    Tiered {
+        tiers: Vec<Tier>,
+    },
+    // The assets per complete set of tokens, out of the declared maximum number of sets
+    Sets {
+        components: Vec<RequiredToken>,
+        max_sets: u64,
+    },
+}
 
 pub fn claim_rewards(
-    deps: DepsMut,
+    mut deps: DepsMut,
     env: Env,
     info: MessageInfo,
     token_ids: Vec<String>,
     start_after: Option<String>,
+    dist_ids: Option<Vec<u64>>,
     bulk: bool,
 ) -> Result<Response, ContractError> {
     // Bulk uses the "Tokens" query approach
@@ -25,20 +86,31 @@ pub fn claim_rewards(
         }
     }
 
-    let config = CONFIG.load(deps.storage)?;
-    // If no Distribution logged -> this will error
-    let mut distribution = DISTRIBUTION.load(deps.storage)?;
+    let mut config = CONFIG.load(deps.storage)?;
 
-    if config.current_dist_halted {
-        return Err(ContractError::DistributionHalted {});
-    }
+    ensure_not_paused(&config, &PauseScope::Claim, &env)?;
 
-    let dist_timer_end = config
-        .current_dist_end_time
-        .unwrap_or(Timestamp::from_seconds(0u64));
-    if env.block.time > dist_timer_end {
-        return Err(ContractError::ClaimingWindowHasClosed {});
-    }
+    let mut response = sync_config(deps.branch(), &env, &mut config)?;
+
+    let mut distributions = select_open_distributions(deps.storage, &env, dist_ids)?;
+
+    // Each distribution has its own holding requirement
+    let minimum_nfts_to_claim = distributions
+        .iter()
+        .map(|(_, distribution)| distribution.minimum_nfts_to_claim)
+        .max()
+        .unwrap_or_default();
+    let nfts_held = query_nfts_held(
+        &deps,
+        config.nft_address.as_ref(),
+        info.sender.as_ref(),
+        minimum_nfts_to_claim,
+    )?;
+    distributions.retain(|(_, distribution)| nfts_held >= distribution.minimum_nfts_to_claim);
+    ensure!(
+        !distributions.is_empty(),
+        ContractError::NotEnoughNftsToClaim {}
+    );
 
     // Intersection between provided and actually owned
     let validated_tokens = query_owned_tokens(
@@ -48,31 +120,701 @@ pub fn claim_rewards(
         token_ids,
         start_after,
         bulk,
-        distribution.minimum_nfts_to_claim,
     )?;
     let owned_tokens = validated_tokens.found;
 
-    let mut dist_count: u64 = 0;
-    for token in owned_tokens.iter() {
-        // If it errors -> means unclaimed
-        if CLAIMS.load(deps.storage, token).is_err() {
-            dist_count += 1;
-            CLAIMS.save(deps.storage, token.as_str(), &true)?;
+    let mut claimed_dist_ids: Vec<String> = Vec::with_capacity(distributions.len());
+    let mut wallet_capped = false;
+    for (dist_id, distribution) in distributions {
+        let eligible = eligible_tokens(distribution.max_token_id, &owned_tokens);
+        match claim_tokens(
+            &mut deps,
+            &config,
+            dist_id,
+            distribution,
+            eligible,
+            info.sender.as_str(),
+        )? {
+            Claimed::WalletCapReached => wallet_capped = true,
+            Claimed::Nothing => {}
+            Claimed::Paid(messages) => {
+                response = response.add_messages(messages);
+                claimed_dist_ids.push(dist_id.to_string());
+            }
+        }
+    }
+
+    if claimed_dist_ids.is_empty() {
+        return Err(if wallet_capped {
+            ContractError::WalletCapReached {}
+        } else {
+            ContractError::NothingToClaim {}
+        });
+    }
+
+    response = response.add_attribute("dist_ids", claimed_dist_ids.join(", "));
+    if !validated_tokens.not_found.is_empty() {
+        // Returns an answer if the required tokens were owned or not
+        response = response.add_attribute(
+            "Tokens not found or not owned: ".to_string(),
+            validated_tokens.not_found.join(", "),
+        );
+    }
+    Ok(response)
+}
+
+// Outcome of claiming the tokens of a wallet in a distribution
+enum Claimed {
+    WalletCapReached,
+    Nothing,
+    Paid(Vec<CosmosMsg>),
+}
+
+// Claims the tokens within the wallet cap, the ones which claimed before a top up get the difference
+fn claim_tokens(
+    deps: &mut DepsMut,
+    config: &Config,
+    dist_id: u64,
+    mut distribution: Distribution,
+    token_ids: Vec<String>,
+    recipient: &str,
+) -> Result<Claimed, ContractError> {
+    let Some(allowed) = wallet_allowance(
+        deps.storage,
+        &config.claim_storage,
+        dist_id,
+        &distribution,
+        recipient,
+        token_ids,
+    )?
+    else {
+        return Ok(Claimed::WalletCapReached);
+    };
+    let newly_claimed = mark_claimed(deps.storage, &config.claim_storage, dist_id, &allowed)?;
+    let already_claimed: Vec<String> = allowed
+        .into_iter()
+        .filter(|token_id| !newly_claimed.contains(token_id))
+        .collect();
+    let mut messages = pay_top_up_differences(
+        deps.storage,
+        dist_id,
+        &mut distribution,
+        &already_claimed,
+        recipient,
+    )?;
+    if newly_claimed.is_empty() {
+        return Ok(if messages.is_empty() {
+            Claimed::Nothing
+        } else {
+            Claimed::Paid(messages)
+        });
+    }
+    messages.extend(pay_claimed_tokens(
+        deps,
+        config,
+        dist_id,
+        distribution,
+        &newly_claimed,
+        recipient,
+    )?);
+    Ok(Claimed::Paid(messages))
+}
+
+pub fn claim_collections(
+    mut deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    tokens: Vec<CollectionToken>,
+    dist_ids: Option<Vec<u64>>,
+) -> Result<Response, ContractError> {
+    ensure!(tokens.len() <= 500, ContractError::TooManyTokensSent {});
+    let bulk = tokens.len() > 5;
+
+    let mut config = CONFIG.load(deps.storage)?;
+    ensure_not_paused(&config, &PauseScope::Claim, &env)?;
+
+    let mut response = sync_config(deps.branch(), &env, &mut config)?;
+
+    let distributions = select_open_distributions(deps.storage, &env, dist_ids)?;
+    let minimum_nfts_to_claim = distributions
+        .iter()
+        .flat_map(|(_, distribution)| {
+            distribution
+                .collection_rewards
+                .iter()
+                .map(|rewards| rewards.minimum_nfts_to_claim)
+                .chain([distribution.minimum_nfts_to_claim])
+        })
+        .max()
+        .unwrap_or_default();
+
+    let mut token_ids_by_collection: BTreeMap<Addr, Vec<String>> = BTreeMap::new();
+    for token in tokens {
+        let collection = deps.api.addr_validate(&token.collection)?;
+        ensure!(
+            collection == config.nft_address || COLLECTIONS.has(deps.storage, &collection),
+            ContractError::UnknownCollection {}
+        );
+        token_ids_by_collection
+            .entry(collection)
+            .or_default()
+            .push(token.token_id);
+    }
+
+    // Owned tokens and number of NFTs held, by collection
+    let mut holdings: BTreeMap<Addr, (Vec<String>, u64)> = BTreeMap::new();
+    let mut not_found_tokens: Vec<String> = vec![];
+    for (collection, token_ids) in token_ids_by_collection {
+        let validated_tokens = match query_owned_tokens(
+            &deps,
+            &collection.to_string(),
+            info.sender.as_ref(),
+            token_ids.clone(),
+            None,
+            bulk,
+        ) {
+            Ok(validated_tokens) => validated_tokens,
+            Err(ContractError::NothingToClaim {}) => ToPay {
+                found: vec![],
+                not_found: token_ids,
+            },
+            Err(err) => return Err(err),
+        };
+        not_found_tokens.extend(
+            validated_tokens
+                .not_found
+                .iter()
+                .map(|token_id| format!("{collection}:{token_id}")),
+        );
+        let nfts_held = query_nfts_held(
+            &deps,
+            collection.as_str(),
+            info.sender.as_ref(),
+            minimum_nfts_to_claim,
+        )?;
+        holdings.insert(collection, (validated_tokens.found, nfts_held));
+    }
+
+    let mut claimed_dist_ids: Vec<String> = Vec::with_capacity(distributions.len());
+    let mut wallet_capped = false;
+    for (dist_id, mut distribution) in distributions {
+        let mut collection_tokens = 0u64;
+        // Each collection has its own holding requirement and claims
+        for rewards in distribution.collection_rewards.iter_mut() {
+            let Some((owned_tokens, nfts_held)) = holdings.get(&rewards.collection) else {
+                continue;
+            };
+            if *nfts_held < rewards.minimum_nfts_to_claim {
+                continue;
+            }
+            let mut newly_claimed = 0u64;
+            for token_id in eligible_tokens(rewards.max_token_id, owned_tokens) {
+                let key = (dist_id, &rewards.collection, token_id.as_str());
+                if !COLLECTION_CLAIMS.has(deps.storage, key) {
+                    COLLECTION_CLAIMS.save(deps.storage, key, &true)?;
+                    newly_claimed += 1;
+                }
+            }
+            if newly_claimed == 0 {
+                continue;
+            }
+            rewards.claimed += newly_claimed;
+            ensure!(
+                rewards.claimed <= rewards.nft_count,
+                ContractError::AllTokensClaimed {}
+            );
+            for asset in rewards.assets.iter() {
+                response = response.add_message(transfer_asset_msg(
+                    &Asset {
+                        info: asset.info.clone(),
+                        amount: asset.amount * Uint128::new(newly_claimed as u128),
+                    },
+                    info.sender.as_str(),
+                )?);
+            }
+            collection_tokens += newly_claimed;
+        }
+        if collection_tokens > 0 {
+            track_wallet_claim(
+                deps.storage,
+                dist_id,
+                &distribution,
+                info.sender.as_str(),
+                collection_tokens,
+            )?;
+            DISTRIBUTIONS.save(deps.storage, dist_id, &distribution)?;
+        }
+
+        // The configured collection is paid like the other claims, caps and top ups included
+        let eligible = match holdings.get(&config.nft_address) {
+            Some((owned_tokens, nfts_held)) if *nfts_held >= distribution.minimum_nfts_to_claim => {
+                eligible_tokens(distribution.max_token_id, owned_tokens)
+            }
+            _ => vec![],
+        };
+        let claimed = if eligible.is_empty() {
+            Claimed::Nothing
+        } else {
+            claim_tokens(
+                &mut deps,
+                &config,
+                dist_id,
+                distribution,
+                eligible,
+                info.sender.as_str(),
+            )?
+        };
+        match claimed {
+            Claimed::Paid(messages) => response = response.add_messages(messages),
+            Claimed::WalletCapReached if collection_tokens == 0 => {
+                wallet_capped = true;
+                continue;
+            }
+            _ if collection_tokens == 0 => continue,
+            _ => {}
+        }
+        claimed_dist_ids.push(dist_id.to_string());
+    }
+
+    if claimed_dist_ids.is_empty() {
+        return Err(if wallet_capped {
+            ContractError::WalletCapReached {}
+        } else {
+            ContractError::NothingToClaim {}
+        });
+    }
+
+    response = response.add_attribute("dist_ids", claimed_dist_ids.join(", "));
+    if !not_found_tokens.is_empty() {
+        response = response.add_attribute(
+            "Tokens not found or not owned: ".to_string(),
+            not_found_tokens.join(", "),
+        );
+    }
+    Ok(response)
+}
+
+pub fn claim_sets(
+    mut deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    dist_id: u64,
+    sets: Vec<Vec<CollectionToken>>,
+) -> Result<Response, ContractError> {
+    let token_count: usize = sets.iter().map(|set| set.len()).sum();
+    ensure!(token_count <= 500, ContractError::TooManyTokensSent {});
+    let bulk = token_count > 5;
+
+    let mut config = CONFIG.load(deps.storage)?;
+    ensure_not_paused(&config, &PauseScope::Claim, &env)?;
+
+    let mut response = sync_config(deps.branch(), &env, &mut config)?;
+
+    let (_, mut distribution) = load_open_distributions(deps.storage, &env)?
+        .into_iter()
+        .find(|(id, _)| *id == dist_id)
+        .ok_or(ContractError::ClaimingWindowHasClosed {})?;
+    let mut set_rewards = distribution
+        .set_rewards
+        .clone()
+        .ok_or(ContractError::NotASetDistribution {})?;
+
+    // Every token of a set sits at the position of its component
+    let mut token_ids_by_collection: BTreeMap<Addr, Vec<String>> = BTreeMap::new();
+    for set in sets.iter() {
+        ensure!(
+            set.len() == set_rewards.components.len(),
+            ContractError::InvalidSet {}
+        );
+        for (token, component) in set.iter().zip(set_rewards.components.iter()) {
+            ensure!(
+                token.collection == component.collection.as_str(),
+                ContractError::InvalidSet {}
+            );
+            token_ids_by_collection
+                .entry(component.collection.clone())
+                .or_default()
+                .push(token.token_id.clone());
+        }
+    }
+
+    // Intersection between provided and actually owned, by collection
+    let mut owned_tokens: BTreeMap<Addr, Vec<String>> = BTreeMap::new();
+    for (collection, token_ids) in token_ids_by_collection {
+        let validated_tokens = query_owned_tokens(
+            &deps,
+            &collection.to_string(),
+            info.sender.as_ref(),
+            token_ids,
+            None,
+            bulk,
+        )?;
+        owned_tokens.insert(collection, validated_tokens.found);
+    }
+
+    let mut completed_sets = 0u64;
+    let mut incomplete_sets: Vec<String> = vec![];
+    for (set_index, set) in sets.iter().enumerate() {
+        if set_rewards.claimed_sets + completed_sets >= set_rewards.max_sets {
+            incomplete_sets.push(set_index.to_string());
+            continue;
+        }
+        let mut complete = true;
+        for (token, component) in set.iter().zip(set_rewards.components.iter()) {
+            let owned = matches!(
+                owned_tokens.get(&component.collection),
+                Some(token_ids) if token_ids.contains(&token.token_id)
+            );
+            let used = if component.collection == config.nft_address {
+                is_claimed(
+                    deps.storage,
+                    &config.claim_storage,
+                    dist_id,
+                    &token.token_id,
+                )?
+            } else {
+                COLLECTION_CLAIMS.has(
+                    deps.storage,
+                    (dist_id, &component.collection, &token.token_id),
+                )
+            };
+            if !owned || used {
+                complete = false;
+                break;
+            }
+            if !component.traits.is_empty() {
+                let traits = if component.collection == config.nft_address {
+                    load_token_traits(
+                        deps.storage,
+                        &deps.querier,
+                        config.nft_address.as_str(),
+                        &token.token_id,
+                    )?
+                } else {
+                    query_token_traits(
+                        &deps.querier,
+                        component.collection.as_str(),
+                        &token.token_id,
+                    )?
+                };
+                if !component
+                    .traits
+                    .iter()
+                    .all(|required| traits.contains(required))
+                {
+                    complete = false;
+                    break;
+                }
+            }
+        }
+        // The same token cannot complete two sets, they are marked as used right away
+        let distinct_tokens = set.iter().enumerate().all(|(position, token)| {
+            set[..position].iter().all(|previous| {
+                previous.collection != token.collection || previous.token_id != token.token_id
+            })
+        });
+        if !complete || !distinct_tokens {
+            incomplete_sets.push(set_index.to_string());
+            continue;
+        }
+        for (token, component) in set.iter().zip(set_rewards.components.iter()) {
+            if component.collection == config.nft_address {
+                mark_claimed(
+                    deps.storage,
+                    &config.claim_storage,
+                    dist_id,
+                    std::slice::from_ref(&token.token_id),
+                )?;
+            } else {
+                COLLECTION_CLAIMS.save(
+                    deps.storage,
+                    (dist_id, &component.collection, &token.token_id),
+                    &true,
+                )?;
+            }
+        }
+        completed_sets += 1;
+    }
+    ensure!(completed_sets > 0, ContractError::NothingToClaim {});
+
+    set_rewards.claimed_sets += completed_sets;
+    distribution.set_rewards = Some(set_rewards);
+    DISTRIBUTIONS.save(deps.storage, dist_id, &distribution)?;
+    track_wallet_claim(
+        deps.storage,
+        dist_id,
+        &distribution,
+        info.sender.as_str(),
+        completed_sets,
+    )?;
+
+    response = response
+        .add_messages(create_send_assets_messages(
+            &distribution,
+            info.sender.as_str(),
+            completed_sets,
+        )?)
+        .add_attribute("dist_ids", dist_id.to_string())
+        .add_attribute("sets", completed_sets.to_string());
+    if !incomplete_sets.is_empty() {
+        response = response.add_attribute("Sets not completed: ", incomplete_sets.join(", "));
+    }
+    Ok(response)
+}
+
+pub fn claim_with_proofs(
+    mut deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    dist_id: u64,
+    claims: Vec<MerkleClaim>,
+) -> Result<Response, ContractError> {
+    // Same limits as the other claims, owners are checked like BulkClaim above five tokens
+    ensure!(claims.len() <= 500, ContractError::TooManyTokensSent {});
+    let bulk = claims.len() > 5;
+
+    let mut config = CONFIG.load(deps.storage)?;
+    ensure_not_paused(&config, &PauseScope::Claim, &env)?;
+
+    let mut response = sync_config(deps.branch(), &env, &mut config)?;
+
+    let (_, mut distribution) = load_open_distributions(deps.storage, &env)?
+        .into_iter()
+        .find(|(id, _)| *id == dist_id)
+        .ok_or(ContractError::ClaimingWindowHasClosed {})?;
+    let merkle_root = distribution
+        .merkle_root
+        .clone()
+        .ok_or(ContractError::NotAMerkleDistribution {})?;
+
+    let nfts_held = query_nfts_held(
+        &deps,
+        config.nft_address.as_ref(),
+        info.sender.as_ref(),
+        distribution.minimum_nfts_to_claim,
+    )?;
+    ensure!(
+        nfts_held >= distribution.minimum_nfts_to_claim,
+        ContractError::NotEnoughNftsToClaim {}
+    );
+
+    for claim in claims.iter() {
+        verify_merkle_proof(&merkle_root, &claim.token_id, &claim.amounts, &claim.proof)?;
+    }
+
+    // Intersection between provided and actually owned
+    let validated_tokens = query_owned_tokens(
+        &deps,
+        &config.nft_address.to_string(),
+        info.sender.as_ref(),
+        claims.iter().map(|claim| claim.token_id.clone()).collect(),
+        None,
+        bulk,
+    )?;
+    let newly_claimed = mark_claimed(
+        deps.storage,
+        &config.claim_storage,
+        dist_id,
+        &validated_tokens.found,
+    )?;
+    ensure!(!newly_claimed.is_empty(), ContractError::NothingToClaim {});
+
+    let mut to_send: Vec<Asset> = vec![];
+    for token_id in newly_claimed.iter() {
+        if let Some(claim) = claims.iter().find(|claim| &claim.token_id == token_id) {
+            for amount in claim.amounts.iter() {
+                add_asset(&mut to_send, amount);
+            }
+        }
+    }
+    for asset in to_send.iter() {
+        add_asset(&mut distribution.paid, asset);
+    }
+    // Proofs cannot pay more than what was committed
+    ensure!(
+        distribution.paid.iter().all(|paid| distribution
+            .assets
+            .iter()
+            .any(|total| total.info == paid.info && total.amount >= paid.amount)),
+        ContractError::InvalidClaimValue {}
+    );
+    distribution.claimed += newly_claimed.len() as u64;
+    DISTRIBUTIONS.save(deps.storage, dist_id, &distribution)?;
+    track_wallet_claim(
+        deps.storage,
+        dist_id,
+        &distribution,
+        info.sender.as_str(),
+        newly_claimed.len() as u64,
+    )?;
+
+    for asset in to_send.iter().filter(|asset| !asset.amount.is_zero()) {
+        response = response.add_message(transfer_asset_msg(asset, info.sender.as_str())?);
+    }
+    response = response.add_attribute("dist_ids", dist_id.to_string());
+    if !validated_tokens.not_found.is_empty() {
+        response = response.add_attribute(
+            "Tokens not found or not owned: ".to_string(),
+            validated_tokens.not_found.join(", "),
+        );
+    }
+    Ok(response)
+}
+
+pub fn claim_from_snapshot(
+    mut deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    dist_id: u64,
+    claims: Vec<SnapshotClaim>,
+) -> Result<Response, ContractError> {
+    ensure!(claims.len() <= 500, ContractError::TooManyTokensSent {});
+
+    let mut config = CONFIG.load(deps.storage)?;
+    ensure_not_paused(&config, &PauseScope::Claim, &env)?;
+
+    let mut response = sync_config(deps.branch(), &env, &mut config)?;
+
+    let (_, distribution) = load_open_distributions(deps.storage, &env)?
+        .into_iter()
+        .find(|(id, _)| *id == dist_id)
+        .ok_or(ContractError::ClaimingWindowHasClosed {})?;
+    let snapshot = distribution
+        .snapshot
+        .clone()
+        .ok_or(ContractError::NotASnapshotDistribution {})?;
+
+    // Tokens owned by the claimer at the snapshot time, whoever holds them now
+    let mut owned_tokens: Vec<String> = Vec::with_capacity(claims.len());
+    let mut not_owned_tokens: Vec<String> = vec![];
+    for claim in claims.iter() {
+        let owned = match &snapshot.merkle_root {
+            Some(merkle_root) => {
+                verify_merkle_leaf(
+                    merkle_root,
+                    &format!("{}:{}", claim.token_id, info.sender),
+                    &claim.proof,
+                )?;
+                true
+            }
+            None => {
+                SNAPSHOT_OWNERS.may_load(deps.storage, (snapshot.snapshot_id, &claim.token_id))?
+                    == Some(info.sender.clone())
+            }
+        };
+        if owned {
+            owned_tokens.push(claim.token_id.clone());
+        } else {
+            not_owned_tokens.push(claim.token_id.clone());
         }
     }
+    ensure!(!owned_tokens.is_empty(), ContractError::NothingToClaim {});
+    // The holding requirement is checked against the snapshot tokens of the claim
+    ensure!(
+        owned_tokens.len() as u64 >= distribution.minimum_nfts_to_claim,
+        ContractError::NotEnoughNftsToClaim {}
+    );
 
-    if dist_count < 1 {
-        return Err(ContractError::NothingToClaim {});
+    let allowed = wallet_allowance(
+        deps.storage,
+        &config.claim_storage,
+        dist_id,
+        &distribution,
+        info.sender.as_str(),
+        eligible_tokens(distribution.max_token_id, &owned_tokens),
+    )?
+    .ok_or(ContractError::WalletCapReached {})?;
+    let newly_claimed = mark_claimed(deps.storage, &config.claim_storage, dist_id, &allowed)?;
+    ensure!(!newly_claimed.is_empty(), ContractError::NothingToClaim {});
+    response = response.add_messages(pay_claimed_tokens(
+        &mut deps,
+        &config,
+        dist_id,
+        distribution,
+        &newly_claimed,
+        info.sender.as_str(),
+    )?);
+
+    response = response.add_attribute("dist_ids", dist_id.to_string());
+    if !not_owned_tokens.is_empty() {
+        response = response.add_attribute(
+            "Tokens not owned at the snapshot: ".to_string(),
+            not_owned_tokens.join(", "),
+        );
     }
+    Ok(response)
+}
+
+pub fn claim_bonus(
+    mut deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    dist_id: u64,
+    token_ids: Vec<String>,
+) -> Result<Response, ContractError> {
+    ensure!(token_ids.len() <= 500, ContractError::TooManyTokensSent {});
+
+    let mut config = CONFIG.load(deps.storage)?;
+    ensure_not_paused(&config, &PauseScope::Claim, &env)?;
+
+    let response = sync_config(deps.branch(), &env, &mut config)?;
+
+    let mut distribution = DISTRIBUTIONS.load(deps.storage, dist_id)?;
+    let UnclaimedPolicy::BonusRound { end_time } = distribution.unclaimed_policy else {
+        return Err(ContractError::NotABonusRoundDistribution {});
+    };
+    // Open between the end of the claiming window and its own end time
+    ensure!(
+        env.block.time > distribution.end_time
+            && env.block.time <= end_time
+            && !distribution.unclaimed_settled,
+        ContractError::BonusRoundIsNotOpen {}
+    );
+    let mut bonus_round = match distribution.bonus_round.take() {
+        Some(bonus_round) => bonus_round,
+        None => open_bonus_round(&distribution),
+    };
 
-    distribution.claimed += dist_count;
+    // Tokens which claimed in the window, held by the claimer now
+    let validated_tokens = query_owned_tokens(
+        &deps,
+        &config.nft_address.to_string(),
+        info.sender.as_ref(),
+        token_ids,
+        None,
+        true,
+    )?;
+    let mut newly_claimed = 0u64;
+    for token_id in validated_tokens.found.iter() {
+        if is_claimed(deps.storage, &config.claim_storage, dist_id, token_id)?
+            && !BONUS_CLAIMS.has(deps.storage, (dist_id, token_id))
+        {
+            BONUS_CLAIMS.save(deps.storage, (dist_id, token_id), &true)?;
+            newly_claimed += 1;
+        }
+    }
+    ensure!(newly_claimed > 0, ContractError::NothingToClaim {});
 
-    DISTRIBUTION.save(deps.storage, &distribution)?;
+    bonus_round.claimed += newly_claimed;
+    let to_send: Vec<Asset> = bonus_round
+        .assets_per_token
+        .iter()
+        .map(|asset| Asset {
+            info: asset.info.clone(),
+            amount: asset.amount * Uint128::new(newly_claimed as u128),
+        })
+        .filter(|asset| !asset.amount.is_zero())
+        .collect();
+    distribution.bonus_round = Some(bonus_round);
+    DISTRIBUTIONS.save(deps.storage, dist_id, &distribution)?;
 
-    let messages = create_send_assets_messages(&distribution, info.sender.as_str(), dist_count);
-    let mut response = Response::new().add_messages(messages);
+    let mut response = response
+        .add_attribute("dist_ids", dist_id.to_string())
+        .add_attribute("bonus_claimed", newly_claimed.to_string());
+    for asset in to_send.iter() {
+        response = response.add_message(transfer_asset_msg(asset, info.sender.as_str())?);
+    }
     if !validated_tokens.not_found.is_empty() {
-        // Returns an answer if the required tokens were owned or not
         response = response.add_attribute(
             "Tokens not found or not owned: ".to_string(),
             validated_tokens.not_found.join(", "),
@@ -81,20 +823,59 @@ pub fn claim_rewards(
     Ok(response)
 }
 
+pub fn claim_pro_rata(
+    mut deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    dist_id: u64,
+) -> Result<Response, ContractError> {
+    let mut config = CONFIG.load(deps.storage)?;
+    ensure_not_paused(&config, &PauseScope::Claim, &env)?;
+
+    let response = sync_config(deps.branch(), &env, &mut config)?;
+
+    // Set once the unclaimed of the distribution was settled
+    let shares = PRO_RATA_SHARES
+        .may_load(deps.storage, dist_id)?
+        .ok_or(ContractError::NothingToClaim {})?;
+    // The wallet claim is removed once its share is paid
+    let wallet_claim = WALLET_CLAIMS
+        .may_load(deps.storage, (dist_id, info.sender.as_str()))?
+        .ok_or(ContractError::NothingToClaim {})?;
+    WALLET_CLAIMS.remove(deps.storage, (dist_id, info.sender.as_str()));
+    ensure!(wallet_claim.tokens > 0, ContractError::NothingToClaim {});
+
+    let mut response = response
+        .add_attribute("dist_ids", dist_id.to_string())
+        .add_attribute("pro_rata_tokens", wallet_claim.tokens.to_string());
+    for share in shares.iter() {
+        response = response.add_message(transfer_asset_msg(
+            &Asset {
+                info: share.info.clone(),
+                amount: share.amount * Uint128::new(wallet_claim.tokens as u128),
+            },
+            info.sender.as_str(),
+        )?);
+    }
+    Ok(response)
+}
+
 pub fn return_unclaimed(
-    deps: DepsMut,
+    mut deps: DepsMut,
     env: Env,
     info: MessageInfo,
+    dist_id: Option<u64>,
 ) -> Result<Response, ContractError> {
-    // Returns the unclaimed amounts to to distributor if it wasnt entirely claimed
+    // Carries out the unclaimed policy if it wasnt entirely claimed
     // Need the distribution to have ended
-    let config = CONFIG.load(deps.storage)?;
-    ensure!(
-        !config.current_dist_halted,
-        ContractError::DistributionHalted {}
-    );
+    let mut config = CONFIG.load(deps.storage)?;
+    ensure_not_paused(&config, &PauseScope::Return, &env)?;
+    let mut response = sync_config(deps.branch(), &env, &mut config)?;
+    // Defaults to the latest distribution
+    let dist_id = dist_id.unwrap_or(config.current_dist_id);
+    let mut current_dist = DISTRIBUTIONS.load(deps.storage, dist_id)?;
     ensure!(
-        env.block.time > config.current_dist_end_time.unwrap(),
+        env.block.time > current_dist.end_time,
         ContractError::CurrentDistributionHasNotYetEnded {}
     );
     ensure!(
@@ -102,174 +883,1549 @@ pub fn return_unclaimed(
         ContractError::Unauthorized {}
     );
 
-    let mut current_dist = DISTRIBUTION.load(deps.storage)?;
     ensure!(
-        current_dist.unclaimed_to_distributor,
+        current_dist.unclaimed_policy != UnclaimedPolicy::Rollover,
         ContractError::CurrentUnclaimedGoesToTheSubsequentDistribution {}
     );
+    if let UnclaimedPolicy::BonusRound { end_time } = current_dist.unclaimed_policy {
+        ensure!(
+            env.block.time > end_time,
+            ContractError::BonusRoundHasNotYetEnded {}
+        );
+    }
     ensure!(
-        !current_dist.unclaimed_sent_to_distributor.unwrap(),
+        !current_dist.unclaimed_settled,
         ContractError::UnclaimedWasAlreadyReturned {}
     );
+    // Tokens may all have claimed and still leave assets, e.g. tiers nobody reached
+    let unclaimed = unclaimed_assets(&current_dist);
+    let nfts_left = match &current_dist.nft_rewards {
+        Some(nft_rewards) => NFT_POOLS
+            .prefix(nft_rewards.pool_id)
+            .keys(deps.storage, None, None, Order::Ascending)
+            .next()
+            .is_some(),
+        None => false,
+    };
     ensure!(
-        current_dist.claimed < config.nft_count,
+        !unclaimed.is_empty() || nfts_left,
         ContractError::NothingToReturn {}
     );
 
     // Can return the unclaimed
-    let remaining_to_dist = config.nft_count - current_dist.claimed;
-    let mut response = Response::new();
-    for curr_dist in &current_dist.assets {
-        let remain_contract = deps
-            .querier
-            .query_balance(env.contract.address.as_str(), &curr_dist.denom)?;
+    for unclaimed in unclaimed.iter() {
+        let remain_contract = query_asset_balance(
+            &deps.querier,
+            &unclaimed.info,
+            env.contract.address.as_str(),
+        )?;
         ensure!(
-            remain_contract.amount >= Uint128::new(remaining_to_dist as u128) * curr_dist.amount,
+            remain_contract >= unclaimed.amount,
             ContractError::InvalidClaimValue {}
         );
-        response = response.add_message(BankMsg::Send {
-            to_address: config.distributor.to_string(),
-            amount: vec![Coin {
-                denom: remain_contract.denom.clone(),
-                amount: Uint128::new(remaining_to_dist as u128) * curr_dist.amount,
-            }],
-        });
+    }
+    response = response.add_messages(settle_unclaimed(
+        deps.storage,
+        &config,
+        dist_id,
+        &current_dist,
+        &unclaimed,
+    )?);
+    // Unclaimed NFTs always go back to the distributor
+    if let Some(nft_rewards) = &current_dist.nft_rewards {
+        response = response.add_messages(take_nft_rewards(
+            deps.storage,
+            nft_rewards,
+            config.distributor.as_str(),
+            None,
+        )?);
     }
 
-    current_dist.unclaimed_sent_to_distributor = Some(true);
-    DISTRIBUTION.save(deps.storage, &current_dist)?;
+    current_dist.unclaimed_settled = true;
+    DISTRIBUTIONS.save(deps.storage, dist_id, &current_dist)?;
+    ACTIVE_DISTRIBUTIONS.remove(deps.storage, dist_id);
 
     Ok(response)
 }
 
+#[allow(clippy::too_many_arguments)]
 pub fn add_distribution(
     deps: DepsMut,
     env: Env,
-    info: MessageInfo,
-    mut assets_per_nft: Vec<Coin>,
+    sender: Addr,
+    funds: Vec<Asset>,
+    assets_per_nft: Vec<Asset>,
+    distribution_end_time: Timestamp,
+    unclaimed_policy: UnclaimedPolicy,
+    minimum_nfts_to_claim: u64,
+    start_time: Option<Timestamp>,
+    max_token_id: Option<u64>,
+    snapshot_id: Option<u64>,
+    payout: Payout,
+) -> Result<Response, ContractError> {
+    let mut config = CONFIG.load(deps.storage)?;
+    refresh_nft_count(&deps, &mut config)?;
+    fund_distribution(
+        deps,
+        env,
+        config,
+        sender,
+        funds,
+        assets_per_nft,
+        distribution_end_time,
+        unclaimed_policy,
+        minimum_nfts_to_claim,
+        start_time,
+        max_token_id,
+        snapshot_id,
+        payout,
+    )
+}
+
+// Same as add_distribution with the config already loaded and its nft_count refreshed
+#[allow(clippy::too_many_arguments)]
+fn fund_distribution(
+    mut deps: DepsMut,
+    env: Env,
+    mut config: Config,
+    sender: Addr,
+    mut funds: Vec<Asset>,
+    assets_per_nft: Vec<Asset>,
     distribution_end_time: Timestamp,
-    unclaimed_to_distributor: bool,
+    unclaimed_policy: UnclaimedPolicy,
     minimum_nfts_to_claim: u64,
+    start_time: Option<Timestamp>,
+    max_token_id: Option<u64>,
+    snapshot_id: Option<u64>,
+    payout: Payout,
 ) -> Result<Response, ContractError> {
     ensure!(
         distribution_end_time > env.block.time,
         ContractError::InvalidDistributionEndTime {}
     );
-    let mut config = CONFIG.load(deps.storage)?;
-    if config.current_dist_halted {
-        return Err(ContractError::DistributionHalted {});
-    }
-    if config.current_dist_end_time.is_some() {
-        let prev_dist = config.current_dist_end_time.unwrap();
-        ensure!(
-            prev_dist < env.block.time,
-            ContractError::CurrentDistributionHasNotYetEnded {}
-        );
-        ensure!(
-            prev_dist < distribution_end_time,
-            ContractError::InvalidDistributionEndTime {}
-        );
-    }
-    let previous_dist = DISTRIBUTION
-        .load(deps.storage)
-        .unwrap_or_else(|_| Distribution {
-            assets: vec![],
-            claimed: 0,
-            unclaimed_to_distributor: false,
-            unclaimed_sent_to_distributor: None,
-            minimum_nfts_to_claim: 0,
-        });
+    validate_unclaimed_policy(&deps, &unclaimed_policy, distribution_end_time)?;
+    ensure_not_paused(&config, &PauseScope::Fund, &env)?;
+    let nft_count = config.nft_count;
+    // Merkle and set distributions already name the paid tokens
+    let max_token_id = match (max_token_id, &payout) {
+        (Some(max_token_id), _) => Some(max_token_id),
+        (None, Payout::Merkle { .. } | Payout::Sets { .. }) => None,
+        (None, _) => query_highest_token_id(&deps.querier, config.nft_address.as_str(), nft_count),
+    };
+    let snapshot = load_snapshot(deps.storage, snapshot_id)?;
+    let tiers = match &payout {
+        Payout::Tiered { tiers } => {
+            // Every wallet allowed to claim falls in a tier
+            let lowest_tier = tiers.iter().map(|tier| tier.min_nfts).min();
+            ensure!(
+                matches!(lowest_tier, Some(min_nfts) if min_nfts <= minimum_nfts_to_claim.max(1)),
+                ContractError::InvalidTiers {}
+            );
+            Some(tiers.clone())
+        }
+        _ => None,
+    };
+    let wallet_cap = match &payout {
+        Payout::Flat {
+            max_tokens_per_wallet,
+            max_amount_per_wallet,
+        } if max_tokens_per_wallet.is_some() || max_amount_per_wallet.is_some() => {
+            // The amount caps are set on distributed assets
+            ensure!(
+                *max_tokens_per_wallet != Some(0)
+                    && max_amount_per_wallet
+                        .iter()
+                        .flatten()
+                        .all(|cap| !cap.amount.is_zero()
+                            && assets_per_nft.iter().any(|asset| asset.info == cap.info)),
+                ContractError::InvalidWalletCap {}
+            );
+            Some(WalletCap {
+                max_tokens_per_wallet: *max_tokens_per_wallet,
+                max_amount_per_wallet: max_amount_per_wallet.clone(),
+            })
+        }
+        _ => None,
+    };
+    let (merkle_root, total_weight, trait_multipliers, set_rewards) = match payout {
+        Payout::Flat { .. } | Payout::Tiered { .. } => (None, None, None, None),
+        Payout::Merkle { merkle_root } => {
+            decode_merkle_root(&merkle_root)?;
+            (Some(merkle_root), None, None, None)
+        }
+        Payout::Weighted => {
+            let weight_table = WEIGHT_TABLE.may_load(deps.storage)?.unwrap_or_default();
+            ensure!(weight_table.frozen, ContractError::WeightsNotFrozen {});
+            (None, Some(weight_table.total_weight), None, None)
+        }
+        Payout::Traits { total_weight } => {
+            ensure!(total_weight > 0, ContractError::InvalidWeights {});
+            let trait_multipliers = TRAIT_MULTIPLIERS
+                .may_load(deps.storage)?
+                .unwrap_or_default();
+            (None, Some(total_weight), Some(trait_multipliers), None)
+        }
+        Payout::Sets {
+            components,
+            max_sets,
+        } => {
+            ensure!(
+                !components.is_empty() && max_sets > 0,
+                ContractError::InvalidSet {}
+            );
+            let mut set_components: Vec<SetComponent> = Vec::with_capacity(components.len());
+            for component in components {
+                let collection = deps.api.addr_validate(&component.collection)?;
+                ensure!(
+                    collection == config.nft_address || COLLECTIONS.has(deps.storage, &collection),
+                    ContractError::UnknownCollection {}
+                );
+                set_components.push(SetComponent {
+                    collection,
+                    traits: component.traits,
+                });
+            }
+            let set_rewards = SetRewards {
+                components: set_components,
+                max_sets,
+                claimed_sets: 0,
+            };
+            (None, None, None, Some(set_rewards))
+        }
+    };
+    // Merkle distributions are funded with their committed totals, weighted ones per unit of weight
+    // and set ones per set
+    let funded_count = match (&merkle_root, &set_rewards) {
+        (Some(_), _) => 1,
+        (None, Some(set_rewards)) => set_rewards.max_sets,
+        (None, None) => total_weight.unwrap_or(config.nft_count),
+    };
+
+    // Tiered distributions are funded as if every token was paid the highest multiplier
+    let funded_assets: Vec<Asset> = match &tiers {
+        Some(tiers) => assets_per_nft
+            .iter()
+            .map(|asset| Asset {
+                info: asset.info.clone(),
+                amount: asset.amount * highest_tier_multiplier(tiers),
+            })
+            .collect(),
+        None => assets_per_nft.clone(),
+    };
+
+    let used_deposits = add_cw20_deposits(&deps, &funded_assets, &mut funds)?;
 
-    validate_distribution(
+    let funding = validate_distribution(
         config.distributor.as_str(),
-        &info,
-        &assets_per_nft,
-        &Uint128::new(config.nft_count as u128),
+        &sender,
+        &funds,
+        &funded_assets,
+        &Uint128::new(funded_count as u128),
     )?;
 
-    config.current_dist_id += 1;
-    config.current_dist_end_time = Some(distribution_end_time);
+    for contract_addr in used_deposits.iter() {
+        CW20_DEPOSITS.remove(deps.storage, contract_addr);
+    }
+
+    let response = schedule_distribution(
+        deps.branch(),
+        &env,
+        &mut config,
+        QueuedDistribution {
+            assets_per_nft,
+            funds: funding.required.clone(),
+            start_time: start_time.unwrap_or(env.block.time),
+            end_time: distribution_end_time,
+            nft_count,
+            max_token_id,
+            unclaimed_policy,
+            minimum_nfts_to_claim,
+            nft_rewards: None,
+            merkle_root,
+            total_weight,
+            trait_multipliers,
+            snapshot,
+            collection_rewards: vec![],
+            set_rewards,
+            tiers,
+            wallet_cap,
+        },
+    )?;
+    CONFIG.save(deps.storage, &config)?;
+
+    Ok(funding_response(response, &funding, &sender)?)
+}
 
-    let remaining_to_dist = config.nft_count - previous_dist.claimed;
+// Raises the per NFT amounts of the current distribution, or adds assets to them
+pub fn top_up_distribution(
+    mut deps: DepsMut,
+    env: Env,
+    sender: Addr,
+    mut funds: Vec<Asset>,
+    assets_per_nft: Vec<Asset>,
+) -> Result<Response, ContractError> {
+    let mut config = CONFIG.load(deps.storage)?;
+    ensure_not_paused(&config, &PauseScope::Fund, &env)?;
+    let response = sync_config(deps.branch(), &env, &mut config)?;
 
-    let mut new_assets_per_nft: Vec<Coin> = Vec::with_capacity(assets_per_nft.len());
+    let dist_id = config.current_dist_id;
+    let mut distribution = DISTRIBUTIONS
+        .may_load(deps.storage, dist_id)?
+        .unwrap_or_default();
+    ensure!(
+        ACTIVE_DISTRIBUTIONS.has(deps.storage, dist_id) && env.block.time <= distribution.end_time,
+        ContractError::ClaimingWindowHasClosed {}
+    );
+    ensure!(
+        distribution.can_be_topped_up(),
+        ContractError::CannotTopUpDistribution {}
+    );
+    ensure!(
+        assets_per_nft.iter().all(|asset| !asset.amount.is_zero()),
+        ContractError::InvalidDistributionInputs {}
+    );
 
-    let mut response: Response = Response::new();
+    // Funded for every token the distribution was funded for
+    let used_deposits = add_cw20_deposits(&deps, &assets_per_nft, &mut funds)?;
+    let funding = validate_distribution(
+        config.distributor.as_str(),
+        &sender,
+        &funds,
+        &assets_per_nft,
+        &Uint128::new(distribution.nft_count as u128),
+    )?;
+    for contract_addr in used_deposits.iter() {
+        CW20_DEPOSITS.remove(deps.storage, contract_addr);
+    }
 
-    for prev_dist in &previous_dist.assets {
-        let remain_contract = deps
-            .querier
-            .query_balance(env.contract.address.as_str(), &prev_dist.denom)?;
-        let zero_coin = &Coin {
-            denom: prev_dist.denom.clone(),
-            amount: Uint128::zero(),
-        };
-        let being_sent_validated = info
-            .funds
+    // From the first top up on, what was paid is tracked to pay the tokens which already claimed
+    if distribution.topped_up_from.is_none() {
+        let claimed = Uint128::new(distribution.claimed as u128);
+        distribution.paid = distribution
+            .assets
             .iter()
-            .find(|coin| coin.denom == prev_dist.denom)
-            .unwrap_or(zero_coin);
-        let rem_amount = remain_contract.amount - being_sent_validated.amount;
-        if previous_dist.unclaimed_to_distributor
-            && previous_dist.unclaimed_sent_to_distributor == Some(false)
-        {
-            response = response.add_message(BankMsg::Send {
-                to_address: config.distributor.to_string(),
-                amount: vec![Coin {
-                    denom: remain_contract.denom.clone(),
-                    amount: Uint128::new(remaining_to_dist as u128) * prev_dist.amount,
-                }],
+            .map(|asset| Asset {
+                info: asset.info.clone(),
+                amount: asset.amount * claimed,
+            })
+            .collect();
+        distribution.topped_up_from = Some(distribution.assets.clone());
+    }
+    for asset in assets_per_nft.iter() {
+        add_asset(&mut distribution.assets, asset);
+    }
+    DISTRIBUTIONS.save(deps.storage, dist_id, &distribution)?;
+
+    let response = response
+        .add_attribute("action", "top_up")
+        .add_attribute("dist_id", dist_id.to_string());
+    Ok(funding_response(response, &funding, &sender)?)
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn add_pool_distribution(
+    mut deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    distribution_end_time: Timestamp,
+    unclaimed_policy: UnclaimedPolicy,
+    minimum_nfts_to_claim: u64,
+    start_time: Option<Timestamp>,
+    snapshot_id: Option<u64>,
+) -> Result<Response, ContractError> {
+    let mut config = CONFIG.load(deps.storage)?;
+    refresh_nft_count(&deps, &mut config)?;
+    let nft_count = Uint128::new(config.nft_count as u128);
+
+    // floor(total / eligible tokens) per NFT
+    let mut assets_per_nft: Vec<Asset> = Vec::with_capacity(info.funds.len());
+    let mut funds: Vec<Asset> = Vec::with_capacity(info.funds.len());
+    let mut dust: Vec<Asset> = vec![];
+    for coin in info.funds {
+        let amount_per_nft = coin.amount.checked_div(nft_count).unwrap_or_default();
+        ensure!(
+            !amount_per_nft.is_zero(),
+            ContractError::InvalidFundsReceived {}
+        );
+        assets_per_nft.push(Asset::native(&coin.denom, amount_per_nft));
+        funds.push(Asset::native(&coin.denom, amount_per_nft * nft_count));
+        dust.push(Asset::native(
+            &coin.denom,
+            coin.amount - amount_per_nft * nft_count,
+        ));
+    }
+    dust.retain(|asset| !asset.amount.is_zero());
+
+    let response = fund_distribution(
+        deps.branch(),
+        env,
+        config,
+        info.sender,
+        funds,
+        assets_per_nft.clone(),
+        distribution_end_time,
+        unclaimed_policy,
+        minimum_nfts_to_claim,
+        start_time,
+        None,
+        snapshot_id,
+        Payout::Flat {
+            max_tokens_per_wallet: None,
+            max_amount_per_wallet: None,
+        },
+    )?;
+    // Added once the distribution is scheduled as activating it may roll the dust over
+    let mut total_dust = DUST.may_load(deps.storage)?.unwrap_or_default();
+    for asset in dust.iter() {
+        add_asset(&mut total_dust, asset);
+    }
+    DUST.save(deps.storage, &total_dust)?;
+
+    Ok(response.set_data(to_binary(&DistributePoolResponse {
+        assets_per_nft,
+        dust,
+    })?))
+}
+
+pub fn add_collections_distribution(
+    mut deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    collections: Vec<CollectionAssets>,
+    distribution_end_time: Timestamp,
+    unclaimed_policy: UnclaimedPolicy,
+    start_time: Option<Timestamp>,
+) -> Result<Response, ContractError> {
+    ensure!(
+        distribution_end_time > env.block.time,
+        ContractError::InvalidDistributionEndTime {}
+    );
+    validate_unclaimed_policy(&deps, &unclaimed_policy, distribution_end_time)?;
+    let mut config = CONFIG.load(deps.storage)?;
+    ensure_not_paused(&config, &PauseScope::Fund, &env)?;
+    refresh_nft_count(&deps, &mut config)?;
+    let nft_count = config.nft_count;
+
+    // The configured collection keeps the usual per NFT amounts, the others their own
+    let mut assets_per_nft: Vec<Asset> = vec![];
+    let mut minimum_nfts_to_claim = 0;
+    let mut max_token_id = None;
+    let mut collection_rewards: Vec<CollectionRewards> = vec![];
+    let mut total_assets: Vec<Asset> = vec![];
+    let mut seen_collections: Vec<Addr> = vec![];
+    for collection_assets in collections {
+        let collection = deps.api.addr_validate(&collection_assets.collection)?;
+        ensure!(
+            !seen_collections.contains(&collection),
+            ContractError::InvalidDistributionInputs {}
+        );
+        seen_collections.push(collection.clone());
+        let collection_count = if collection == config.nft_address {
+            assets_per_nft = collection_assets.assets_per_nft.clone();
+            minimum_nfts_to_claim = collection_assets.minimum_nfts_to_claim;
+            max_token_id = collection_assets
+                .max_token_id
+                .or_else(|| query_highest_token_id(&deps.querier, collection.as_str(), nft_count));
+            nft_count
+        } else {
+            let mut collection_info = COLLECTIONS
+                .may_load(deps.storage, &collection)?
+                .ok_or(ContractError::UnknownCollection {})?;
+            collection_info.nft_count = query_nft_count(&deps.querier, collection.as_str())?;
+            COLLECTIONS.save(deps.storage, &collection, &collection_info)?;
+            let max_token_id = collection_assets.max_token_id.or_else(|| {
+                query_highest_token_id(
+                    &deps.querier,
+                    collection.as_str(),
+                    collection_info.nft_count,
+                )
+            });
+            collection_rewards.push(CollectionRewards {
+                collection,
+                assets: collection_assets.assets_per_nft.clone(),
+                minimum_nfts_to_claim: collection_assets.minimum_nfts_to_claim,
+                nft_count: collection_info.nft_count,
+                max_token_id,
+                claimed: 0,
             });
+            collection_info.nft_count
+        };
+        for asset in collection_assets.assets_per_nft.iter() {
+            add_asset(
+                &mut total_assets,
+                &Asset {
+                    info: asset.info.clone(),
+                    amount: asset.amount * Uint128::new(collection_count as u128),
+                },
+            );
+        }
+    }
+
+    let mut funds: Vec<Asset> = info.funds.into_iter().map(Asset::from).collect();
+    let used_deposits = add_cw20_deposits(&deps, &total_assets, &mut funds)?;
+    let funding = validate_distribution(
+        config.distributor.as_str(),
+        &info.sender,
+        &funds,
+        &total_assets,
+        &Uint128::one(),
+    )?;
+    for contract_addr in used_deposits.iter() {
+        CW20_DEPOSITS.remove(deps.storage, contract_addr);
+    }
+
+    // The snapshot only lists the owners of the configured collection
+    let response = schedule_distribution(
+        deps.branch(),
+        &env,
+        &mut config,
+        QueuedDistribution {
+            assets_per_nft,
+            funds: funding.required.clone(),
+            start_time: start_time.unwrap_or(env.block.time),
+            end_time: distribution_end_time,
+            nft_count,
+            max_token_id,
+            unclaimed_policy,
+            minimum_nfts_to_claim,
+            nft_rewards: None,
+            merkle_root: None,
+            total_weight: None,
+            trait_multipliers: None,
+            snapshot: None,
+            collection_rewards,
+            set_rewards: None,
+            tiers: None,
+            wallet_cap: None,
+        },
+    )?;
+    CONFIG.save(deps.storage, &config)?;
+
+    Ok(funding_response(response, &funding, &info.sender)?)
+}
+
+pub fn add_collection(
+    deps: DepsMut,
+    info: MessageInfo,
+    collection: String,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    ensure!(info.sender == config.admin, ContractError::Unauthorized {});
+    let collection = deps.api.addr_validate(&collection)?;
+    ensure!(
+        collection != config.nft_address && !COLLECTIONS.has(deps.storage, &collection),
+        ContractError::InvalidDistributionInputs {}
+    );
+    // Counts are queried when a distribution pays the collection
+    COLLECTIONS.save(deps.storage, &collection, &CollectionInfo::default())?;
+    Ok(Response::new()
+        .add_attribute("action", "add_collection")
+        .add_attribute("collection", collection))
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn add_nft_distribution(
+    mut deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    collection: String,
+    distribution_end_time: Timestamp,
+    minimum_nfts_to_claim: u64,
+    start_time: Option<Timestamp>,
+    snapshot_id: Option<u64>,
+) -> Result<Response, ContractError> {
+    ensure!(
+        distribution_end_time > env.block.time,
+        ContractError::InvalidDistributionEndTime {}
+    );
+    let mut config = CONFIG.load(deps.storage)?;
+    ensure_not_paused(&config, &PauseScope::Fund, &env)?;
+    ensure!(
+        info.sender == config.distributor,
+        ContractError::Unauthorized {}
+    );
+    ensure!(
+        info.funds.is_empty(),
+        ContractError::InvalidFundsReceived {}
+    );
+    refresh_nft_count(&deps, &mut config)?;
+    let nft_count = config.nft_count;
+
+    // One deposited NFT is set aside for every token of the collection
+    let collection = deps.api.addr_validate(&collection)?;
+    let token_ids = NFT_DEPOSITS
+        .prefix(&collection)
+        .keys(deps.storage, None, None, Order::Ascending)
+        .take(config.nft_count as usize)
+        .collect::<StdResult<Vec<String>>>()?;
+    ensure!(
+        token_ids.len() as u64 == config.nft_count,
+        ContractError::InvalidNftCount {}
+    );
+    config.last_nft_pool_id += 1;
+    let pool_id = config.last_nft_pool_id;
+    for token_id in token_ids.iter() {
+        NFT_DEPOSITS.remove(deps.storage, (&collection, token_id));
+        NFT_POOLS.save(deps.storage, (pool_id, token_id), &true)?;
+    }
+
+    // NFTs cannot be split over the next round so the unclaimed ones always go back
+    let max_token_id =
+        query_highest_token_id(&deps.querier, config.nft_address.as_str(), nft_count);
+    let snapshot = load_snapshot(deps.storage, snapshot_id)?;
+    let response = schedule_distribution(
+        deps.branch(),
+        &env,
+        &mut config,
+        QueuedDistribution {
+            assets_per_nft: vec![],
+            funds: vec![],
+            start_time: start_time.unwrap_or(env.block.time),
+            end_time: distribution_end_time,
+            nft_count,
+            max_token_id,
+            unclaimed_policy: UnclaimedPolicy::Distributor,
+            minimum_nfts_to_claim,
+            nft_rewards: Some(NftRewards {
+                collection,
+                pool_id,
+            }),
+            merkle_root: None,
+            total_weight: None,
+            trait_multipliers: None,
+            snapshot,
+            collection_rewards: vec![],
+            set_rewards: None,
+            tiers: None,
+            wallet_cap: None,
+        },
+    )?;
+    CONFIG.save(deps.storage, &config)?;
+
+    Ok(response)
+}
+
+// CW20s deposited beforehand for the distributed tokens are part of the funds
+fn add_cw20_deposits(
+    deps: &DepsMut,
+    assets: &[Asset],
+    funds: &mut Vec<Asset>,
+) -> StdResult<Vec<Addr>> {
+    let mut used_deposits: Vec<Addr> = vec![];
+    for asset in assets.iter() {
+        if let AssetInfo::Cw20(contract_addr) = &asset.info {
+            deps.api.addr_validate(contract_addr.as_str())?;
+            if let Some(deposit) = CW20_DEPOSITS.may_load(deps.storage, contract_addr)? {
+                add_asset(funds, &Asset::cw20(contract_addr.clone(), deposit));
+                used_deposits.push(contract_addr.clone());
+            }
+        }
+    }
+    Ok(used_deposits)
+}
+
+// Open distributions claimed by the holders, all of them unless some are selected
+// Merkle distributions are only claimed with their proofs, snapshot ones by the snapshot owners
+// and set ones with complete sets
+fn select_open_distributions(
+    storage: &dyn Storage,
+    env: &Env,
+    dist_ids: Option<Vec<u64>>,
+) -> Result<Vec<(u64, Distribution)>, ContractError> {
+    let mut distributions = load_open_distributions(storage, env)?;
+    distributions.retain(|(_, distribution)| {
+        distribution.merkle_root.is_none()
+            && distribution.snapshot.is_none()
+            && distribution.set_rewards.is_none()
+    });
+    if let Some(mut dist_ids) = dist_ids {
+        dist_ids.sort_unstable();
+        dist_ids.dedup();
+        ensure!(
+            dist_ids
+                .iter()
+                .all(|dist_id| distributions.iter().any(|(id, _)| id == dist_id)),
+            ContractError::ClaimingWindowHasClosed {}
+        );
+        distributions.retain(|(id, _)| dist_ids.contains(id));
+    }
+    ensure!(
+        !distributions.is_empty(),
+        ContractError::ClaimingWindowHasClosed {}
+    );
+    Ok(distributions)
+}
+
+// Burned and newly minted tokens are taken into account by the next distribution
+fn validate_unclaimed_policy(
+    deps: &DepsMut,
+    unclaimed_policy: &UnclaimedPolicy,
+    distribution_end_time: Timestamp,
+) -> Result<(), ContractError> {
+    match unclaimed_policy {
+        UnclaimedPolicy::Address { address } => {
+            deps.api.addr_validate(address)?;
         }
-        if !previous_dist.unclaimed_to_distributor {
+        UnclaimedPolicy::BonusRound { end_time } => ensure!(
+            *end_time > distribution_end_time,
+            ContractError::InvalidBonusRound {}
+        ),
+        _ => {}
+    }
+    Ok(())
+}
+
+// The snapshot named by the distributor, it must still be the current one
+fn load_snapshot(
+    storage: &dyn Storage,
+    snapshot_id: Option<u64>,
+) -> Result<Option<Snapshot>, ContractError> {
+    let Some(snapshot_id) = snapshot_id else {
+        return Ok(None);
+    };
+    let snapshot = SNAPSHOT
+        .may_load(storage)?
+        .filter(|snapshot| snapshot.snapshot_id == snapshot_id)
+        .ok_or(ContractError::UnknownSnapshot {})?;
+    Ok(Some(snapshot))
+}
+
+fn refresh_nft_count(deps: &DepsMut, config: &mut Config) -> StdResult<()> {
+    config.nft_count = query_nft_count(&deps.querier, config.nft_address.as_str())?;
+    Ok(())
+}
+
+// Queues the distribution or makes it current right away
+fn schedule_distribution(
+    mut deps: DepsMut,
+    env: &Env,
+    config: &mut Config,
+    distribution: QueuedDistribution,
+) -> Result<Response, ContractError> {
+    // Rounds that were queued before this one go first
+    let mut response = activate_queued_distributions(deps.branch(), env, config)?;
+
+    if distribution.start_time > env.block.time {
+        ensure!(
+            distribution.start_time < distribution.end_time,
+            ContractError::InvalidDistributionStartTime {}
+        );
+        // A queued round starts once the previously scheduled window has ended
+        let previous_end_time = QUEUE
+            .range(deps.storage, None, None, Order::Descending)
+            .next()
+            .transpose()?
+            .map(|(_, queued)| queued.end_time)
+            .or(config.current_dist_end_time);
+        if let Some(previous_end_time) = previous_end_time {
             ensure!(
-                rem_amount >= Uint128::new(remaining_to_dist as u128) * prev_dist.amount,
-                ContractError::InvalidDistributionInputs {}
+                distribution.start_time >= previous_end_time,
+                ContractError::InvalidDistributionStartTime {}
             );
-            let mut found_asset = false;
-            for curr_dist in &mut assets_per_nft {
-                if curr_dist.denom == prev_dist.denom {
-                    curr_dist.amount += (Uint128::new(remaining_to_dist as u128)
-                        * prev_dist.amount)
-                        / Uint128::new(config.nft_count as u128);
-                    found_asset = true;
-                    break;
-                }
+        }
+        config.last_queue_id += 1;
+        QUEUE.save(deps.storage, config.last_queue_id, &distribution)?;
+        response = response.add_attribute("queue_id", config.last_queue_id.to_string());
+    } else {
+        response = activate_distribution(deps.branch(), env, config, distribution, response)?;
+    }
+
+    Ok(response)
+}
+
+// Activates the queued distributions whose start time has come and saves the config if one was
+fn sync_config(
+    mut deps: DepsMut,
+    env: &Env,
+    config: &mut Config,
+) -> Result<Response, ContractError> {
+    let latest_dist_id = config.current_dist_id;
+    let response = activate_queued_distributions(deps.branch(), env, config)?;
+    if config.current_dist_id != latest_dist_id {
+        CONFIG.save(deps.storage, config)?;
+    }
+    Ok(response)
+}
+
+// Activates the queued distributions whose start time has come, in order
+fn activate_queued_distributions(
+    mut deps: DepsMut,
+    env: &Env,
+    config: &mut Config,
+) -> Result<Response, ContractError> {
+    let mut response = Response::new();
+    // Queued rounds wait while funding is paused
+    if config.pauses.is_paused(&PauseScope::Fund, env.block.time) {
+        return Ok(response);
+    }
+    loop {
+        let next_queued = QUEUE
+            .range(deps.storage, None, None, Order::Ascending)
+            .next()
+            .transpose()?;
+        match next_queued {
+            Some((queue_id, queued)) if queued.start_time <= env.block.time => {
+                QUEUE.remove(deps.storage, queue_id);
+                response = activate_distribution(deps.branch(), env, config, queued, response)?;
             }
+            _ => break,
+        }
+    }
+    Ok(response)
+}
 
-            if !found_asset {
-                new_assets_per_nft.push(Coin {
-                    denom: prev_dist.denom.clone(),
-                    amount: (Uint128::new(remaining_to_dist as u128) * prev_dist.amount)
-                        / Uint128::new(config.nft_count as u128),
-                });
+// Settles the distributions that ended and makes the new one claimable
+fn activate_distribution(
+    deps: DepsMut,
+    env: &Env,
+    config: &mut Config,
+    distribution: QueuedDistribution,
+    mut response: Response,
+) -> Result<Response, ContractError> {
+    let pays_flat_amount = distribution.pays_flat_amount();
+    let mut assets_per_nft = distribution.assets_per_nft;
+    let unclaimed_policy = distribution.unclaimed_policy;
+
+    config.current_dist_id += 1;
+    config.current_dist_end_time = Some(distribution.end_time);
+
+    let mut rolled_over: Vec<Asset> = vec![];
+    let mut rollover: Vec<Asset> = vec![];
+    let returns_paused = config.pauses.is_paused(&PauseScope::Return, env.block.time);
+
+    // Distributions still open keep running, the ones that ended are settled here
+    for (previous_dist_id, mut previous_dist) in load_ended_distributions(deps.storage, env)? {
+        let rolls_over = previous_dist.unclaimed_policy == UnclaimedPolicy::Rollover;
+        // Rollovers wait for a distribution paying a flat amount per token
+        if rolls_over && !pays_flat_amount {
+            continue;
+        }
+        // The others stay active until returns are unpaused
+        if !rolls_over && !previous_dist.unclaimed_settled && returns_paused {
+            continue;
+        }
+        // Bonus rounds are settled once they have ended
+        if let UnclaimedPolicy::BonusRound { end_time } = previous_dist.unclaimed_policy {
+            if env.block.time <= end_time {
+                continue;
             }
         }
+
+        let unclaimed = unclaimed_assets(&previous_dist);
+        if rolls_over {
+            for unclaimed in unclaimed.iter() {
+                let remain_contract = query_asset_balance(
+                    &deps.querier,
+                    &unclaimed.info,
+                    env.contract.address.as_str(),
+                )?;
+                let being_sent_validated = distribution
+                    .funds
+                    .iter()
+                    .find(|fund| fund.info == unclaimed.info)
+                    .map(|fund| fund.amount)
+                    .unwrap_or_default();
+                let rem_amount = remain_contract - being_sent_validated;
+                ensure!(
+                    rem_amount >= unclaimed.amount,
+                    ContractError::InvalidDistributionInputs {}
+                );
+                add_asset(&mut rolled_over, unclaimed);
+            }
+        } else if !previous_dist.unclaimed_settled {
+            response = response.add_messages(settle_unclaimed(
+                deps.storage,
+                config,
+                previous_dist_id,
+                &previous_dist,
+                &unclaimed,
+            )?);
+            if let Some(nft_rewards) = &previous_dist.nft_rewards {
+                response = response.add_messages(take_nft_rewards(
+                    deps.storage,
+                    nft_rewards,
+                    config.distributor.as_str(),
+                    None,
+                )?);
+            }
+        }
+
+        // Ended distributions are kept as history so their unclaimed status is updated
+        previous_dist.unclaimed_settled = true;
+        DISTRIBUTIONS.save(deps.storage, previous_dist_id, &previous_dist)?;
+        ACTIVE_DISTRIBUTIONS.remove(deps.storage, previous_dist_id);
     }
 
-    if !unclaimed_to_distributor {
-        assets_per_nft.extend(new_assets_per_nft);
+    // Whole units per NFT are rolled over, the remainders are kept as dust until they add up to one
+    if pays_flat_amount {
+        let mut dust = DUST.may_load(deps.storage)?.unwrap_or_default();
+        for asset in dust.drain(..) {
+            add_asset(&mut rolled_over, &asset);
+        }
+        let nft_count = Uint128::new(distribution.nft_count as u128);
+        for asset in rolled_over {
+            let rollover_asset = Asset {
+                info: asset.info.clone(),
+                amount: asset.amount.checked_div(nft_count).unwrap_or_default(),
+            };
+            add_asset(
+                &mut dust,
+                &Asset {
+                    info: asset.info,
+                    amount: asset.amount - rollover_asset.amount * nft_count,
+                },
+            );
+            if rollover_asset.amount.is_zero() {
+                continue;
+            }
+            add_asset(&mut assets_per_nft, &rollover_asset);
+            add_asset(&mut rollover, &rollover_asset);
+        }
+        dust.retain(|asset| !asset.amount.is_zero());
+        DUST.save(deps.storage, &dust)?;
     }
 
-    DISTRIBUTION.save(
+    DISTRIBUTIONS.save(
         deps.storage,
+        config.current_dist_id,
         &Distribution {
             assets: assets_per_nft.clone(),
             claimed: 0,
-            unclaimed_to_distributor,
-            unclaimed_sent_to_distributor: if unclaimed_to_distributor {
-                Some(false)
-            } else {
-                None
-            },
-            minimum_nfts_to_claim,
+            unclaimed_policy,
+            unclaimed_settled: false,
+            minimum_nfts_to_claim: distribution.minimum_nfts_to_claim,
+            start_time: distribution.start_time,
+            end_time: distribution.end_time,
+            nft_count: distribution.nft_count,
+            max_token_id: distribution.max_token_id,
+            rollover,
+            nft_rewards: distribution.nft_rewards,
+            merkle_root: distribution.merkle_root,
+            paid: vec![],
+            total_weight: distribution.total_weight,
+            claimed_weight: 0,
+            trait_multipliers: distribution.trait_multipliers,
+            snapshot: distribution.snapshot,
+            collection_rewards: distribution.collection_rewards,
+            set_rewards: distribution.set_rewards,
+            tiers: distribution.tiers,
+            wallet_cap: distribution.wallet_cap,
+            capped: vec![],
+            bonus_round: None,
+            topped_up_from: None,
+            cancelled_at: None,
         },
     )?;
+    ACTIVE_DISTRIBUTIONS.save(deps.storage, config.current_dist_id, &true)?;
+
+    Ok(response.add_attribute("dist_id", config.current_dist_id.to_string()))
+}
+
+pub fn cancel_queued_distribution(
+    mut deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    queue_id: u64,
+) -> Result<Response, ContractError> {
+    let mut config = CONFIG.load(deps.storage)?;
+    ensure!(info.sender == config.admin, ContractError::Unauthorized {});
+
+    // A round whose start time has passed is no longer queued
+    let mut response = activate_queued_distributions(deps.branch(), &env, &mut config)?;
+    let queued = QUEUE
+        .may_load(deps.storage, queue_id)?
+        .ok_or(ContractError::QueuedDistributionNotFound {})?;
+    QUEUE.remove(deps.storage, queue_id);
+    CONFIG.save(deps.storage, &config)?;
+
+    for fund in queued.funds.iter() {
+        response = response.add_message(transfer_asset_msg(fund, config.distributor.as_str())?);
+    }
+    if let Some(nft_rewards) = &queued.nft_rewards {
+        response = response.add_messages(take_nft_rewards(
+            deps.storage,
+            nft_rewards,
+            config.distributor.as_str(),
+            None,
+        )?);
+    }
+    Ok(response
+        .add_attribute("action", "cancel_queued_distribution")
+        .add_attribute("queue_id", queue_id.to_string()))
+}
+
+// Closes the claims of a distribution and refunds what was not claimed to the distributor,
+// whatever its unclaimed policy
+pub fn cancel_distribution(
+    mut deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    dist_id: Option<u64>,
+) -> Result<Response, ContractError> {
+    let mut config = CONFIG.load(deps.storage)?;
+    ensure!(info.sender == config.admin, ContractError::Unauthorized {});
+
+    let mut response = activate_queued_distributions(deps.branch(), &env, &mut config)?;
+    // Defaults to the latest distribution
+    let dist_id = dist_id.unwrap_or(config.current_dist_id);
+    ensure!(
+        ACTIVE_DISTRIBUTIONS.has(deps.storage, dist_id),
+        ContractError::NoActiveDistribution {}
+    );
+    let mut distribution = DISTRIBUTIONS.load(deps.storage, dist_id)?;
+
+    let unclaimed = unclaimed_assets(&distribution);
+    for asset in unclaimed.iter() {
+        response = response.add_message(transfer_asset_msg(asset, config.distributor.as_str())?);
+    }
+    if let Some(nft_rewards) = &distribution.nft_rewards {
+        response = response.add_messages(take_nft_rewards(
+            deps.storage,
+            nft_rewards,
+            config.distributor.as_str(),
+            None,
+        )?);
+    }
+
+    // Kept as history with its claims, it is neither claimed nor settled again
+    distribution.cancelled_at = Some(env.block.time);
+    distribution.unclaimed_settled = true;
+    DISTRIBUTIONS.save(deps.storage, dist_id, &distribution)?;
+    ACTIVE_DISTRIBUTIONS.remove(deps.storage, dist_id);
+    if dist_id == config.current_dist_id {
+        config.current_dist_end_time = None;
+    }
+    CONFIG.save(deps.storage, &config)?;
+
+    Ok(response
+        .add_attribute("action", "cancel_distribution")
+        .add_attribute("dist_id", dist_id.to_string())
+        .add_attribute(
+            "refunded",
+            match unclaimed.is_empty() {
+                true => "none".to_string(),
+                false => unclaimed
+                    .iter()
+                    .map(|asset| asset.to_string())
+                    .collect::<Vec<String>>()
+                    .join(", "),
+            },
+        ))
+}
+
+// The amendments change the current distribution while its window is open
+pub enum Amendment {
+    ExtendWindow { end_time: Timestamp },
+    // Defaults to now
+    CloseWindow { end_time: Option<Timestamp> },
+    MinimumNftsToClaim { minimum_nfts_to_claim: u64 },
+}
+
+pub fn amend_distribution(
+    mut deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    amendment: Amendment,
+) -> Result<Response, ContractError> {
+    let mut config = CONFIG.load(deps.storage)?;
+    ensure!(info.sender == config.admin, ContractError::Unauthorized {});
+
+    // A round whose start time has passed is the one amended
+    let response = activate_queued_distributions(deps.branch(), &env, &mut config)?;
+    let dist_id = config.current_dist_id;
+    let mut distribution = DISTRIBUTIONS
+        .may_load(deps.storage, dist_id)?
+        .unwrap_or_default();
+    ensure!(
+        ACTIVE_DISTRIBUTIONS.has(deps.storage, dist_id) && env.block.time <= distribution.end_time,
+        ContractError::ClaimingWindowHasClosed {}
+    );
+
+    let (field, previous, value) = match amendment {
+        Amendment::ExtendWindow { end_time } => {
+            ensure!(
+                end_time > distribution.end_time,
+                ContractError::InvalidDistributionEndTime {}
+            );
+            // The window cannot run into the next queued round
+            let next_queued = QUEUE
+                .range(deps.storage, None, None, Order::Ascending)
+                .next()
+                .transpose()?;
+            if let Some((_, queued)) = next_queued {
+                ensure!(
+                    end_time <= queued.start_time,
+                    ContractError::InvalidDistributionEndTime {}
+                );
+            }
+            validate_unclaimed_policy(&deps, &distribution.unclaimed_policy, end_time)?;
+            let previous = distribution.end_time;
+            distribution.end_time = end_time;
+            ("end_time", previous.to_string(), end_time.to_string())
+        }
+        Amendment::CloseWindow { end_time } => {
+            let end_time = end_time.unwrap_or(env.block.time);
+            ensure!(
+                end_time >= env.block.time && end_time < distribution.end_time,
+                ContractError::InvalidDistributionEndTime {}
+            );
+            let previous = distribution.end_time;
+            distribution.end_time = end_time;
+            ("end_time", previous.to_string(), end_time.to_string())
+        }
+        Amendment::MinimumNftsToClaim {
+            minimum_nfts_to_claim,
+        } => {
+            // Every wallet allowed to claim still falls in a tier
+            if let Some(tiers) = &distribution.tiers {
+                ensure!(
+                    tiers
+                        .iter()
+                        .any(|tier| tier.min_nfts <= minimum_nfts_to_claim.max(1)),
+                    ContractError::InvalidTiers {}
+                );
+            }
+            let previous = distribution.minimum_nfts_to_claim;
+            distribution.minimum_nfts_to_claim = minimum_nfts_to_claim;
+            (
+                "minimum_nfts_to_claim",
+                previous.to_string(),
+                minimum_nfts_to_claim.to_string(),
+            )
+        }
+    };
+    config.current_dist_end_time = Some(distribution.end_time);
+    DISTRIBUTIONS.save(deps.storage, dist_id, &distribution)?;
+    CONFIG.save(deps.storage, &config)?;
+
+    Ok(response
+        .add_attribute("action", "amend_distribution")
+        .add_event(
+            Event::new("amend_distribution")
+                .add_attribute("dist_id", dist_id.to_string())
+                .add_attribute("field", field)
+                .add_attribute("previous", previous)
+                .add_attribute("value", value),
+        ))
+}
+
+pub fn upload_weights(
+    deps: DepsMut,
+    info: MessageInfo,
+    weights: Vec<TokenWeight>,
+    ranges: Vec<WeightRange>,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    ensure!(info.sender == config.admin, ContractError::Unauthorized {});
+    ensure!(
+        weights.len() + ranges.len() <= MAX_WEIGHTS_PER_UPLOAD,
+        ContractError::TooManyTokensSent {}
+    );
+    let mut weight_table = WEIGHT_TABLE.may_load(deps.storage)?.unwrap_or_default();
+    ensure!(!weight_table.frozen, ContractError::WeightsFrozen {});
+
+    // Single numeric tokens are stored as one token ranges, replaced when uploaded again like the
+    // other tokens
+    let mut ranges = ranges;
+    for token_weight in weights.iter() {
+        match numeric_token_id(&token_weight.token_id) {
+            Some(numeric_id) => {
+                if let Some((_, previous_weight)) = WEIGHT_RANGES
+                    .may_load(deps.storage, numeric_id)?
+                    .filter(|(end, _)| *end == numeric_id)
+                {
+                    WEIGHT_RANGES.remove(deps.storage, numeric_id);
+                    weight_table.total_weight -= previous_weight;
+                }
+                ranges.push(WeightRange {
+                    start: numeric_id,
+                    end: numeric_id,
+                    weight: token_weight.weight,
+                });
+            }
+            None => {
+                let previous_weight = TOKEN_WEIGHTS
+                    .may_load(deps.storage, &token_weight.token_id)?
+                    .unwrap_or_default();
+                TOKEN_WEIGHTS.save(deps.storage, &token_weight.token_id, &token_weight.weight)?;
+                weight_table.total_weight = (weight_table.total_weight - previous_weight)
+                    .checked_add(token_weight.weight)
+                    .ok_or(ContractError::InvalidWeights {})?;
+            }
+        }
+    }
+
+    for range in ranges.iter() {
+        ensure!(range.start <= range.end, ContractError::InvalidWeights {});
+        // Ranges cannot overlap so every token has a single weight
+        let overlaps = WEIGHT_RANGES
+            .range(
+                deps.storage,
+                None,
+                Some(Bound::inclusive(range.end)),
+                Order::Descending,
+            )
+            .next()
+            .transpose()?
+            .map(|(_, (end, _))| end >= range.start)
+            .unwrap_or(false);
+        ensure!(!overlaps, ContractError::InvalidWeights {});
+        WEIGHT_RANGES.save(deps.storage, range.start, &(range.end, range.weight))?;
+        weight_table.total_weight = (range.end - range.start + 1)
+            .checked_mul(range.weight)
+            .and_then(|range_weight| weight_table.total_weight.checked_add(range_weight))
+            .ok_or(ContractError::InvalidWeights {})?;
+    }
+
+    WEIGHT_TABLE.save(deps.storage, &weight_table)?;
+    Ok(Response::new()
+        .add_attribute("action", "upload_weights")
+        .add_attribute("total_weight", weight_table.total_weight.to_string()))
+}
+
+pub fn upload_snapshot(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    snapshot_time: Timestamp,
+    owners: Vec<SnapshotOwner>,
+    merkle_root: Option<String>,
+) -> Result<Response, ContractError> {
+    let mut config = CONFIG.load(deps.storage)?;
+    ensure!(info.sender == config.admin, ContractError::Unauthorized {});
+    ensure!(
+        snapshot_time <= env.block.time && owners.len() <= MAX_OWNERS_PER_UPLOAD,
+        ContractError::InvalidSnapshot {}
+    );
+    // A merkle snapshot has no uploaded owners
+    if let Some(merkle_root) = &merkle_root {
+        decode_merkle_root(merkle_root)?;
+        ensure!(owners.is_empty(), ContractError::InvalidSnapshot {});
+    }
+
+    // Chunks of the current snapshot are added to it, another time or root starts a new one
+    let snapshot = match SNAPSHOT.may_load(deps.storage)? {
+        Some(snapshot) if snapshot.time == snapshot_time && snapshot.merkle_root == merkle_root => {
+            snapshot
+        }
+        _ => {
+            config.last_snapshot_id += 1;
+            CONFIG.save(deps.storage, &config)?;
+            Snapshot {
+                snapshot_id: config.last_snapshot_id,
+                time: snapshot_time,
+                merkle_root,
+            }
+        }
+    };
+    for owner in owners.iter() {
+        SNAPSHOT_OWNERS.save(
+            deps.storage,
+            (snapshot.snapshot_id, &owner.token_id),
+            &deps.api.addr_validate(&owner.owner)?,
+        )?;
+    }
+    SNAPSHOT.save(deps.storage, &snapshot)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "upload_snapshot")
+        .add_attribute("snapshot_id", snapshot.snapshot_id.to_string())
+        .add_attribute("count", owners.len().to_string()))
+}
+
+pub fn clear_snapshot(deps: DepsMut, info: MessageInfo) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    ensure!(info.sender == config.admin, ContractError::Unauthorized {});
+    // Distributions funded with the snapshot keep it
+    SNAPSHOT.remove(deps.storage);
+    Ok(Response::new().add_attribute("action", "clear_snapshot"))
+}
+
+pub fn set_trait_multipliers(
+    deps: DepsMut,
+    info: MessageInfo,
+    trait_multipliers: Vec<TraitMultiplier>,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    ensure!(info.sender == config.admin, ContractError::Unauthorized {});
+    // Distributions keep the multipliers they were funded with
+    TRAIT_MULTIPLIERS.save(deps.storage, &trait_multipliers)?;
+    Ok(Response::new()
+        .add_attribute("action", "set_trait_multipliers")
+        .add_attribute("count", trait_multipliers.len().to_string()))
+}
+
+pub fn freeze_weights(deps: DepsMut, info: MessageInfo) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    ensure!(info.sender == config.admin, ContractError::Unauthorized {});
+    let mut weight_table = WEIGHT_TABLE.may_load(deps.storage)?.unwrap_or_default();
+    ensure!(!weight_table.frozen, ContractError::WeightsFrozen {});
+    ensure!(
+        weight_table.total_weight > 0,
+        ContractError::InvalidWeights {}
+    );
+    weight_table.frozen = true;
+    WEIGHT_TABLE.save(deps.storage, &weight_table)?;
+    Ok(Response::new()
+        .add_attribute("action", "freeze_weights")
+        .add_attribute("total_weight", weight_table.total_weight.to_string()))
+}
+
+pub fn receive_cw20(
+    deps: DepsMut,
+    info: MessageInfo,
+    cw20_msg: Cw20ReceiveMsg,
+) -> Result<Response, ContractError> {
+    // Only turns away wallets calling the hook directly, any contract can answer TokenInfo. The
+    // deposit is keyed by the calling contract, so a fake one only credits its own token
+    ensure!(
+        query_cw20_token_info(&deps.querier, &info.sender).is_ok(),
+        ContractError::NotACw20Contract {}
+    );
+    let sender = deps.api.addr_validate(&cw20_msg.sender)?;
+
+    match from_binary(&cw20_msg.msg)? {
+        ReceiveMsg::Deposit {} => {
+            let config = CONFIG.load(deps.storage)?;
+            ensure!(sender == config.distributor, ContractError::Unauthorized {});
+            CW20_DEPOSITS.update(deps.storage, &info.sender, |deposit| -> StdResult<_> {
+                Ok(deposit.unwrap_or_default() + cw20_msg.amount)
+            })?;
+            let received = Asset::cw20(info.sender, cw20_msg.amount);
+            Ok(Response::new()
+                .add_attribute("action", "deposit")
+                .add_attribute("deposit", received.to_string()))
+        }
+    }
+}
+
+pub fn receive_nft(
+    deps: DepsMut,
+    info: MessageInfo,
+    nft_msg: Cw721ReceiveMsg,
+) -> Result<Response, ContractError> {
+    // The collection calls this on behalf of the original owner
+    match from_binary(&nft_msg.msg)? {
+        ReceiveNftMsg::Deposit {} => {
+            let config = CONFIG.load(deps.storage)?;
+            ensure!(
+                deps.api.addr_validate(&nft_msg.sender)? == config.distributor,
+                ContractError::Unauthorized {}
+            );
+            NFT_DEPOSITS.save(deps.storage, (&info.sender, &nft_msg.token_id), &true)?;
+            Ok(Response::new()
+                .add_attribute("action", "deposit_nft")
+                .add_attribute("collection", info.sender.to_string())
+                .add_attribute("token_id", nft_msg.token_id))
+        }
+    }
+}
+
+pub fn withdraw_deposits(deps: DepsMut, info: MessageInfo) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    ensure!(
+        info.sender == config.distributor,
+        ContractError::Unauthorized {}
+    );
+
+    let deposits = CW20_DEPOSITS
+        .range(deps.storage, None, None, Order::Ascending)
+        .collect::<StdResult<Vec<(Addr, Uint128)>>>()?;
+    let mut response = Response::new().add_attribute("action", "withdraw_deposits");
+    for (contract_addr, amount) in deposits {
+        CW20_DEPOSITS.remove(deps.storage, &contract_addr);
+        response = response.add_message(transfer_asset_msg(
+            &Asset::cw20(contract_addr, amount),
+            config.distributor.as_str(),
+        )?);
+    }
 
-    // Remove all states
-    CLAIMS.clear(deps.storage);
+    let nft_deposits = NFT_DEPOSITS
+        .keys(deps.storage, None, None, Order::Ascending)
+        .collect::<StdResult<Vec<(Addr, String)>>>()?;
+    for (collection, token_id) in nft_deposits {
+        NFT_DEPOSITS.remove(deps.storage, (&collection, &token_id));
+        response = response.add_message(transfer_nft_msg(
+            &collection,
+            &token_id,
+            config.distributor.as_str(),
+        )?);
+    }
+    Ok(response)
+}
 
+pub fn pause(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    scope: PauseScope,
+    reason: String,
+    until: Option<Timestamp>,
+) -> Result<Response, ContractError> {
+    let mut config = CONFIG.load(deps.storage)?;
+    ensure!(
+        config.pauser.as_ref() == Some(&info.sender),
+        ContractError::Unauthorized {}
+    );
+    ensure!(
+        !reason.trim().is_empty() && !matches!(until, Some(until) if until <= env.block.time),
+        ContractError::InvalidPause {}
+    );
+
+    let mut response = Response::new()
+        .add_attribute("action", "pause")
+        .add_attribute("scope", scope.to_string())
+        .add_attribute("reason", reason.clone());
+    if let Some(until) = until {
+        response = response.add_attribute("until", until.to_string());
+    }
+    *config.pauses.scope_mut(&scope) = Some(Pause { reason, until });
+    CONFIG.save(deps.storage, &config)?;
+    Ok(response)
+}
+
+pub fn unpause(
+    deps: DepsMut,
+    info: MessageInfo,
+    scope: PauseScope,
+) -> Result<Response, ContractError> {
+    let mut config = CONFIG.load(deps.storage)?;
+    ensure!(
+        config.pauser.as_ref() == Some(&info.sender),
+        ContractError::Unauthorized {}
+    );
+
+    *config.pauses.scope_mut(&scope) = None;
     CONFIG.save(deps.storage, &config)?;
+    Ok(Response::new()
+        .add_attribute("action", "unpause")
+        .add_attribute("scope", scope.to_string()))
+}
+
+pub fn set_pauser(
+    deps: DepsMut,
+    info: MessageInfo,
+    pauser: Option<String>,
+) -> Result<Response, ContractError> {
+    let mut config = CONFIG.load(deps.storage)?;
+    ensure!(info.sender == config.admin, ContractError::Unauthorized {});
 
+    // Pauses in place are kept until a pauser lifts them or their time comes
+    config.pauser = pauser
+        .map(|pauser| deps.api.addr_validate(&pauser))
+        .transpose()?;
+    CONFIG.save(deps.storage, &config)?;
+    let pauser = match &config.pauser {
+        Some(pauser) => pauser.to_string(),
+        None => "none".to_string(),
+    };
+    Ok(Response::new()
+        .add_attribute("action", "set_pauser")
+        .add_attribute("pauser", pauser))
+}
+
+pub fn withdraw_dust(deps: DepsMut, info: MessageInfo) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    ensure!(info.sender == config.admin, ContractError::Unauthorized {});
+
+    let dust = DUST.may_load(deps.storage)?.unwrap_or_default();
+    DUST.remove(deps.storage);
+    let mut response = Response::new().add_attribute("action", "withdraw_dust");
+    for asset in dust.iter() {
+        response = response.add_message(transfer_asset_msg(asset, config.admin.as_str())?);
+    }
     Ok(response)
 }
+
+pub fn prune_claims(
+    deps: DepsMut,
+    info: MessageInfo,
+    dist_id: u64,
+    limit: Option<u32>,
+) -> Result<Response, ContractError> {
+    // Claims are keyed by distribution so old ones never need to be cleared when a new one starts
+    // This only frees the storage of past distributions, a few at a time
+    let config = CONFIG.load(deps.storage)?;
+    ensure!(info.sender == config.admin, ContractError::Unauthorized {});
+    ensure!(
+        dist_id < config.current_dist_id && !ACTIVE_DISTRIBUTIONS.has(deps.storage, dist_id),
+        ContractError::CannotPruneCurrentDistribution {}
+    );
+
+    let limit = limit.unwrap_or(DEFAULT_PRUNE_LIMIT).min(MAX_PRUNE_LIMIT) as usize;
+    let token_ids = CLAIMS
+        .prefix(dist_id)
+        .keys(deps.storage, None, None, Order::Ascending)
+        .take(limit)
+        .collect::<StdResult<Vec<String>>>()?;
+    for token_id in token_ids.iter() {
+        CLAIMS.remove(deps.storage, (dist_id, token_id));
+    }
+    // Whatever is left of the limit goes to the other collections, then to the bitmap slots
+    let collection_tokens = COLLECTION_CLAIMS
+        .sub_prefix(dist_id)
+        .keys(deps.storage, None, None, Order::Ascending)
+        .take(limit - token_ids.len())
+        .collect::<StdResult<Vec<(Addr, String)>>>()?;
+    for (collection, token_id) in collection_tokens.iter() {
+        COLLECTION_CLAIMS.remove(deps.storage, (dist_id, collection, token_id));
+    }
+    let slots = CLAIM_BITMAPS
+        .prefix(dist_id)
+        .keys(deps.storage, None, None, Order::Ascending)
+        .take(limit - token_ids.len() - collection_tokens.len())
+        .collect::<StdResult<Vec<u64>>>()?;
+    for slot in slots.iter() {
+        CLAIM_BITMAPS.remove(deps.storage, (dist_id, *slot));
+    }
+    // The wallets of a pro rata distribution still have their share to pull
+    let wallets = if PRO_RATA_SHARES.has(deps.storage, dist_id) {
+        vec![]
+    } else {
+        WALLET_CLAIMS
+            .prefix(dist_id)
+            .keys(deps.storage, None, None, Order::Ascending)
+            .take(limit - token_ids.len() - collection_tokens.len() - slots.len())
+            .collect::<StdResult<Vec<String>>>()?
+    };
+    for wallet in wallets.iter() {
+        WALLET_CLAIMS.remove(deps.storage, (dist_id, wallet));
+    }
+    let bonus_tokens = BONUS_CLAIMS
+        .prefix(dist_id)
+        .keys(deps.storage, None, None, Order::Ascending)
+        .take(limit - token_ids.len() - collection_tokens.len() - slots.len() - wallets.len())
+        .collect::<StdResult<Vec<String>>>()?;
+    for token_id in bonus_tokens.iter() {
+        BONUS_CLAIMS.remove(deps.storage, (dist_id, token_id));
+    }
+    let paid_tokens = TOKEN_PAID
+        .prefix(dist_id)
+        .keys(deps.storage, None, None, Order::Ascending)
+        .take(
+            limit
+                - token_ids.len()
+                - collection_tokens.len()
+                - slots.len()
+                - wallets.len()
+                - bonus_tokens.len(),
+        )
+        .collect::<StdResult<Vec<String>>>()?;
+    for token_id in paid_tokens.iter() {
+        TOKEN_PAID.remove(deps.storage, (dist_id, token_id));
+    }
+
+    Ok(Response::new()
+        .add_attribute("action", "prune_claims")
+        .add_attribute("dist_id", dist_id.to_string())
+        .add_attribute(
+            "pruned",
+            (token_ids.len()
+                + collection_tokens.len()
+                + slots.len()
+                + wallets.len()
+                + bonus_tokens.len()
+                + paid_tokens.len())
+            .to_string(),
+        ))
+}
@@ -1,48 +1,490 @@
-use cosmwasm_std::{ensure, BankMsg, Coin, DepsMut, MessageInfo, Uint128};
-use std::collections::HashMap;
+use cosmwasm_std::{
+    ensure, to_binary, Addr, BankMsg, Binary, Coin, Decimal, DepsMut, Env, Order, QuerierWrapper,
+    StdResult, Storage, Uint128, WasmMsg,
+};
+use cw20::{Cw20ExecuteMsg, Cw20QueryMsg};
+use cw_storage_plus::Bound;
+use sg_std::{create_fund_community_pool_msg, CosmosMsg, Response};
+use sha2::{Digest, Sha256};
+use std::collections::btree_map::Entry;
+use std::collections::{BTreeMap, HashMap};
 
 use crate::error::ContractError;
-use crate::state::Distribution;
+use crate::state::{
+    Asset, AssetInfo, BonusRound, ClaimStorage, Config, Distribution, NftRewards, PauseScope, Tier,
+    TraitMultiplier, UnclaimedPolicy, ACTIVE_DISTRIBUTIONS, CLAIMS, CLAIM_BITMAPS, DISTRIBUTIONS,
+    NFT_POOLS, PRO_RATA_SHARES, PRO_RATA_TOKENS, TOKEN_PAID, TOKEN_TRAITS, TOKEN_WEIGHTS,
+    WALLET_CLAIMS, WEIGHT_RANGES,
+};
+use serde::Deserialize;
+
+const BITMAP_SLOT_BITS: u64 = 256;
+// Burned ids in a row the highest token id search looks past
+const MAX_BURNED_ID_GAP: u64 = 10;
+
+// Required, received and refunded amounts of a distribution's funding
+pub struct Funding {
+    pub required: Vec<Asset>,
+    pub received: Vec<Asset>,
+    pub refunded: Vec<Asset>,
+}
 
 pub fn validate_distribution(
     admin: &str,
-    info: &MessageInfo,
-    assets: &[Coin],
+    sender: &Addr,
+    funds: &[Asset],
+    assets: &[Asset],
     nft_count: &Uint128,
-) -> Result<(), ContractError> {
-    ensure!(
-        info.sender.as_str() == admin,
-        ContractError::Unauthorized {}
-    );
+) -> Result<Funding, ContractError> {
+    ensure!(sender.as_str() == admin, ContractError::Unauthorized {});
 
-    let asset_map: HashMap<&str, Uint128> = assets
+    let asset_map: HashMap<&AssetInfo, Uint128> = assets
         .iter()
-        .map(|asset_coin| (asset_coin.denom.as_str(), asset_coin.amount * nft_count))
+        .map(|asset| (&asset.info, asset.amount * nft_count))
         .collect();
 
     ensure!(
-        !info.funds.is_empty() && !assets.is_empty() && assets.len() == info.funds.len(),
+        !funds.is_empty()
+            && !assets.is_empty()
+            && asset_map
+                .keys()
+                .all(|info| funds.iter().any(|fund| &fund.info == *info)),
         ContractError::InvalidFundsReceived {}
     );
 
-    ensure!(
-        info.funds.iter().all(|fund_coin| {
-            asset_map
-                .get(fund_coin.denom.as_str())
-                .map(|expected_amount| fund_coin.amount == *expected_amount)
-                .unwrap_or(false)
-        }),
-        ContractError::InvalidDistributionInputs {}
+    // Anything above the required amounts, or not distributed, is refunded
+    let mut funding = Funding {
+        required: vec![],
+        received: funds.to_vec(),
+        refunded: vec![],
+    };
+    for fund in funds.iter() {
+        let required = asset_map.get(&fund.info).copied().unwrap_or_default();
+        ensure!(
+            fund.amount >= required,
+            ContractError::InvalidDistributionInputs {}
+        );
+        if !required.is_zero() {
+            add_asset(
+                &mut funding.required,
+                &Asset {
+                    info: fund.info.clone(),
+                    amount: required,
+                },
+            );
+        }
+        if fund.amount > required {
+            add_asset(
+                &mut funding.refunded,
+                &Asset {
+                    info: fund.info.clone(),
+                    amount: fund.amount - required,
+                },
+            );
+        }
+    }
+
+    Ok(funding)
+}
+
+pub fn ensure_not_paused(
+    config: &Config,
+    scope: &PauseScope,
+    env: &Env,
+) -> Result<(), ContractError> {
+    if !config.pauses.is_paused(scope, env.block.time) {
+        return Ok(());
+    }
+    Err(match scope {
+        PauseScope::Claim => ContractError::ClaimsPaused {},
+        PauseScope::Fund => ContractError::FundingPaused {},
+        PauseScope::Return => ContractError::ReturnsPaused {},
+    })
+}
+
+// Refunds the excess to the sender and itemizes the funding in the response
+pub fn funding_response(
+    response: Response,
+    funding: &Funding,
+    sender: &Addr,
+) -> StdResult<Response> {
+    let join = |assets: &[Asset]| match assets.is_empty() {
+        true => "none".to_string(),
+        false => assets
+            .iter()
+            .map(|asset| asset.to_string())
+            .collect::<Vec<String>>()
+            .join(", "),
+    };
+    let mut response = response
+        .add_attribute("required", join(&funding.required))
+        .add_attribute("received", join(&funding.received))
+        .add_attribute("refunded", join(&funding.refunded));
+    for refund in funding.refunded.iter() {
+        response = response.add_message(transfer_asset_msg(refund, sender.as_str())?);
+    }
+    Ok(response)
+}
+
+pub fn decode_merkle_root(merkle_root: &str) -> Result<[u8; 32], ContractError> {
+    let mut root = [0u8; 32];
+    hex::decode_to_slice(merkle_root, &mut root)
+        .map_err(|_| ContractError::InvalidMerkleRoot {})?;
+    Ok(root)
+}
+
+// Leaves are sha256("{token_id}:{amount}{denom or CW20 address},...") and pairs are hashed sorted
+pub fn verify_merkle_proof(
+    merkle_root: &str,
+    token_id: &str,
+    amounts: &[Asset],
+    proof: &[String],
+) -> Result<(), ContractError> {
+    let leaf = format!(
+        "{token_id}:{}",
+        amounts
+            .iter()
+            .map(|asset| asset.to_string())
+            .collect::<Vec<String>>()
+            .join(",")
     );
+    verify_merkle_leaf(merkle_root, &leaf, proof)
+}
 
+pub fn verify_merkle_leaf(
+    merkle_root: &str,
+    leaf: &str,
+    proof: &[String],
+) -> Result<(), ContractError> {
+    let mut hash: [u8; 32] = Sha256::digest(leaf.as_bytes()).into();
+    for step in proof.iter() {
+        let mut step_hash = [0u8; 32];
+        hex::decode_to_slice(step, &mut step_hash)
+            .map_err(|_| ContractError::InvalidMerkleProof {})?;
+        let mut pair = [hash, step_hash];
+        pair.sort_unstable();
+        hash = Sha256::digest(pair.concat()).into();
+    }
+    ensure!(
+        decode_merkle_root(merkle_root)? == hash,
+        ContractError::InvalidMerkleProof {}
+    );
     Ok(())
 }
 
+// What is left to claim in a distribution, without the zero amounts
+pub fn unclaimed_assets(distribution: &Distribution) -> Vec<Asset> {
+    // Once the bonus round is open only what it did not pay is left
+    if let Some(bonus_round) = &distribution.bonus_round {
+        let claimed = Uint128::new(bonus_round.claimed as u128);
+        let mut unclaimed: Vec<Asset> = bonus_round
+            .pool
+            .iter()
+            .map(|asset| {
+                let paid = bonus_round
+                    .assets_per_token
+                    .iter()
+                    .find(|per_token| per_token.info == asset.info)
+                    .map(|per_token| per_token.amount * claimed)
+                    .unwrap_or_default();
+                Asset {
+                    info: asset.info.clone(),
+                    amount: asset.amount - paid,
+                }
+            })
+            .collect();
+        unclaimed.retain(|asset| !asset.amount.is_zero());
+        return unclaimed;
+    }
+    let mut unclaimed: Vec<Asset> = vec![];
+    for asset in distribution.assets.iter() {
+        let paid = distribution
+            .paid
+            .iter()
+            .find(|paid| paid.info == asset.info)
+            .map(|paid| paid.amount)
+            .unwrap_or_default();
+        let amount = match (
+            &distribution.set_rewards,
+            &distribution.merkle_root,
+            &distribution.trait_multipliers,
+            distribution.total_weight,
+        ) {
+            (Some(set_rewards), _, _, _) => {
+                Uint128::new((set_rewards.max_sets - set_rewards.claimed_sets) as u128)
+                    * asset.amount
+            }
+            // Tiered distributions hold the highest multiplier for every token
+            _ if distribution.tiers.is_some() => {
+                let tiers = distribution.tiers.as_deref().unwrap_or_default();
+                (asset.amount
+                    * highest_tier_multiplier(tiers)
+                    * Uint128::new(distribution.nft_count as u128))
+                .saturating_sub(paid)
+            }
+            // Topped up distributions hold the raised amounts for every token
+            _ if distribution.topped_up_from.is_some() => {
+                (asset.amount * Uint128::new(distribution.nft_count as u128)).saturating_sub(paid)
+            }
+            // Merkle distributions hold the committed totals
+            (None, Some(_), _, _) => asset.amount - paid,
+            (None, None, Some(_), Some(total_weight)) => {
+                Uint128::new(total_weight as u128) * asset.amount - paid
+            }
+            (None, None, _, Some(total_weight)) => {
+                Uint128::new((total_weight - distribution.claimed_weight) as u128) * asset.amount
+            }
+            (None, None, _, None) => {
+                Uint128::new(distribution.nft_count.saturating_sub(distribution.claimed) as u128)
+                    * asset.amount
+            }
+        };
+        unclaimed.push(Asset {
+            info: asset.info.clone(),
+            amount,
+        });
+    }
+    // The holders of the other collections are counted per collection
+    for rewards in distribution.collection_rewards.iter() {
+        let unclaimed_count =
+            Uint128::new(rewards.nft_count.saturating_sub(rewards.claimed) as u128);
+        for asset in rewards.assets.iter() {
+            add_asset(
+                &mut unclaimed,
+                &Asset {
+                    info: asset.info.clone(),
+                    amount: asset.amount * unclaimed_count,
+                },
+            );
+        }
+    }
+    for capped in distribution.capped.iter() {
+        add_asset(&mut unclaimed, capped);
+    }
+    unclaimed.retain(|asset| !asset.amount.is_zero());
+    unclaimed
+}
+
+// Splits what was not claimed in the claiming window between the tokens which claimed
+pub fn open_bonus_round(distribution: &Distribution) -> BonusRound {
+    let pool = unclaimed_assets(distribution);
+    let assets_per_token = pool
+        .iter()
+        .map(|asset| Asset {
+            info: asset.info.clone(),
+            amount: asset
+                .amount
+                .checked_div(Uint128::new(distribution.claimed as u128))
+                .unwrap_or_default(),
+        })
+        .collect();
+    BonusRound {
+        pool,
+        assets_per_token,
+        claimed: 0,
+    }
+}
+
+// Canonical numeric token ids use the weight ranges, the others the token weights
+pub fn numeric_token_id(token_id: &str) -> Option<u64> {
+    token_id
+        .parse::<u64>()
+        .ok()
+        .filter(|numeric_id| numeric_id.to_string() == token_id)
+}
+
+// Tokens missing from the weight table have no weight
+pub fn token_weight(storage: &dyn Storage, token_id: &str) -> StdResult<u64> {
+    match numeric_token_id(token_id) {
+        Some(numeric_id) => Ok(WEIGHT_RANGES
+            .range(
+                storage,
+                None,
+                Some(Bound::inclusive(numeric_id)),
+                Order::Descending,
+            )
+            .next()
+            .transpose()?
+            .filter(|(_, (end, _))| *end >= numeric_id)
+            .map(|(_, (_, weight))| weight)
+            .unwrap_or_default()),
+        None => Ok(TOKEN_WEIGHTS
+            .may_load(storage, token_id)?
+            .unwrap_or_default()),
+    }
+}
+
+#[derive(Deserialize)]
+struct TraitMetadata {
+    #[serde(default)]
+    attributes: Vec<Trait>,
+}
+
+#[derive(Deserialize)]
+struct Trait {
+    trait_type: String,
+    value: String,
+}
+
+pub fn query_token_traits(
+    querier: &QuerierWrapper,
+    nft_address: &str,
+    token_id: &str,
+) -> StdResult<Vec<(String, String)>> {
+    let nft_info: cw721::NftInfoResponse<Option<TraitMetadata>> = querier.query_wasm_smart(
+        nft_address,
+        &sg721_base::QueryMsg::NftInfo {
+            token_id: token_id.to_string(),
+        },
+    )?;
+    Ok(nft_info
+        .extension
+        .map(|metadata| {
+            metadata
+                .attributes
+                .into_iter()
+                .map(|attribute| (attribute.trait_type, attribute.value))
+                .collect()
+        })
+        .unwrap_or_default())
+}
+
+// Traits are only queried the first time a token claims a trait distribution
+pub fn load_token_traits(
+    storage: &mut dyn Storage,
+    querier: &QuerierWrapper,
+    nft_address: &str,
+    token_id: &str,
+) -> StdResult<Vec<(String, String)>> {
+    if let Some(traits) = TOKEN_TRAITS.may_load(storage, token_id)? {
+        return Ok(traits);
+    }
+    let traits = query_token_traits(querier, nft_address, token_id)?;
+    TOKEN_TRAITS.save(storage, token_id, &traits)?;
+    Ok(traits)
+}
+
+// Tokens without any of the traits have a multiplier of 1
+pub fn trait_multiplier(
+    trait_multipliers: &[TraitMultiplier],
+    traits: &[(String, String)],
+) -> Decimal {
+    trait_multipliers
+        .iter()
+        .filter(|trait_multiplier| {
+            traits.iter().any(|(trait_type, value)| {
+                *trait_type == trait_multiplier.trait_type && *value == trait_multiplier.value
+            })
+        })
+        .fold(Decimal::one(), |multiplier, trait_multiplier| {
+            multiplier * trait_multiplier.multiplier
+        })
+}
+
 pub struct ToPay {
     pub found: Vec<String>,
     pub not_found: Vec<String>,
 }
 
+// Tokens of the collection, burned ones are not counted
+pub fn query_nft_count(querier: &QuerierWrapper, nft_address: &str) -> StdResult<u64> {
+    let nft_count: cw721::NumTokensResponse =
+        querier.query_wasm_smart(nft_address, &sg721_base::QueryMsg::NumTokens {})?;
+    Ok(nft_count.count)
+}
+
+// Highest numeric id minted when the token numbered by the count exists, None otherwise as the ids
+// may not run from 1; the search goes past up to MAX_BURNED_ID_GAP burned ids in a row
+pub fn query_highest_token_id(
+    querier: &QuerierWrapper,
+    nft_address: &str,
+    nft_count: u64,
+) -> Option<u64> {
+    let exists = |token_id: u64| {
+        querier
+            .query_wasm_smart::<cw721::OwnerOfResponse>(
+                nft_address,
+                &sg721_base::QueryMsg::OwnerOf {
+                    token_id: token_id.to_string(),
+                    include_expired: None,
+                },
+            )
+            .is_ok()
+    };
+    if nft_count == 0 || !exists(nft_count) {
+        return None;
+    }
+    let mut highest = nft_count;
+    let mut token_id = nft_count + 1;
+    while token_id - highest <= MAX_BURNED_ID_GAP {
+        if exists(token_id) {
+            highest = token_id;
+        }
+        token_id += 1;
+    }
+    Some(highest)
+}
+
+// Tokens above the id limit of the distribution cannot claim it
+pub fn eligible_tokens(max_token_id: Option<u64>, token_ids: &[String]) -> Vec<String> {
+    token_ids
+        .iter()
+        .filter(|token_id| match max_token_id {
+            Some(max_token_id) => {
+                !matches!(numeric_token_id(token_id), Some(token_id) if token_id > max_token_id)
+            }
+            None => true,
+        })
+        .cloned()
+        .collect()
+}
+
+// Number of NFTs held by the owner, counted up to the limit
+pub fn query_nfts_held(
+    deps: &DepsMut,
+    nft_address: &str,
+    owner: &str,
+    limit: u64,
+) -> Result<u64, ContractError> {
+    // Pages of Tokens are capped by the collection so they are followed up to the limit
+    let mut nfts_held = 0u64;
+    let mut start_after: Option<String> = None;
+    while nfts_held < limit {
+        let tokens_response: cw721::TokensResponse = deps.querier.query_wasm_smart(
+            nft_address,
+            &sg721_base::QueryMsg::Tokens {
+                owner: owner.to_string(),
+                start_after,
+                limit: Some((limit - nfts_held).min(100) as u32),
+            },
+        )?;
+        match tokens_response.tokens.last() {
+            Some(last_token) => start_after = Some(last_token.to_string()),
+            None => break,
+        }
+        nfts_held += tokens_response.tokens.len() as u64;
+    }
+    Ok(nfts_held)
+}
+
+// Multiplier of the highest tier reached by the number of NFTs held, nothing below the lowest one
+pub fn tier_multiplier(tiers: &[Tier], nfts_held: u64) -> Decimal {
+    tiers
+        .iter()
+        .filter(|tier| tier.min_nfts <= nfts_held)
+        .max_by_key(|tier| tier.min_nfts)
+        .map(|tier| tier.multiplier)
+        .unwrap_or_default()
+}
+
+pub fn highest_tier_multiplier(tiers: &[Tier]) -> Decimal {
+    tiers
+        .iter()
+        .map(|tier| tier.multiplier)
+        .max()
+        .unwrap_or_default()
+}
+
 pub fn query_owned_tokens(
     deps: &DepsMut,
     nft_address: &String,
@@ -50,29 +492,10 @@ pub fn query_owned_tokens(
     token_ids: Vec<String>,
     start_after: Option<String>,
     bulk: bool,
-    minimum_nfts_to_claim: u64,
 ) -> Result<ToPay, ContractError> {
-    let tokens_response: cw721::TokensResponse = deps.querier.query_wasm_smart(
-        nft_address,
-        &sg721_base::QueryMsg::Tokens {
-            owner: owner.to_string(),
-            start_after: None,
-            limit: Some(minimum_nfts_to_claim as u32),
-        },
-    )?;
-    // This approach ensure that someone could've claimed more than once
-    ensure!(
-        tokens_response.tokens.len() >= minimum_nfts_to_claim as usize,
-        ContractError::NotEnoughNftsToClaim {}
-    );
-
     let mut all_tokens = Vec::with_capacity(token_ids.len());
     if bulk {
-        let mut start_roll: Option<String> = if let Some(start) = &start_after {
-            Some(start.clone())
-        } else {
-            None
-        };
+        let mut start_roll: Option<String> = start_after;
         // Will try to catch most of them from querying Tokens (max of 5 queries)
         for _query_id in 1..=5 {
             let tokens_response: cw721::TokensResponse = deps.querier.query_wasm_smart(
@@ -83,7 +506,7 @@ pub fn query_owned_tokens(
                     limit: Some(100u32),
                 },
             )?;
-            if tokens_response.tokens.len() > 0 {
+            if !tokens_response.tokens.is_empty() {
                 start_roll = Some(tokens_response.tokens.last().unwrap().to_string());
             } else {
                 break;
@@ -96,7 +519,7 @@ pub fn query_owned_tokens(
             .cloned()
             .partition(|item| all_tokens.contains(item));
 
-        if intersection_of_tokens.len() < 1 {
+        if intersection_of_tokens.is_empty() {
             Err(ContractError::NothingToClaim {})
         } else {
             Ok(ToPay {
@@ -120,7 +543,7 @@ pub fn query_owned_tokens(
                 not_found_tokens.push(tok_id.to_string())
             }
         }
-        if all_tokens.len() < 1 {
+        if all_tokens.is_empty() {
             Err(ContractError::NothingToClaim {})
         } else {
             Ok(ToPay {
@@ -131,20 +554,667 @@ pub fn query_owned_tokens(
     }
 }
 
+// Pays the newly claimed tokens of a distribution to the recipient and saves it
+pub fn pay_claimed_tokens(
+    deps: &mut DepsMut,
+    config: &Config,
+    dist_id: u64,
+    mut distribution: Distribution,
+    newly_claimed: &[String],
+    recipient: &str,
+) -> Result<Vec<CosmosMsg>, ContractError> {
+    let dist_count = newly_claimed.len() as u64;
+    distribution.claimed += dist_count;
+    // Tokens minted after the funding would be paid out of the other distributions' funds
+    ensure!(
+        distribution.claimed <= distribution.nft_count,
+        ContractError::AllTokensClaimed {}
+    );
+    track_wallet_claim(deps.storage, dist_id, &distribution, recipient, dist_count)?;
+
+    // Trait distributions pay each token by its multiplier
+    if let (Some(trait_multipliers), Some(total_weight)) = (
+        distribution.trait_multipliers.clone(),
+        distribution.total_weight,
+    ) {
+        let mut to_send: Vec<Asset> = vec![];
+        for token_id in newly_claimed.iter() {
+            let traits = load_token_traits(
+                deps.storage,
+                &deps.querier,
+                config.nft_address.as_str(),
+                token_id,
+            )?;
+            let multiplier = trait_multiplier(&trait_multipliers, &traits);
+            for asset in distribution.assets.iter() {
+                add_asset(
+                    &mut to_send,
+                    &Asset {
+                        info: asset.info.clone(),
+                        amount: asset.amount * multiplier,
+                    },
+                );
+            }
+        }
+        for asset in to_send.iter() {
+            add_asset(&mut distribution.paid, asset);
+        }
+        // The declared total weight caps what can be paid
+        ensure!(
+            distribution.paid.iter().all(|paid| distribution
+                .assets
+                .iter()
+                .any(|asset| asset.info == paid.info
+                    && asset.amount * Uint128::new(total_weight as u128) >= paid.amount)),
+            ContractError::InvalidClaimValue {}
+        );
+        DISTRIBUTIONS.save(deps.storage, dist_id, &distribution)?;
+        return to_send
+            .iter()
+            .filter(|asset| !asset.amount.is_zero())
+            .map(|asset| Ok(transfer_asset_msg(asset, recipient)?))
+            .collect();
+    }
+
+    // Tiered distributions pay each token by the tier of the holder
+    if let Some(tiers) = distribution.tiers.clone() {
+        let highest_tier = tiers
+            .iter()
+            .map(|tier| tier.min_nfts)
+            .max()
+            .unwrap_or_default();
+        let nfts_held =
+            query_nfts_held(deps, config.nft_address.as_str(), recipient, highest_tier)?;
+        let multiplier = tier_multiplier(&tiers, nfts_held);
+        // Fails the claim rather than marking the tokens claimed for nothing
+        ensure!(!multiplier.is_zero(), ContractError::BelowLowestTier {});
+        let to_send: Vec<Asset> = distribution
+            .assets
+            .iter()
+            .map(|asset| Asset {
+                info: asset.info.clone(),
+                amount: asset.amount * multiplier * Uint128::new(dist_count as u128),
+            })
+            .collect();
+        for asset in to_send.iter() {
+            add_asset(&mut distribution.paid, asset);
+        }
+        // Every token at the highest multiplier caps what can be paid
+        let funded_tokens = Uint128::new(distribution.nft_count as u128);
+        ensure!(
+            distribution
+                .paid
+                .iter()
+                .all(|paid| distribution
+                    .assets
+                    .iter()
+                    .any(|asset| asset.info == paid.info
+                        && asset.amount * highest_tier_multiplier(&tiers) * funded_tokens
+                            >= paid.amount)),
+            ContractError::InvalidClaimValue {}
+        );
+        DISTRIBUTIONS.save(deps.storage, dist_id, &distribution)?;
+        return to_send
+            .iter()
+            .filter(|asset| !asset.amount.is_zero())
+            .map(|asset| Ok(transfer_asset_msg(asset, recipient)?))
+            .collect();
+    }
+
+    // Capped distributions withhold what the wallet receives over its cap
+    if let Some(wallet_cap) = distribution.wallet_cap.clone() {
+        let mut wallet_claim = WALLET_CLAIMS
+            .may_load(deps.storage, (dist_id, recipient))?
+            .unwrap_or_default();
+        let mut to_send: Vec<Asset> = Vec::with_capacity(distribution.assets.len());
+        for asset in distribution.assets.iter() {
+            let due = asset.amount * Uint128::new(dist_count as u128);
+            let amount = match wallet_cap
+                .max_amount_per_wallet
+                .iter()
+                .flatten()
+                .find(|cap| cap.info == asset.info)
+            {
+                Some(cap) => {
+                    let received = wallet_claim
+                        .amounts
+                        .iter()
+                        .find(|received| received.info == asset.info)
+                        .map(|received| received.amount)
+                        .unwrap_or_default();
+                    due.min(cap.amount.saturating_sub(received))
+                }
+                None => due,
+            };
+            add_asset(
+                &mut distribution.capped,
+                &Asset {
+                    info: asset.info.clone(),
+                    amount: due - amount,
+                },
+            );
+            let paid = Asset {
+                info: asset.info.clone(),
+                amount,
+            };
+            add_asset(&mut wallet_claim.amounts, &paid);
+            to_send.push(paid);
+        }
+        distribution
+            .capped
+            .retain(|capped| !capped.amount.is_zero());
+        WALLET_CLAIMS.save(deps.storage, (dist_id, recipient), &wallet_claim)?;
+        DISTRIBUTIONS.save(deps.storage, dist_id, &distribution)?;
+        return to_send
+            .iter()
+            .filter(|asset| !asset.amount.is_zero())
+            .map(|asset| Ok(transfer_asset_msg(asset, recipient)?))
+            .collect();
+    }
+
+    // Weighted distributions pay per unit of weight
+    let paid_units = match distribution.total_weight {
+        Some(_) => {
+            let mut claimed_weight = 0u64;
+            for token_id in newly_claimed.iter() {
+                claimed_weight += token_weight(deps.storage, token_id)?;
+            }
+            distribution.claimed_weight += claimed_weight;
+            claimed_weight
+        }
+        None => dist_count,
+    };
+    // Once topped up, what each token was paid is tracked to pay it the next top ups
+    if distribution.topped_up_from.is_some() {
+        for token_id in newly_claimed.iter() {
+            TOKEN_PAID.save(deps.storage, (dist_id, token_id), &distribution.assets)?;
+        }
+        for asset in distribution.assets.clone().iter() {
+            add_asset(
+                &mut distribution.paid,
+                &Asset {
+                    info: asset.info.clone(),
+                    amount: asset.amount * Uint128::new(dist_count as u128),
+                },
+            );
+        }
+    }
+    DISTRIBUTIONS.save(deps.storage, dist_id, &distribution)?;
+
+    let mut messages = vec![];
+    if paid_units > 0 {
+        messages.extend(create_send_assets_messages(
+            &distribution,
+            recipient,
+            paid_units,
+        )?);
+    }
+    if let Some(nft_rewards) = &distribution.nft_rewards {
+        messages.extend(take_nft_rewards(
+            deps.storage,
+            nft_rewards,
+            recipient,
+            Some(dist_count),
+        )?);
+    }
+    Ok(messages)
+}
+
+// Pays the tokens which already claimed a topped up distribution what was added since they did
+pub fn pay_top_up_differences(
+    storage: &mut dyn Storage,
+    dist_id: u64,
+    distribution: &mut Distribution,
+    claimed_tokens: &[String],
+    recipient: &str,
+) -> StdResult<Vec<CosmosMsg>> {
+    let Some(topped_up_from) = distribution.topped_up_from.clone() else {
+        return Ok(vec![]);
+    };
+    let mut to_send: Vec<Asset> = vec![];
+    for token_id in claimed_tokens.iter() {
+        let token_paid = TOKEN_PAID
+            .may_load(storage, (dist_id, token_id))?
+            .unwrap_or_else(|| topped_up_from.clone());
+        let differences: Vec<Asset> = distribution
+            .assets
+            .iter()
+            .map(|asset| {
+                let paid = token_paid
+                    .iter()
+                    .find(|paid| paid.info == asset.info)
+                    .map(|paid| paid.amount)
+                    .unwrap_or_default();
+                Asset {
+                    info: asset.info.clone(),
+                    amount: asset.amount.saturating_sub(paid),
+                }
+            })
+            .filter(|difference| !difference.amount.is_zero())
+            .collect();
+        if differences.is_empty() {
+            continue;
+        }
+        TOKEN_PAID.save(storage, (dist_id, token_id), &distribution.assets)?;
+        for difference in differences.iter() {
+            add_asset(&mut to_send, difference);
+        }
+    }
+    if to_send.is_empty() {
+        return Ok(vec![]);
+    }
+    for asset in to_send.iter() {
+        add_asset(&mut distribution.paid, asset);
+    }
+    DISTRIBUTIONS.save(storage, dist_id, distribution)?;
+    to_send
+        .iter()
+        .map(|asset| transfer_asset_msg(asset, recipient))
+        .collect()
+}
+
 pub fn create_send_assets_messages(
     distribution: &Distribution,
     recipient: &str,
     dist_count: u64,
-) -> Vec<BankMsg> {
+) -> StdResult<Vec<CosmosMsg>> {
     distribution
         .assets
         .iter()
-        .map(|dist| BankMsg::Send {
+        .map(|dist| {
+            transfer_asset_msg(
+                &Asset {
+                    info: dist.info.clone(),
+                    amount: dist.amount * Uint128::new(dist_count as u128),
+                },
+                recipient,
+            )
+        })
+        .collect()
+}
+
+// Bank send for native coins, CW20 transfer for tokens
+pub fn transfer_asset_msg(asset: &Asset, recipient: &str) -> StdResult<CosmosMsg> {
+    Ok(match &asset.info {
+        AssetInfo::Native(denom) => BankMsg::Send {
             to_address: recipient.to_string(),
             amount: vec![Coin {
-                denom: dist.denom.clone(),
-                amount: dist.amount * Uint128::new(dist_count as u128),
+                denom: denom.clone(),
+                amount: asset.amount,
+            }],
+        }
+        .into(),
+        AssetInfo::Cw20(contract_addr) => WasmMsg::Execute {
+            contract_addr: contract_addr.to_string(),
+            msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                recipient: recipient.to_string(),
+                amount: asset.amount,
+            })?,
+            funds: vec![],
+        }
+        .into(),
+    })
+}
+
+pub fn burn_asset_msg(asset: &Asset) -> StdResult<CosmosMsg> {
+    Ok(match &asset.info {
+        AssetInfo::Native(denom) => BankMsg::Burn {
+            amount: vec![Coin {
+                denom: denom.clone(),
+                amount: asset.amount,
             }],
+        }
+        .into(),
+        AssetInfo::Cw20(contract_addr) => WasmMsg::Execute {
+            contract_addr: contract_addr.to_string(),
+            msg: to_binary(&Cw20ExecuteMsg::Burn {
+                amount: asset.amount,
+            })?,
+            funds: vec![],
+        }
+        .into(),
+    })
+}
+
+// Carries out the unclaimed policy of an ended distribution, rollovers are added to the next
+// distribution when it becomes current instead
+pub fn settle_unclaimed(
+    storage: &mut dyn Storage,
+    config: &Config,
+    dist_id: u64,
+    distribution: &Distribution,
+    unclaimed: &[Asset],
+) -> StdResult<Vec<CosmosMsg>> {
+    let mut messages: Vec<CosmosMsg> = Vec::with_capacity(unclaimed.len());
+    match &distribution.unclaimed_policy {
+        UnclaimedPolicy::Distributor
+        | UnclaimedPolicy::Rollover
+        | UnclaimedPolicy::BonusRound { .. } => {
+            for asset in unclaimed.iter() {
+                messages.push(transfer_asset_msg(asset, config.distributor.as_str())?);
+            }
+        }
+        UnclaimedPolicy::Address { address } => {
+            for asset in unclaimed.iter() {
+                messages.push(transfer_asset_msg(asset, address)?);
+            }
+        }
+        UnclaimedPolicy::Burn => {
+            for asset in unclaimed.iter() {
+                messages.push(burn_asset_msg(asset)?);
+            }
+        }
+        UnclaimedPolicy::CommunityPool => {
+            let mut coins: Vec<Coin> = vec![];
+            for asset in unclaimed.iter() {
+                match &asset.info {
+                    AssetInfo::Native(denom) => coins.push(Coin {
+                        denom: denom.clone(),
+                        amount: asset.amount,
+                    }),
+                    AssetInfo::Cw20(_) => {
+                        messages.push(transfer_asset_msg(asset, config.distributor.as_str())?)
+                    }
+                }
+            }
+            if !coins.is_empty() {
+                messages.push(create_fund_community_pool_msg(coins));
+            }
+        }
+        UnclaimedPolicy::ProRata => {
+            // The wallets pull their share with ClaimProRata
+            let total_tokens = Uint128::new(
+                PRO_RATA_TOKENS
+                    .may_load(storage, dist_id)?
+                    .unwrap_or_default() as u128,
+            );
+            let mut shares: Vec<Asset> = vec![];
+            for asset in unclaimed.iter() {
+                let share = asset.amount.checked_div(total_tokens).unwrap_or_default();
+                if !share.is_zero() {
+                    shares.push(Asset {
+                        info: asset.info.clone(),
+                        amount: share,
+                    });
+                }
+                // Rounding leftovers, or everything when nobody claimed, go back to the distributor
+                let remaining = asset.amount - share * total_tokens;
+                if !remaining.is_zero() {
+                    messages.push(transfer_asset_msg(
+                        &Asset {
+                            info: asset.info.clone(),
+                            amount: remaining,
+                        },
+                        config.distributor.as_str(),
+                    )?);
+                }
+            }
+            if !shares.is_empty() {
+                PRO_RATA_SHARES.save(storage, dist_id, &shares)?;
+            }
+        }
+    }
+    Ok(messages)
+}
+
+pub fn query_asset_balance(
+    querier: &QuerierWrapper,
+    asset_info: &AssetInfo,
+    address: &str,
+) -> StdResult<Uint128> {
+    match asset_info {
+        AssetInfo::Native(denom) => Ok(querier.query_balance(address, denom)?.amount),
+        AssetInfo::Cw20(contract_addr) => {
+            let balance: cw20::BalanceResponse = querier.query_wasm_smart(
+                contract_addr,
+                &Cw20QueryMsg::Balance {
+                    address: address.to_string(),
+                },
+            )?;
+            Ok(balance.balance)
+        }
+    }
+}
+
+pub fn query_cw20_token_info(
+    querier: &QuerierWrapper,
+    contract_addr: &Addr,
+) -> StdResult<cw20::TokenInfoResponse> {
+    querier.query_wasm_smart(contract_addr, &Cw20QueryMsg::TokenInfo {})
+}
+
+pub fn transfer_nft_msg(
+    collection: &Addr,
+    token_id: &str,
+    recipient: &str,
+) -> StdResult<CosmosMsg> {
+    Ok(WasmMsg::Execute {
+        contract_addr: collection.to_string(),
+        msg: to_binary(&cw721::Cw721ExecuteMsg::TransferNft {
+            recipient: recipient.to_string(),
+            token_id: token_id.to_string(),
+        })?,
+        funds: vec![],
+    }
+    .into())
+}
+
+// Takes NFTs out of the distribution's pool (all of them if no count) and sends them to the recipient
+pub fn take_nft_rewards(
+    storage: &mut dyn Storage,
+    nft_rewards: &NftRewards,
+    recipient: &str,
+    count: Option<u64>,
+) -> Result<Vec<CosmosMsg>, ContractError> {
+    let token_ids = NFT_POOLS
+        .prefix(nft_rewards.pool_id)
+        .keys(storage, None, None, Order::Ascending)
+        .take(count.unwrap_or(u64::MAX) as usize)
+        .collect::<StdResult<Vec<String>>>()?;
+    if let Some(count) = count {
+        ensure!(
+            token_ids.len() as u64 == count,
+            ContractError::InvalidClaimValue {}
+        );
+    }
+    token_ids
+        .iter()
+        .map(|token_id| {
+            NFT_POOLS.remove(storage, (nft_rewards.pool_id, token_id));
+            Ok(transfer_nft_msg(
+                &nft_rewards.collection,
+                token_id,
+                recipient,
+            )?)
         })
         .collect()
 }
+
+// Slot and bit of a token in the claim bitmaps, None if the token is tracked in the claims map
+fn bitmap_position(claim_storage: &ClaimStorage, token_id: &str) -> Option<(u64, usize)> {
+    match claim_storage {
+        ClaimStorage::Map => None,
+        ClaimStorage::Bitmap => {
+            let numeric_id = token_id.parse::<u64>().ok()?;
+            // "007" and "7" are different tokens so only the canonical form goes in the bitmap
+            if numeric_id.to_string() != token_id {
+                return None;
+            }
+            Some((
+                numeric_id / BITMAP_SLOT_BITS,
+                (numeric_id % BITMAP_SLOT_BITS) as usize,
+            ))
+        }
+    }
+}
+
+fn load_bitmap_slot(storage: &dyn Storage, dist_id: u64, slot: u64) -> StdResult<Vec<u8>> {
+    Ok(CLAIM_BITMAPS
+        .may_load(storage, (dist_id, slot))?
+        .map(|bitmap| bitmap.to_vec())
+        .unwrap_or_else(|| vec![0u8; (BITMAP_SLOT_BITS / 8) as usize]))
+}
+
+// Wallet claims are tracked for the wallet caps and to split the unclaimed pro rata
+pub fn track_wallet_claim(
+    storage: &mut dyn Storage,
+    dist_id: u64,
+    distribution: &Distribution,
+    wallet: &str,
+    tokens: u64,
+) -> StdResult<()> {
+    if distribution.wallet_cap.is_some()
+        || distribution.unclaimed_policy == UnclaimedPolicy::ProRata
+    {
+        WALLET_CLAIMS.update(storage, (dist_id, wallet), |wallet_claim| -> StdResult<_> {
+            let mut wallet_claim = wallet_claim.unwrap_or_default();
+            wallet_claim.tokens += tokens;
+            Ok(wallet_claim)
+        })?;
+    }
+    if distribution.unclaimed_policy == UnclaimedPolicy::ProRata {
+        PRO_RATA_TOKENS.update(storage, dist_id, |total| -> StdResult<_> {
+            Ok(total.unwrap_or_default() + tokens)
+        })?;
+    }
+    Ok(())
+}
+
+// Unclaimed tokens the wallet can still claim under the distribution's cap, None once the cap
+// refuses all of them
+pub fn wallet_allowance(
+    storage: &dyn Storage,
+    claim_storage: &ClaimStorage,
+    dist_id: u64,
+    distribution: &Distribution,
+    wallet: &str,
+    token_ids: Vec<String>,
+) -> StdResult<Option<Vec<String>>> {
+    let Some(wallet_cap) = &distribution.wallet_cap else {
+        return Ok(Some(token_ids));
+    };
+    let mut unclaimed_tokens: Vec<String> = Vec::with_capacity(token_ids.len());
+    for token_id in token_ids {
+        if !is_claimed(storage, claim_storage, dist_id, &token_id)? {
+            unclaimed_tokens.push(token_id);
+        }
+    }
+    if unclaimed_tokens.is_empty() {
+        return Ok(Some(unclaimed_tokens));
+    }
+
+    let wallet_claim = WALLET_CLAIMS
+        .may_load(storage, (dist_id, wallet))?
+        .unwrap_or_default();
+    let amount_reached = match &wallet_cap.max_amount_per_wallet {
+        Some(caps) => caps.iter().all(|cap| {
+            wallet_claim
+                .amounts
+                .iter()
+                .any(|received| received.info == cap.info && received.amount >= cap.amount)
+        }),
+        None => false,
+    };
+    let tokens_left = wallet_cap
+        .max_tokens_per_wallet
+        .map_or(u64::MAX, |max_tokens| {
+            max_tokens.saturating_sub(wallet_claim.tokens)
+        });
+    if amount_reached || tokens_left == 0 {
+        return Ok(None);
+    }
+    unclaimed_tokens.truncate(tokens_left.min(unclaimed_tokens.len() as u64) as usize);
+    Ok(Some(unclaimed_tokens))
+}
+
+pub fn is_claimed(
+    storage: &dyn Storage,
+    claim_storage: &ClaimStorage,
+    dist_id: u64,
+    token_id: &str,
+) -> StdResult<bool> {
+    match bitmap_position(claim_storage, token_id) {
+        Some((slot, bit)) => {
+            let bitmap = load_bitmap_slot(storage, dist_id, slot)?;
+            Ok(bitmap[bit / 8] & (1u8 << (bit % 8)) != 0)
+        }
+        None => Ok(CLAIMS.has(storage, (dist_id, token_id))),
+    }
+}
+
+// Marks the tokens as claimed and returns the ones which were not claimed yet
+// Bitmap slots are loaded and saved once per claim, whatever the number of tokens in them
+pub fn mark_claimed(
+    storage: &mut dyn Storage,
+    claim_storage: &ClaimStorage,
+    dist_id: u64,
+    token_ids: &[String],
+) -> StdResult<Vec<String>> {
+    let mut newly_claimed: Vec<String> = Vec::with_capacity(token_ids.len());
+    let mut updated_slots: BTreeMap<u64, Vec<u8>> = BTreeMap::new();
+    for token_id in token_ids.iter() {
+        match bitmap_position(claim_storage, token_id) {
+            Some((slot, bit)) => {
+                let bitmap = match updated_slots.entry(slot) {
+                    Entry::Occupied(entry) => entry.into_mut(),
+                    Entry::Vacant(entry) => entry.insert(load_bitmap_slot(storage, dist_id, slot)?),
+                };
+                if bitmap[bit / 8] & (1u8 << (bit % 8)) == 0 {
+                    bitmap[bit / 8] |= 1u8 << (bit % 8);
+                    newly_claimed.push(token_id.to_string());
+                }
+            }
+            None => {
+                if !CLAIMS.has(storage, (dist_id, token_id)) {
+                    CLAIMS.save(storage, (dist_id, token_id), &true)?;
+                    newly_claimed.push(token_id.to_string());
+                }
+            }
+        }
+    }
+    for (slot, bitmap) in updated_slots {
+        CLAIM_BITMAPS.save(storage, (dist_id, slot), &Binary(bitmap))?;
+    }
+    Ok(newly_claimed)
+}
+
+fn load_active_distributions(storage: &dyn Storage) -> StdResult<Vec<(u64, Distribution)>> {
+    ACTIVE_DISTRIBUTIONS
+        .keys(storage, None, None, Order::Ascending)
+        .map(|dist_id| {
+            let dist_id = dist_id?;
+            Ok((dist_id, DISTRIBUTIONS.load(storage, dist_id)?))
+        })
+        .collect()
+}
+
+// Distributions that can currently be claimed
+pub fn load_open_distributions(
+    storage: &dyn Storage,
+    env: &Env,
+) -> StdResult<Vec<(u64, Distribution)>> {
+    let mut distributions = load_active_distributions(storage)?;
+    distributions.retain(|(_, distribution)| env.block.time <= distribution.end_time);
+    Ok(distributions)
+}
+
+// Distributions whose window closed but whose unclaimed was not settled yet
+pub fn load_ended_distributions(
+    storage: &dyn Storage,
+    env: &Env,
+) -> StdResult<Vec<(u64, Distribution)>> {
+    let mut distributions = load_active_distributions(storage)?;
+    distributions.retain(|(_, distribution)| env.block.time > distribution.end_time);
+    Ok(distributions)
+}
+
+// Adds the asset to the list, merging it with the same asset if present
+pub fn add_asset(assets: &mut Vec<Asset>, asset: &Asset) {
+    match assets
+        .iter_mut()
+        .find(|existing| existing.info == asset.info)
+    {
+        Some(existing) => existing.amount += asset.amount,
+        None => assets.push(asset.clone()),
+    }
+}
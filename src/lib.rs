@@ -4,4 +4,15 @@ pub mod executes;
 pub mod helpers;
 pub mod msg;
 pub mod state;
+// The tests keep the style they were written in
+#[cfg(test)]
+#[allow(
+    clippy::module_inception,
+    clippy::bool_assert_comparison,
+    clippy::clone_on_copy,
+    clippy::cmp_owned,
+    clippy::comparison_to_empty,
+    clippy::len_zero,
+    clippy::unnecessary_unwrap
+)]
 mod tests;
@@ -1,30 +1,299 @@
 use cosmwasm_schema::{cw_serde, QueryResponses};
-use cosmwasm_std::{Coin, Timestamp};
+use cosmwasm_std::Timestamp;
+use cw20::Cw20ReceiveMsg;
+use cw721::Cw721ReceiveMsg;
+
+use crate::state::{Asset, ClaimStorage, PauseScope, Tier, TraitMultiplier, UnclaimedPolicy};
 
 #[cw_serde]
 pub struct InstantiateMsg {
     pub admin: String,
     pub distributor: String,
     pub nft_address: String,
+    pub claim_storage: Option<ClaimStorage>,
+    // Defaults to the admin
+    pub pauser: Option<String>,
 }
 
 #[cw_serde]
 pub enum ExecuteMsg {
+    // Claims every open distribution unless dist_ids is set
     BulkClaim {
         token_ids: Vec<String>,
         start_after: Option<String>,
+        dist_ids: Option<Vec<u64>>,
     },
     ClaimFive {
         token_ids: Vec<String>,
+        dist_ids: Option<Vec<u64>>,
     },
     Distribute {
-        assets_per_nft: Vec<Coin>,
+        assets_per_nft: Vec<Asset>,
+        distribution_end_time: Timestamp,
+        unclaimed_policy: UnclaimedPolicy,
+        minimum_nfts_to_claim: u64,
+        // Queued until then if set in the future
+        start_time: Option<Timestamp>,
+        // Tokens over the cap are not claimed, amounts over it are withheld
+        max_tokens_per_wallet: Option<u64>,
+        max_amount_per_wallet: Option<Vec<Asset>>,
+        // Tokens with a higher numeric id cannot claim, e.g. the ones minted afterwards
+        max_token_id: Option<u64>,
+        // Pays the owners of this snapshot instead of the current ones
+        snapshot_id: Option<u64>,
+    },
+    // Adds to the per NFT amounts of the current distribution, tokens which claimed get the difference
+    TopUp {
+        assets_per_nft: Vec<Asset>,
+    },
+    // Splits the native coins sent between the tokens, the remainders are kept as dust
+    DistributePool {
+        distribution_end_time: Timestamp,
+        unclaimed_policy: UnclaimedPolicy,
+        minimum_nfts_to_claim: u64,
+        start_time: Option<Timestamp>,
+        // Pays the owners of this snapshot instead of the current ones
+        snapshot_id: Option<u64>,
+    },
+    // Per NFT amounts for the holders of each collection, the configured one included
+    DistributeCollections {
+        collections: Vec<CollectionAssets>,
+        distribution_end_time: Timestamp,
+        unclaimed_policy: UnclaimedPolicy,
+        start_time: Option<Timestamp>,
+    },
+    // Pays the assets per NFT times the multiplier of the holder's tier, funded for the highest one
+    DistributeTiered {
+        assets_per_nft: Vec<Asset>,
+        tiers: Vec<Tier>,
+        distribution_end_time: Timestamp,
+        unclaimed_policy: UnclaimedPolicy,
+        minimum_nfts_to_claim: u64,
+        start_time: Option<Timestamp>,
+    },
+    // Pays the assets per complete set of tokens, funded for the declared maximum number of sets
+    DistributeSets {
+        assets_per_set: Vec<Asset>,
+        components: Vec<RequiredToken>,
+        max_sets: u64,
+        distribution_end_time: Timestamp,
+        unclaimed_policy: UnclaimedPolicy,
+        start_time: Option<Timestamp>,
+    },
+    // One token per component in each set, in order, the tokens of a claimed set are used
+    ClaimSets {
+        dist_id: u64,
+        sets: Vec<Vec<CollectionToken>>,
+    },
+    // Admin registration of another collection whose holders can be rewarded
+    AddCollection {
+        collection: String,
+    },
+    // Claims with (collection, token_id) pairs, owners are checked like BulkClaim above five tokens
+    ClaimCollections {
+        tokens: Vec<CollectionToken>,
+        dist_ids: Option<Vec<u64>>,
+    },
+    // One deposited NFT of the collection per token, the unclaimed ones go back to the distributor
+    DistributeNfts {
+        collection: String,
+        distribution_end_time: Timestamp,
+        minimum_nfts_to_claim: u64,
+        start_time: Option<Timestamp>,
+        // Pays the owners of this snapshot instead of the current ones
+        snapshot_id: Option<u64>,
+    },
+    // Per token amounts committed in a merkle root, funded with the totals
+    DistributeMerkle {
+        merkle_root: String,
+        total_assets: Vec<Asset>,
+        distribution_end_time: Timestamp,
+        unclaimed_policy: UnclaimedPolicy,
+        minimum_nfts_to_claim: u64,
+        start_time: Option<Timestamp>,
+    },
+    // Pays the assets per unit of weight of the frozen weight table
+    DistributeWeighted {
+        assets_per_weight: Vec<Asset>,
+        distribution_end_time: Timestamp,
+        unclaimed_policy: UnclaimedPolicy,
+        minimum_nfts_to_claim: u64,
+        start_time: Option<Timestamp>,
+        // Pays the owners of this snapshot instead of the current ones
+        snapshot_id: Option<u64>,
+    },
+    // Pays the assets per unit of multiplier, funded for the declared total weight of all the tokens
+    DistributeByTraits {
+        assets_per_weight: Vec<Asset>,
+        total_weight: u64,
         distribution_end_time: Timestamp,
-        unclaimed_to_distributor: bool,
+        unclaimed_policy: UnclaimedPolicy,
+        minimum_nfts_to_claim: u64,
+        start_time: Option<Timestamp>,
+        // Pays the owners of this snapshot instead of the current ones
+        snapshot_id: Option<u64>,
+    },
+    // Replaces the multipliers used by the next trait distributions
+    SetTraitMultipliers {
+        trait_multipliers: Vec<TraitMultiplier>,
+    },
+    // Admin upload of the weight table in chunks, until it is frozen
+    UploadWeights {
+        weights: Vec<TokenWeight>,
+        ranges: Vec<WeightRange>,
+    },
+    FreezeWeights {},
+    ClaimWithProofs {
+        dist_id: u64,
+        claims: Vec<MerkleClaim>,
+    },
+    // Admin upload of the owners at a past time in chunks, or of the merkle root of the owners
+    // Distributions funded afterwards pay the snapshot owners
+    UploadSnapshot {
+        snapshot_time: Timestamp,
+        owners: Vec<SnapshotOwner>,
+        merkle_root: Option<String>,
+    },
+    // Distributions funded afterwards pay the current owners again
+    ClearSnapshot {},
+    // Claims of a snapshot distribution, the proofs are only used with a merkle root
+    ClaimFromSnapshot {
+        dist_id: u64,
+        claims: Vec<SnapshotClaim>,
+    },
+    // Pro rata share of the unclaimed for tokens which claimed the distribution in time
+    ClaimBonus {
+        dist_id: u64,
+        token_ids: Vec<String>,
+    },
+    // Share of the unclaimed of a pro rata distribution for the tokens the wallet claimed
+    ClaimProRata {
+        dist_id: u64,
+    },
+    CancelQueuedDistribution {
+        queue_id: u64,
+    },
+    // Closes the claims and refunds the unclaimed to the distributor, defaults to the latest
+    // distribution
+    CancelDistribution {
+        dist_id: Option<u64>,
+    },
+    // Admin amendments of the current distribution while it is open
+    // The window cannot be extended into the next queued round
+    ExtendDistributionWindow {
+        end_time: Timestamp,
+    },
+    // Defaults to now, never before it
+    CloseDistributionWindow {
+        end_time: Option<Timestamp>,
+    },
+    SetMinimumNftsToClaim {
         minimum_nfts_to_claim: u64,
     },
-    HaltDistribution {},
-    ReturnUnclaimed {},
+    // Pauser only, lifted by Unpause or automatically from `until`
+    Pause {
+        scope: PauseScope,
+        reason: String,
+        until: Option<Timestamp>,
+    },
+    Unpause {
+        scope: PauseScope,
+    },
+    // Admin only, rotates the pauser or revokes it with None
+    SetPauser {
+        pauser: Option<String>,
+    },
+    // Defaults to the latest distribution
+    ReturnUnclaimed {
+        dist_id: Option<u64>,
+    },
+    PruneClaims {
+        dist_id: u64,
+        limit: Option<u32>,
+    },
+    // CW20 funding, the hook is a ReceiveMsg
+    Receive(Cw20ReceiveMsg),
+    // NFT deposits, the hook is a ReceiveNftMsg
+    ReceiveNft(Cw721ReceiveMsg),
+    // Returns the CW20 and NFT deposits not used by a distribution yet
+    WithdrawDeposits {},
+    // Sends the rollover dust to the admin
+    WithdrawDust {},
+}
+
+// Data of the DistributePool response
+#[cw_serde]
+pub struct DistributePoolResponse {
+    pub assets_per_nft: Vec<Asset>,
+    pub dust: Vec<Asset>,
+}
+
+#[cw_serde]
+pub struct MerkleClaim {
+    pub token_id: String,
+    pub amounts: Vec<Asset>,
+    // Hex sha256 hashes from the leaf up to the root
+    pub proof: Vec<String>,
+}
+
+#[cw_serde]
+pub struct CollectionAssets {
+    pub collection: String,
+    pub assets_per_nft: Vec<Asset>,
+    pub minimum_nfts_to_claim: u64,
+    pub max_token_id: Option<u64>,
+}
+
+#[cw_serde]
+pub struct CollectionToken {
+    pub collection: String,
+    pub token_id: String,
+}
+
+// A token of the collection having all the (trait_type, value) traits
+#[cw_serde]
+pub struct RequiredToken {
+    pub collection: String,
+    pub traits: Vec<(String, String)>,
+}
+
+#[cw_serde]
+pub struct SnapshotOwner {
+    pub token_id: String,
+    pub owner: String,
+}
+
+#[cw_serde]
+pub struct SnapshotClaim {
+    pub token_id: String,
+    // Hex sha256 hashes from the "{token_id}:{owner}" leaf up to the root
+    pub proof: Vec<String>,
+}
+
+#[cw_serde]
+pub struct TokenWeight {
+    pub token_id: String,
+    pub weight: u64,
+}
+
+// Numeric token ids from start to end, included
+#[cw_serde]
+pub struct WeightRange {
+    pub start: u64,
+    pub end: u64,
+    pub weight: u64,
+}
+
+#[cw_serde]
+pub enum ReceiveMsg {
+    // Kept until a Distribute uses the token
+    Deposit {},
+}
+
+#[cw_serde]
+pub enum ReceiveNftMsg {
+    // Kept until a DistributeNfts uses the collection
+    Deposit {},
 }
 
 #[cw_serde]
@@ -36,6 +305,62 @@ pub enum QueryMsg {
     GetCurrentDistributionInfo {},
     #[returns(bool)]
     GetIfClaimed { token_id: String },
+    #[returns(crate::state::Distribution)]
+    GetDistribution { dist_id: u64 },
+    #[returns(Vec<u64>)]
+    GetOpenDistributions {},
+    #[returns(Vec<(u64, crate::state::QueuedDistribution)>)]
+    GetQueuedDistributions {},
+    #[returns(bool)]
+    GetIfClaimedInDistribution { dist_id: u64, token_id: String },
+    #[returns(bool)]
+    GetIfClaimedBonus { dist_id: u64, token_id: String },
+    // Per NFT amounts paid to the token, the difference with the assets can be claimed
+    #[returns(Vec<crate::state::Asset>)]
+    GetTokenPaid { dist_id: u64, token_id: String },
+    #[returns(Vec<crate::state::Asset>)]
+    GetDeposits {},
+    #[returns(Vec<crate::state::Asset>)]
+    GetDust {},
+    #[returns(u64)]
+    GetTokenWeight { token_id: String },
+    #[returns(crate::state::WeightTable)]
+    GetWeightTable {},
+    #[returns(Vec<crate::state::TraitMultiplier>)]
+    GetTraitMultipliers {},
+    // Multiplier of the token with the current trait multipliers
+    #[returns(cosmwasm_std::Decimal)]
+    GetTokenMultiplier { token_id: String },
+    #[returns(Vec<(cosmwasm_std::Addr, crate::state::CollectionInfo)>)]
+    GetCollections {},
+    // Claims of the configured collection are also answered
+    #[returns(bool)]
+    GetIfClaimedInCollection {
+        dist_id: u64,
+        collection: String,
+        token_id: String,
+    },
+    // Snapshot used by the next distributions
+    #[returns(Option<crate::state::Snapshot>)]
+    GetSnapshot {},
+    // Owner uploaded for the token, defaults to the current snapshot
+    #[returns(Option<cosmwasm_std::Addr>)]
+    GetSnapshotOwner {
+        snapshot_id: Option<u64>,
+        token_id: String,
+    },
+    // Claimed by the wallet in a distribution with a wallet cap
+    #[returns(crate::state::WalletClaim)]
+    GetWalletClaim { dist_id: u64, wallet: String },
+    // Share the wallet can still pull from a settled pro rata distribution
+    #[returns(Vec<crate::state::Asset>)]
+    GetProRataShare { dist_id: u64, wallet: String },
+    #[returns(Vec<String>)]
+    GetNftDeposits {
+        collection: String,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
 }
 
 #[cw_serde]
@@ -1,6 +1,7 @@
 use cosmwasm_schema::cw_serde;
-use cosmwasm_std::{Addr, Coin, Timestamp};
+use cosmwasm_std::{Addr, Binary, Coin, Decimal, Timestamp, Uint128};
 use cw_storage_plus::{Item, Map};
+use std::fmt;
 
 #[cw_serde]
 pub struct Config {
@@ -8,22 +9,414 @@ pub struct Config {
     pub distributor: Addr,
     pub current_dist_id: u64,
     pub nft_address: Addr,
+    // Refreshed from NumTokens whenever a distribution is funded
     pub nft_count: u64,
     pub current_dist_end_time: Option<Timestamp>,
-    pub current_dist_halted: bool,
+    // Sets and lifts the pauses, nobody once revoked by the admin
+    pub pauser: Option<Addr>,
+    pub pauses: Pauses,
+    pub claim_storage: ClaimStorage,
+    pub last_queue_id: u64,
+    pub last_nft_pool_id: u64,
+    pub last_snapshot_id: u64,
+}
+
+#[cw_serde]
+#[derive(Default)]
+pub enum ClaimStorage {
+    // One entry per claimed token id
+    #[default]
+    Map,
+    // One bit per token for canonical numeric token ids (1..=N), falls back to Map for any other id
+    Bitmap,
 }
 
 pub const CONFIG: Item<Config> = Item::new("config");
 
 #[cw_serde]
+pub enum PauseScope {
+    // Every claim, bonus claims included
+    Claim,
+    // Distributions, top ups and the activation of the queued ones
+    Fund,
+    // ReturnUnclaimed
+    Return,
+}
+
+impl fmt::Display for PauseScope {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            PauseScope::Claim => write!(f, "claim"),
+            PauseScope::Fund => write!(f, "fund"),
+            PauseScope::Return => write!(f, "return"),
+        }
+    }
+}
+
+#[cw_serde]
+pub struct Pause {
+    pub reason: String,
+    // Lifted automatically from then on
+    pub until: Option<Timestamp>,
+}
+
+#[cw_serde]
+#[derive(Default)]
+pub struct Pauses {
+    pub claim: Option<Pause>,
+    pub fund: Option<Pause>,
+    pub r#return: Option<Pause>,
+}
+
+impl Pauses {
+    pub fn scope_mut(&mut self, scope: &PauseScope) -> &mut Option<Pause> {
+        match scope {
+            PauseScope::Claim => &mut self.claim,
+            PauseScope::Fund => &mut self.fund,
+            PauseScope::Return => &mut self.r#return,
+        }
+    }
+
+    pub fn is_paused(&self, scope: &PauseScope, now: Timestamp) -> bool {
+        let pause = match scope {
+            PauseScope::Claim => &self.claim,
+            PauseScope::Fund => &self.fund,
+            PauseScope::Return => &self.r#return,
+        };
+        // Lifted on its own once the unpause time has come
+        match pause {
+            Some(Pause {
+                until: Some(until), ..
+            }) => now < *until,
+            Some(_) => true,
+            None => false,
+        }
+    }
+}
+
+#[cw_serde]
+#[derive(Eq, Hash)]
+pub enum AssetInfo {
+    Native(String),
+    Cw20(Addr),
+}
+
+impl fmt::Display for AssetInfo {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            AssetInfo::Native(denom) => write!(f, "{denom}"),
+            AssetInfo::Cw20(contract_addr) => write!(f, "{contract_addr}"),
+        }
+    }
+}
+
+// Native coin or CW20 token amount
+#[cw_serde]
+pub struct Asset {
+    pub info: AssetInfo,
+    pub amount: Uint128,
+}
+
+impl Asset {
+    pub fn native(denom: impl Into<String>, amount: impl Into<Uint128>) -> Self {
+        Asset {
+            info: AssetInfo::Native(denom.into()),
+            amount: amount.into(),
+        }
+    }
+
+    pub fn cw20(contract_addr: Addr, amount: impl Into<Uint128>) -> Self {
+        Asset {
+            info: AssetInfo::Cw20(contract_addr),
+            amount: amount.into(),
+        }
+    }
+}
+
+impl From<Coin> for Asset {
+    fn from(coin: Coin) -> Self {
+        Asset::native(coin.denom, coin.amount)
+    }
+}
+
+impl fmt::Display for Asset {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}{}", self.amount, self.info)
+    }
+}
+
+// Where what was not claimed goes once the distribution has ended
+#[cw_serde]
+#[derive(Default)]
+pub enum UnclaimedPolicy {
+    #[default]
+    Distributor,
+    // Added to the per NFT amounts of the next distribution paying a flat amount per token
+    Rollover,
+    Address {
+        address: String,
+    },
+    Burn,
+    // Split between the wallets which claimed, by the number of tokens they claimed
+    ProRata,
+    // Native coins only, CW20 tokens go back to the distributor
+    CommunityPool,
+    // Split between the tokens which claimed, claimed with ClaimBonus until the end time, what is
+    // left then goes back to the distributor
+    BonusRound {
+        end_time: Timestamp,
+    },
+}
+
+// Opened by the first bonus claim once the claiming window has ended
+#[cw_serde]
+pub struct BonusRound {
+    // Unclaimed when the claiming window ended
+    pub pool: Vec<Asset>,
+    pub assets_per_token: Vec<Asset>,
+    pub claimed: u64,
+}
+
+// (dist_id, token_id) tokens paid by the bonus round
+pub const BONUS_CLAIMS: Map<(u64, &str), bool> = Map::new("bonus_claims");
+
+#[cw_serde]
+#[derive(Default)]
 pub struct Distribution {
-    pub assets: Vec<Coin>,
+    pub assets: Vec<Asset>,
     pub claimed: u64,
-    pub unclaimed_to_distributor: bool,
-    pub unclaimed_sent_to_distributor: Option<bool>,
+    pub unclaimed_policy: UnclaimedPolicy,
+    // Set once the unclaimed policy was carried out
+    pub unclaimed_settled: bool,
     pub minimum_nfts_to_claim: u64,
+    pub start_time: Timestamp,
+    pub end_time: Timestamp,
+    // Tokens of the collection when it was funded and, for numeric ids, the highest eligible id
+    pub nft_count: u64,
+    pub max_token_id: Option<u64>,
+    // Per NFT amounts carried over from the previous distribution (already included in assets)
+    pub rollover: Vec<Asset>,
+    // One NFT of another collection per token, on top of the assets
+    pub nft_rewards: Option<NftRewards>,
+    // Hex sha256 root of the per token amounts, assets are then the committed totals
+    pub merkle_root: Option<String>,
+    // Paid out of the committed totals of a merkle distribution
+    pub paid: Vec<Asset>,
+    // Weighted distributions pay the assets per unit of weight of the frozen weight table
+    pub total_weight: Option<u64>,
+    pub claimed_weight: u64,
+    // Trait distributions pay the assets times the token's multiplier, out of the declared total weight
+    pub trait_multipliers: Option<Vec<TraitMultiplier>>,
+    // Snapshot distributions pay the owners at the snapshot time instead of the current owners
+    pub snapshot: Option<Snapshot>,
+    // Per NFT amounts of the holders of other collections, on top of the configured one
+    pub collection_rewards: Vec<CollectionRewards>,
+    // Set distributions pay the assets per complete set of tokens, out of the maximum number of sets
+    pub set_rewards: Option<SetRewards>,
+    // Tiered distributions pay the assets times the multiplier of the holder's tier per token
+    pub tiers: Option<Vec<Tier>>,
+    // Limits what a single wallet can claim, what it holds over that is withheld
+    pub wallet_cap: Option<WalletCap>,
+    // Withheld by the wallet cap, follows the unclaimed policy
+    pub capped: Vec<Asset>,
+    pub bonus_round: Option<BonusRound>,
+    // Per NFT amounts before the first top up, paid to the tokens claimed before it
+    pub topped_up_from: Option<Vec<Asset>>,
+    // Set when the admin cancelled it, what was not claimed then went back to the distributor
+    pub cancelled_at: Option<Timestamp>,
 }
 
-pub const DISTRIBUTION: Item<Distribution> = Item::new("distribution");
+impl Distribution {
+    // Top ups raise the per NFT amounts of the tokens paid a flat amount with the regular claims
+    pub fn can_be_topped_up(&self) -> bool {
+        self.merkle_root.is_none()
+            && self.total_weight.is_none()
+            && self.set_rewards.is_none()
+            && self.tiers.is_none()
+            && self.snapshot.is_none()
+            && self.collection_rewards.is_empty()
+            && self.wallet_cap.is_none()
+    }
+}
+
+#[cw_serde]
+pub struct WalletCap {
+    pub max_tokens_per_wallet: Option<u64>,
+    pub max_amount_per_wallet: Option<Vec<Asset>>,
+}
+
+// Tokens claimed and amounts received by a wallet in a distribution
+#[cw_serde]
+#[derive(Default)]
+pub struct WalletClaim {
+    pub tokens: u64,
+    pub amounts: Vec<Asset>,
+}
+
+// (dist_id, wallet) only tracked for distributions with a wallet cap or splitting the unclaimed
+// pro rata
+pub const WALLET_CLAIMS: Map<(u64, &str), WalletClaim> = Map::new("wallet_claims");
+
+// Tokens tracked in WALLET_CLAIMS by a pro rata distribution
+pub const PRO_RATA_TOKENS: Map<u64, u64> = Map::new("pro_rata_tokens");
+
+// Per token share of the unclaimed of a settled pro rata distribution, pulled by the wallets
+pub const PRO_RATA_SHARES: Map<u64, Vec<Asset>> = Map::new("pro_rata_shares");
+
+// Wallets holding at least min_nfts are in the tier
+#[cw_serde]
+pub struct Tier {
+    pub min_nfts: u64,
+    pub multiplier: Decimal,
+}
+
+#[cw_serde]
+pub struct SetRewards {
+    pub components: Vec<SetComponent>,
+    pub max_sets: u64,
+    pub claimed_sets: u64,
+}
+
+// One token of the collection having all the (trait_type, value) traits
+#[cw_serde]
+pub struct SetComponent {
+    pub collection: Addr,
+    pub traits: Vec<(String, String)>,
+}
+
+#[cw_serde]
+pub struct CollectionRewards {
+    pub collection: Addr,
+    pub assets: Vec<Asset>,
+    pub minimum_nfts_to_claim: u64,
+    pub nft_count: u64,
+    pub max_token_id: Option<u64>,
+    pub claimed: u64,
+}
+
+// Multiplier of the tokens having the trait, the multipliers of all the matching traits are multiplied
+#[cw_serde]
+pub struct TraitMultiplier {
+    pub trait_type: String,
+    pub value: String,
+    pub multiplier: Decimal,
+}
+
+#[cw_serde]
+pub struct NftRewards {
+    pub collection: Addr,
+    pub pool_id: u64,
+}
+
+// Every distribution is kept under its id so past rounds can still be queried
+pub const DISTRIBUTIONS: Map<u64, Distribution> = Map::new("distributions");
+
+// Funded distribution waiting for its start time
+#[cw_serde]
+pub struct QueuedDistribution {
+    pub assets_per_nft: Vec<Asset>,
+    pub funds: Vec<Asset>,
+    pub start_time: Timestamp,
+    pub end_time: Timestamp,
+    pub nft_count: u64,
+    pub max_token_id: Option<u64>,
+    pub unclaimed_policy: UnclaimedPolicy,
+    pub minimum_nfts_to_claim: u64,
+    pub nft_rewards: Option<NftRewards>,
+    pub merkle_root: Option<String>,
+    pub total_weight: Option<u64>,
+    pub trait_multipliers: Option<Vec<TraitMultiplier>>,
+    pub snapshot: Option<Snapshot>,
+    pub collection_rewards: Vec<CollectionRewards>,
+    pub set_rewards: Option<SetRewards>,
+    pub tiers: Option<Vec<Tier>>,
+    pub wallet_cap: Option<WalletCap>,
+}
+
+impl QueuedDistribution {
+    // Rollovers are added to the per NFT amounts so they need a flat amount per token
+    pub fn pays_flat_amount(&self) -> bool {
+        self.merkle_root.is_none()
+            && self.total_weight.is_none()
+            && self.set_rewards.is_none()
+            && self.tiers.is_none()
+    }
+}
+
+// FIFO of the scheduled distributions, by queue id
+pub const QUEUE: Map<u64, QueuedDistribution> = Map::new("queue");
+
+// Remainders of the rollovers and pools not adding up to a whole unit per NFT, carried to the next
+// rollovers
+pub const DUST: Item<Vec<Asset>> = Item::new("dust");
+
+// CW20 tokens deposited by the distributor for its next distribution
+pub const CW20_DEPOSITS: Map<&Addr, Uint128> = Map::new("cw20_deposits");
+
+// NFTs of other collections deposited by the distributor, by (collection, token_id)
+pub const NFT_DEPOSITS: Map<(&Addr, &str), bool> = Map::new("nft_deposits");
+
+// (pool_id, token_id) NFTs set aside for a distribution and not claimed yet
+pub const NFT_POOLS: Map<(u64, &str), bool> = Map::new("nft_pools");
+
+#[cw_serde]
+#[derive(Default)]
+pub struct WeightTable {
+    pub total_weight: u64,
+    // Set once the upload is complete, weighted distributions need a frozen table
+    pub frozen: bool,
+}
+
+pub const WEIGHT_TABLE: Item<WeightTable> = Item::new("weight_table");
+
+// Weights of the non numeric token ids
+pub const TOKEN_WEIGHTS: Map<&str, u64> = Map::new("token_weights");
+
+// Numeric token ids [start, end] by start, single numeric tokens are one token ranges
+pub const WEIGHT_RANGES: Map<u64, (u64, u64)> = Map::new("weight_ranges");
+
+// Used by the next trait distributions, set by the admin
+pub const TRAIT_MULTIPLIERS: Item<Vec<TraitMultiplier>> = Item::new("trait_multipliers");
+
+// (trait_type, value) of the tokens, cached from their NftInfo
+pub const TOKEN_TRAITS: Map<&str, Vec<(String, String)>> = Map::new("token_traits");
+
+// Other collections registered by the admin, refreshed whenever a distribution pays them
+#[cw_serde]
+#[derive(Default)]
+pub struct CollectionInfo {
+    pub nft_count: u64,
+}
+
+pub const COLLECTIONS: Map<&Addr, CollectionInfo> = Map::new("collections");
+
+// (dist_id, collection, token_id) claims of the other collections
+pub const COLLECTION_CLAIMS: Map<(u64, &Addr, &str), bool> = Map::new("collection_claims");
+
+// Owners of the tokens at a past time, uploaded in chunks or committed with a merkle root
+#[cw_serde]
+pub struct Snapshot {
+    pub snapshot_id: u64,
+    pub time: Timestamp,
+    // Hex sha256 root of the "{token_id}:{owner}" leaves, the owners then prove their tokens
+    pub merkle_root: Option<String>,
+}
+
+// Used by the next distributions, they pay the current owners when there is none
+pub const SNAPSHOT: Item<Snapshot> = Item::new("snapshot");
+
+// (snapshot_id, token_id) -> owner at the snapshot time
+pub const SNAPSHOT_OWNERS: Map<(u64, &str), Addr> = Map::new("snapshot_owners");
+
+// Distributions which are still open or whose unclaimed was not settled yet
+pub const ACTIVE_DISTRIBUTIONS: Map<u64, bool> = Map::new("active_distributions");
+
+// (dist_id, token_id)
+pub const CLAIMS: Map<(u64, &str), bool> = Map::new("claims");
+
+// (dist_id, token_id) per NFT amounts paid to the token, only tracked once the distribution was
+// topped up
+pub const TOKEN_PAID: Map<(u64, &str), Vec<Asset>> = Map::new("token_paid");
 
-pub const CLAIMS: Map<&str, bool> = Map::new("claims");
+// (dist_id, slot) -> 256 claim bits of the numeric token ids in [slot * 256, slot * 256 + 255]
+pub const CLAIM_BITMAPS: Map<(u64, u64), Binary> = Map::new("claim_bitmaps");
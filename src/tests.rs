@@ -42,8 +42,24 @@ mod tests {
     }
 
     fn valid_instantiate_nft_reward_distribution(
-        mut app: StargazeApp,
+        app: StargazeApp,
         nft_address: Addr,
+    ) -> (StargazeApp, Addr) {
+        instantiate_nft_reward_distribution(
+            app,
+            crate::msg::InstantiateMsg {
+                admin: ADMIN.to_string(),
+                distributor: DISTRIBUTOR.to_string(),
+                nft_address: nft_address.to_string(),
+                claim_storage: None,
+                pauser: None,
+            },
+        )
+    }
+
+    fn instantiate_nft_reward_distribution(
+        mut app: StargazeApp,
+        init_msg: crate::msg::InstantiateMsg,
     ) -> (StargazeApp, Addr) {
         let nft_reward_dist_id = app.store_code(contract_nft_reward_distribution());
 
@@ -51,11 +67,7 @@ mod tests {
             .instantiate_contract(
                 nft_reward_dist_id,
                 Addr::unchecked(ADMIN),
-                &crate::msg::InstantiateMsg {
-                    admin: ADMIN.to_string(),
-                    distributor: DISTRIBUTOR.to_string(),
-                    nft_address: nft_address.to_string(),
-                },
+                &init_msg,
                 &[],
                 "nft_reward_dist",
                 Some(ADMIN.to_string()),
@@ -160,6 +172,236 @@ mod tests {
         }
     }
 
+    // Mints tokens 1..=wallet1_nfts to Wallet 1 and the next wallet2_nfts to Wallet 2, then
+    // instantiates the reward contract and funds the distributor
+    fn setup_reward_distribution(
+        wallet1_nfts: u32,
+        wallet2_nfts: u32,
+    ) -> (StargazeApp, InitReturn, Addr) {
+        setup_reward_distribution_with(wallet1_nfts, wallet2_nfts, |_| {})
+    }
+
+    fn setup_reward_distribution_with(
+        wallet1_nfts: u32,
+        wallet2_nfts: u32,
+        customize_init_msg: impl FnOnce(&mut crate::msg::InstantiateMsg),
+    ) -> (StargazeApp, InitReturn, Addr) {
+        let (mut app, addresses) = valid_instantiate_sg721_factory_minter();
+        for nft_mint in 1..=(wallet1_nfts + wallet2_nfts) {
+            let recipient = if nft_mint > wallet1_nfts {
+                WALLET2
+            } else {
+                WALLET1
+            };
+            let res = app.execute_contract(
+                Addr::unchecked(ADMIN),
+                addresses.minter_addr.clone(),
+                &vending_minter::msg::ExecuteMsg::MintFor {
+                    token_id: nft_mint,
+                    recipient: recipient.to_string(),
+                },
+                &[],
+            );
+            validate_execution_outcome(res, None);
+        }
+
+        let mut init_msg = crate::msg::InstantiateMsg {
+            admin: ADMIN.to_string(),
+            distributor: DISTRIBUTOR.to_string(),
+            nft_address: addresses.nft_addr.to_string(),
+            claim_storage: None,
+            pauser: None,
+        };
+        customize_init_msg(&mut init_msg);
+        let (mut app, nft_reward_dist_addr) = instantiate_nft_reward_distribution(app, init_msg);
+
+        for denom in [
+            NATIVE_DENOM,
+            NATIVE_DENOM1,
+            NATIVE_DENOM2,
+            NATIVE_DENOM3,
+            NATIVE_DENOM4,
+        ] {
+            let mint_denom_outcome = app.sudo(SudoMsg::Bank(BankSudo::Mint {
+                to_address: DISTRIBUTOR.to_string(),
+                amount: vec![coin(100_000_000_000_000u128, denom)],
+            }));
+            validate_execution_outcome(mint_denom_outcome, None);
+        }
+
+        (app, addresses, nft_reward_dist_addr)
+    }
+
+    fn set_block_time(app: &mut StargazeApp, time: Timestamp) {
+        app.set_block(BlockInfo {
+            height: 123456,
+            time,
+            chain_id: "cosmos-testnet-14002".to_string(),
+        });
+    }
+
+    fn query_claimed_in_distribution(
+        app: &StargazeApp,
+        nft_reward_dist_addr: &Addr,
+        dist_id: u64,
+        token_id: &str,
+    ) -> bool {
+        app.wrap()
+            .query_wasm_smart(
+                nft_reward_dist_addr.to_string(),
+                &crate::msg::QueryMsg::GetIfClaimedInDistribution {
+                    dist_id,
+                    token_id: token_id.to_string(),
+                },
+            )
+            .unwrap()
+    }
+
+    fn instantiate_cw20(app: &mut StargazeApp, initial_balance: u128) -> Addr {
+        let code_id = app.store_code(Box::new(ContractWrapper::new_with_empty(
+            cw20_base::contract::execute,
+            cw20_base::contract::instantiate,
+            cw20_base::contract::query,
+        )));
+        app.instantiate_contract(
+            code_id,
+            Addr::unchecked(ADMIN),
+            &cw20_base::msg::InstantiateMsg {
+                name: "Partner Token".to_string(),
+                symbol: "PART".to_string(),
+                decimals: 6,
+                initial_balances: vec![cw20::Cw20Coin {
+                    address: DISTRIBUTOR.to_string(),
+                    amount: Uint128::new(initial_balance),
+                }],
+                mint: None,
+                marketing: None,
+            },
+            &[],
+            "cw20",
+            None,
+        )
+        .unwrap()
+    }
+
+    fn query_cw20_balance(app: &StargazeApp, cw20_addr: &Addr, address: &str) -> Uint128 {
+        let balance: cw20::BalanceResponse = app
+            .wrap()
+            .query_wasm_smart(
+                cw20_addr.to_string(),
+                &cw20::Cw20QueryMsg::Balance {
+                    address: address.to_string(),
+                },
+            )
+            .unwrap();
+        balance.balance
+    }
+
+    // Partner collection whose tokens 1..=count are minted to the distributor
+    fn instantiate_partner_collection(app: &mut StargazeApp, count: u32) -> Addr {
+        let code_id = app.store_code(Box::new(ContractWrapper::new_with_empty(
+            cw721_base::entry::execute,
+            cw721_base::entry::instantiate,
+            cw721_base::entry::query,
+        )));
+        let collection = app
+            .instantiate_contract(
+                code_id,
+                Addr::unchecked(ADMIN),
+                &cw721_base::InstantiateMsg {
+                    name: "Partner Collection".to_string(),
+                    symbol: "PART".to_string(),
+                    minter: ADMIN.to_string(),
+                },
+                &[],
+                "partner",
+                None,
+            )
+            .unwrap();
+        for token_id in 1..=count {
+            let execute_outcome = app.execute_contract(
+                Addr::unchecked(ADMIN),
+                collection.clone(),
+                &cw721_base::ExecuteMsg::<cw721_base::Extension, cosmwasm_std::Empty>::Mint {
+                    token_id: token_id.to_string(),
+                    owner: DISTRIBUTOR.to_string(),
+                    token_uri: None,
+                    extension: None,
+                },
+                &[],
+            );
+            validate_execution_outcome(execute_outcome, None);
+        }
+        collection
+    }
+
+    fn deposit_nft(
+        app: &mut StargazeApp,
+        sender: &str,
+        collection: &Addr,
+        nft_reward_dist_addr: &Addr,
+        token_id: u32,
+    ) -> anyhow::Result<AppResponse> {
+        app.execute_contract(
+            Addr::unchecked(sender),
+            collection.clone(),
+            &cw721_base::ExecuteMsg::<cw721_base::Extension, cosmwasm_std::Empty>::SendNft {
+                contract: nft_reward_dist_addr.to_string(),
+                token_id: token_id.to_string(),
+                msg: cosmwasm_std::to_binary(&crate::msg::ReceiveNftMsg::Deposit {}).unwrap(),
+            },
+            &[],
+        )
+    }
+
+    fn query_partner_tokens(app: &StargazeApp, collection: &Addr, owner: &str) -> Vec<String> {
+        let tokens: TokensResponse = app
+            .wrap()
+            .query_wasm_smart(
+                collection.to_string(),
+                &cw721_base::QueryMsg::<cosmwasm_std::Empty>::Tokens {
+                    owner: owner.to_string(),
+                    start_after: None,
+                    limit: None,
+                },
+            )
+            .unwrap();
+        tokens.tokens
+    }
+
+    // Root and proofs of the leaves, pairs are hashed sorted and an odd node goes up as is
+    fn merkle_tree(leaves: &[String]) -> (String, Vec<Vec<String>>) {
+        use sha2::{Digest, Sha256};
+        let mut level: Vec<[u8; 32]> = leaves
+            .iter()
+            .map(|leaf| Sha256::digest(leaf.as_bytes()).into())
+            .collect();
+        let mut positions: Vec<usize> = (0..leaves.len()).collect();
+        let mut proofs: Vec<Vec<String>> = vec![vec![]; leaves.len()];
+        while level.len() > 1 {
+            for (leaf, position) in positions.iter_mut().enumerate() {
+                let sibling = *position ^ 1;
+                if sibling < level.len() {
+                    proofs[leaf].push(hex::encode(level[sibling]));
+                }
+                *position /= 2;
+            }
+            level = level
+                .chunks(2)
+                .map(|pair| match pair {
+                    [left, right] => {
+                        let mut sorted = [*left, *right];
+                        sorted.sort_unstable();
+                        Sha256::digest(sorted.concat()).into()
+                    }
+                    [single] => *single,
+                    _ => unreachable!(),
+                })
+                .collect();
+        }
+        (hex::encode(level[0]), proofs)
+    }
+
     #[test]
     fn integration_tests() {
         // Init the NFT and Minter contracts
@@ -226,22 +468,18 @@ mod tests {
             nft_reward_dist_addr.clone(),
             &crate::msg::ExecuteMsg::Distribute {
                 assets_per_nft: vec![
-                    Coin {
-                        denom: NATIVE_DENOM.to_string(),
-                        amount: Uint128::new(100_000_000u128),
-                    },
-                    Coin {
-                        denom: NATIVE_DENOM1.to_string(),
-                        amount: Uint128::new(200_000_000u128),
-                    },
-                    Coin {
-                        denom: NATIVE_DENOM2.to_string(),
-                        amount: Uint128::new(300_000_000u128),
-                    },
+                    crate::state::Asset::native(NATIVE_DENOM, 100_000_000u128),
+                    crate::state::Asset::native(NATIVE_DENOM1, 200_000_000u128),
+                    crate::state::Asset::native(NATIVE_DENOM2, 300_000_000u128),
                 ],
                 distribution_end_time: end_time_distribution.clone(),
-                unclaimed_to_distributor: false,
+                unclaimed_policy: crate::state::UnclaimedPolicy::Rollover,
                 minimum_nfts_to_claim: 1,
+                start_time: None,
+                max_tokens_per_wallet: None,
+                max_amount_per_wallet: None,
+                max_token_id: None,
+                snapshot_id: None,
             },
             &[coin(50000000u128, NATIVE_DENOM)],
         );
@@ -265,6 +503,7 @@ mod tests {
             &crate::msg::ExecuteMsg::BulkClaim {
                 token_ids: token_ids.tokens,
                 start_after: None,
+                dist_ids: None,
             },
             &[],
         );
@@ -276,22 +515,18 @@ mod tests {
             nft_reward_dist_addr.clone(),
             &crate::msg::ExecuteMsg::Distribute {
                 assets_per_nft: vec![
-                    Coin {
-                        denom: NATIVE_DENOM.to_string(),
-                        amount: Uint128::new(100_000_000u128),
-                    },
-                    Coin {
-                        denom: NATIVE_DENOM1.to_string(),
-                        amount: Uint128::new(200_000_000u128),
-                    },
-                    Coin {
-                        denom: NATIVE_DENOM2.to_string(),
-                        amount: Uint128::new(300_000_000u128),
-                    },
+                    crate::state::Asset::native(NATIVE_DENOM, 100_000_000u128),
+                    crate::state::Asset::native(NATIVE_DENOM1, 200_000_000u128),
+                    crate::state::Asset::native(NATIVE_DENOM2, 300_000_000u128),
                 ],
                 distribution_end_time: end_time_distribution.clone(),
-                unclaimed_to_distributor: false,
+                unclaimed_policy: crate::state::UnclaimedPolicy::Rollover,
                 minimum_nfts_to_claim: 1,
+                start_time: None,
+                max_tokens_per_wallet: None,
+                max_amount_per_wallet: None,
+                max_token_id: None,
+                snapshot_id: None,
             },
             &[],
         );
@@ -323,22 +558,18 @@ mod tests {
             nft_reward_dist_addr.clone(),
             &crate::msg::ExecuteMsg::Distribute {
                 assets_per_nft: vec![
-                    Coin {
-                        denom: NATIVE_DENOM.to_string(),
-                        amount: Uint128::new(100_000_000u128),
-                    },
-                    Coin {
-                        denom: NATIVE_DENOM1.to_string(),
-                        amount: Uint128::new(200_000_000u128),
-                    },
-                    Coin {
-                        denom: NATIVE_DENOM2.to_string(),
-                        amount: Uint128::new(300_000_000u128),
-                    },
+                    crate::state::Asset::native(NATIVE_DENOM, 100_000_000u128),
+                    crate::state::Asset::native(NATIVE_DENOM1, 200_000_000u128),
+                    crate::state::Asset::native(NATIVE_DENOM2, 300_000_000u128),
                 ],
                 distribution_end_time: end_time_distribution.clone(),
-                unclaimed_to_distributor: false,
+                unclaimed_policy: crate::state::UnclaimedPolicy::Rollover,
                 minimum_nfts_to_claim: 1,
+                start_time: None,
+                max_tokens_per_wallet: None,
+                max_amount_per_wallet: None,
+                max_token_id: None,
+                snapshot_id: None,
             },
             &[
                 Coin {
@@ -357,7 +588,7 @@ mod tests {
         );
         validate_execution_outcome(
             execute_outcome,
-            Some("InvalidDistributionInputs"),
+            Some("InvalidFundsReceived"),
         );
 
         // Add distribution with invalid funds being sent
@@ -366,22 +597,18 @@ mod tests {
             nft_reward_dist_addr.clone(),
             &crate::msg::ExecuteMsg::Distribute {
                 assets_per_nft: vec![
-                    Coin {
-                        denom: NATIVE_DENOM.to_string(),
-                        amount: Uint128::new(100_000_000u128),
-                    },
-                    Coin {
-                        denom: NATIVE_DENOM1.to_string(),
-                        amount: Uint128::new(200_000_000u128),
-                    },
-                    Coin {
-                        denom: NATIVE_DENOM2.to_string(),
-                        amount: Uint128::new(300_000_000u128),
-                    },
+                    crate::state::Asset::native(NATIVE_DENOM, 100_000_000u128),
+                    crate::state::Asset::native(NATIVE_DENOM1, 200_000_000u128),
+                    crate::state::Asset::native(NATIVE_DENOM2, 300_000_000u128),
                 ],
                 distribution_end_time: end_time_distribution.clone(),
-                unclaimed_to_distributor: false,
+                unclaimed_policy: crate::state::UnclaimedPolicy::Rollover,
                 minimum_nfts_to_claim: 1,
+                start_time: None,
+                max_tokens_per_wallet: None,
+                max_amount_per_wallet: None,
+                max_token_id: None,
+                snapshot_id: None,
             },
             &[
                 Coin {
@@ -401,13 +628,15 @@ mod tests {
             Addr::unchecked(DISTRIBUTOR.to_string()),
             nft_reward_dist_addr.clone(),
             &crate::msg::ExecuteMsg::Distribute {
-                assets_per_nft: vec![Coin {
-                    denom: NATIVE_DENOM2.to_string(),
-                    amount: Uint128::new(300_000_000u128),
-                }],
+                assets_per_nft: vec![crate::state::Asset::native(NATIVE_DENOM2, 300_000_000u128)],
                 distribution_end_time: end_time_distribution.clone(),
-                unclaimed_to_distributor: false,
+                unclaimed_policy: crate::state::UnclaimedPolicy::Rollover,
                 minimum_nfts_to_claim: 1,
+                start_time: None,
+                max_tokens_per_wallet: None,
+                max_amount_per_wallet: None,
+                max_token_id: None,
+                snapshot_id: None,
             },
             &[
                 Coin {
@@ -429,8 +658,13 @@ mod tests {
             &crate::msg::ExecuteMsg::Distribute {
                 assets_per_nft: vec![],
                 distribution_end_time: end_time_distribution.clone(),
-                unclaimed_to_distributor: false,
+                unclaimed_policy: crate::state::UnclaimedPolicy::Rollover,
                 minimum_nfts_to_claim: 1,
+                start_time: None,
+                max_tokens_per_wallet: None,
+                max_amount_per_wallet: None,
+                max_token_id: None,
+                snapshot_id: None,
             },
             &[
                 Coin {
@@ -451,18 +685,17 @@ mod tests {
             nft_reward_dist_addr.clone(),
             &crate::msg::ExecuteMsg::Distribute {
                 assets_per_nft: vec![
-                    Coin {
-                        denom: NATIVE_DENOM.to_string(),
-                        amount: Uint128::new(100_000_000u128),
-                    },
-                    Coin {
-                        denom: NATIVE_DENOM1.to_string(),
-                        amount: Uint128::new(200_000_000u128),
-                    },
+                    crate::state::Asset::native(NATIVE_DENOM, 100_000_000u128),
+                    crate::state::Asset::native(NATIVE_DENOM1, 200_000_000u128),
                 ],
                 distribution_end_time: end_time_distribution.clone().minus_days(2u64), // before the actual time now + 1 day
-                unclaimed_to_distributor: false,
+                unclaimed_policy: crate::state::UnclaimedPolicy::Rollover,
                 minimum_nfts_to_claim: 1,
+                start_time: None,
+                max_tokens_per_wallet: None,
+                max_amount_per_wallet: None,
+                max_token_id: None,
+                snapshot_id: None,
             },
             &[
                 Coin {
@@ -486,18 +719,17 @@ mod tests {
             nft_reward_dist_addr.clone(),
             &crate::msg::ExecuteMsg::Distribute {
                 assets_per_nft: vec![
-                    Coin {
-                        denom: NATIVE_DENOM.to_string(),
-                        amount: Uint128::new(100_000_000u128),
-                    },
-                    Coin {
-                        denom: NATIVE_DENOM1.to_string(),
-                        amount: Uint128::new(200_000_000u128),
-                    },
+                    crate::state::Asset::native(NATIVE_DENOM, 100_000_000u128),
+                    crate::state::Asset::native(NATIVE_DENOM1, 200_000_000u128),
                 ],
                 distribution_end_time: end_time_distribution.clone(),
-                unclaimed_to_distributor: false,
+                unclaimed_policy: crate::state::UnclaimedPolicy::Rollover,
                 minimum_nfts_to_claim: 1,
+                start_time: None,
+                max_tokens_per_wallet: None,
+                max_amount_per_wallet: None,
+                max_token_id: None,
+                snapshot_id: None,
             },
             &[
                 Coin {
@@ -524,33 +756,52 @@ mod tests {
             query_result,
             crate::state::Distribution {
                 assets: vec![
-                    Coin {
-                        denom: NATIVE_DENOM.to_string(),
-                        amount: Uint128::new(100_000_000u128)
-                    },
-                    Coin {
-                        denom: NATIVE_DENOM1.to_string(),
-                        amount: Uint128::new(200_000_000u128)
-                    }
+                    crate::state::Asset::native(NATIVE_DENOM, 100_000_000u128),
+                    crate::state::Asset::native(NATIVE_DENOM1, 200_000_000u128)
                 ],
                 claimed: 0,
-                unclaimed_to_distributor: false,
-                unclaimed_sent_to_distributor: None,
-                minimum_nfts_to_claim: 1
+                unclaimed_policy: crate::state::UnclaimedPolicy::Rollover,
+                unclaimed_settled: false,
+                minimum_nfts_to_claim: 1,
+                start_time: end_time_distribution.minus_days(1u64),
+                end_time: end_time_distribution.clone(),
+                nft_count: 1000,
+                max_token_id: Some(1000),
+                rollover: vec![],
+                nft_rewards: None,
+                merkle_root: None,
+                paid: vec![],
+                total_weight: None,
+                claimed_weight: 0,
+                trait_multipliers: None,
+                snapshot: None,
+                collection_rewards: vec![],
+                set_rewards: None,
+                tiers: None,
+                wallet_cap: None,
+                capped: vec![],
+                bonus_round: None,
+                topped_up_from: None,
+                cancelled_at: None,
             }
         );
 
-        // Try to halt from a non-admin wallet
+        // Try to pause the claims from a non-pauser wallet
         // This can be used if something happens
+        let pause_msg = crate::msg::ExecuteMsg::Pause {
+            scope: crate::state::PauseScope::Claim,
+            reason: "incident".to_string(),
+            until: None,
+        };
         let execute_outcome = app.execute_contract(
             Addr::unchecked(DISTRIBUTOR.to_string()),
             nft_reward_dist_addr.clone(),
-            &crate::msg::ExecuteMsg::HaltDistribution {},
+            &pause_msg,
             &[],
         );
         validate_execution_outcome(execute_outcome, Some("Unauthorized"));
 
-        // Check the Config state after trying to halt and adding a distribution
+        // Check the Config state after trying to pause and adding a distribution
         let query_result: crate::state::Config = app
             .wrap()
             .query_wasm_smart(
@@ -559,7 +810,8 @@ mod tests {
             )
             .unwrap();
         assert_eq!(query_result.nft_count, 1000);
-        assert_eq!(query_result.current_dist_halted, false);
+        assert_eq!(query_result.pauses, crate::state::Pauses::default());
+        assert_eq!(query_result.pauser, Some(Addr::unchecked(ADMIN)));
         assert_eq!(query_result.distributor, Addr::unchecked(DISTRIBUTOR));
         assert_eq!(query_result.current_dist_id, 1);
         assert_eq!(
@@ -568,16 +820,16 @@ mod tests {
         );
         assert_eq!(query_result.current_dist_end_time, Some(end_time_distribution.clone()));
 
-        // Try to halt from admin
+        // Pause the claims from the pauser, the admin by default
         let execute_outcome = app.execute_contract(
             Addr::unchecked(ADMIN.to_string()),
             nft_reward_dist_addr.clone(),
-            &crate::msg::ExecuteMsg::HaltDistribution {},
+            &pause_msg,
             &[],
         );
         validate_execution_outcome(execute_outcome, None);
 
-        // Check the Config state after trying to halt and adding a distribution
+        // Check the Config state after pausing and adding a distribution
         let query_result: crate::state::Config = app
             .wrap()
             .query_wasm_smart(
@@ -585,7 +837,13 @@ mod tests {
                 &crate::msg::QueryMsg::GetConfig {},
             )
             .unwrap();
-        assert_eq!(query_result.current_dist_halted, true);
+        assert_eq!(
+            query_result.pauses.claim,
+            Some(crate::state::Pause {
+                reason: "incident".to_string(),
+                until: None,
+            })
+        );
 
         // Check the balances in the contract (after adding a distribution)
         let reward_balance = app
@@ -601,23 +859,26 @@ mod tests {
             }
         }
 
-        // Wallet 1 try to claims while halted
+        // Wallet 1 try to claims while paused
         let execute_outcome = app.execute_contract(
             Addr::unchecked(WALLET1.to_string()),
             nft_reward_dist_addr.clone(),
             &crate::msg::ExecuteMsg::BulkClaim {
                 token_ids: vec!["1".to_string()],
                 start_after: None,
+                dist_ids: None,
             },
             &[],
         );
-        validate_execution_outcome(execute_outcome, Some("DistributionHalted"));
+        validate_execution_outcome(execute_outcome, Some("ClaimsPaused"));
 
-        // Remove halt by admin
+        // Unpause by the pauser
         let execute_outcome = app.execute_contract(
             Addr::unchecked(ADMIN.to_string()),
             nft_reward_dist_addr.clone(),
-            &crate::msg::ExecuteMsg::HaltDistribution {},
+            &crate::msg::ExecuteMsg::Unpause {
+                scope: crate::state::PauseScope::Claim,
+            },
             &[],
         );
         validate_execution_outcome(execute_outcome, None);
@@ -640,6 +901,7 @@ mod tests {
             &crate::msg::ExecuteMsg::BulkClaim {
                 token_ids: token_ids.tokens,
                 start_after: None,
+                dist_ids: None,
             },
             &[],
         );
@@ -681,6 +943,7 @@ mod tests {
             &crate::msg::ExecuteMsg::BulkClaim {
                 token_ids: (1..=100).map(|num| num.to_string()).collect(),
                 start_after: None,
+                dist_ids: None,
             },
             &[],
         );
@@ -735,6 +998,7 @@ mod tests {
             nft_reward_dist_addr.clone(),
             &crate::msg::ExecuteMsg::ClaimFive {
                 token_ids: (101..=105).map(|num| num.to_string()).collect(),
+                dist_ids: None,
             },
             &[],
         );
@@ -747,6 +1011,7 @@ mod tests {
             &crate::msg::ExecuteMsg::BulkClaim {
                 token_ids: (101..=105).map(|num| num.to_string()).collect(),
                 start_after: None,
+                dist_ids: None,
             },
             &[],
         );
@@ -758,6 +1023,7 @@ mod tests {
             nft_reward_dist_addr.clone(),
             &crate::msg::ExecuteMsg::ClaimFive {
                 token_ids: (55..=59).map(|num| num.to_string()).collect(),
+                dist_ids: None,
             },
             &[],
         );
@@ -769,6 +1035,7 @@ mod tests {
             nft_reward_dist_addr.clone(),
             &crate::msg::ExecuteMsg::ClaimFive {
                 token_ids: (110..=120).map(|num| num.to_string()).collect(),
+                dist_ids: None,
             },
             &[],
         );
@@ -781,6 +1048,7 @@ mod tests {
             &crate::msg::ExecuteMsg::BulkClaim {
                 token_ids: (301..=1000).map(|num| num.to_string()).collect(),
                 start_after: None,
+                dist_ids: None,
             },
             &[],
         );
@@ -800,6 +1068,7 @@ mod tests {
             &crate::msg::ExecuteMsg::BulkClaim {
                 token_ids: vec!["TOKEN".to_string()],
                 start_after: None,
+                dist_ids: None,
             },
             &[],
         );
@@ -847,22 +1116,18 @@ mod tests {
             nft_reward_dist_addr.clone(),
             &crate::msg::ExecuteMsg::Distribute {
                 assets_per_nft: vec![
-                    Coin {
-                        denom: NATIVE_DENOM.to_string(),
-                        amount: Uint128::new(100_000_000u128),
-                    },
-                    Coin {
-                        denom: NATIVE_DENOM2.to_string(),
-                        amount: Uint128::new(200_000_000u128),
-                    },
-                    Coin {
-                        denom: NATIVE_DENOM3.to_string(),
-                        amount: Uint128::new(300_000_000u128),
-                    },
+                    crate::state::Asset::native(NATIVE_DENOM, 100_000_000u128),
+                    crate::state::Asset::native(NATIVE_DENOM2, 200_000_000u128),
+                    crate::state::Asset::native(NATIVE_DENOM3, 300_000_000u128),
                 ],
                 distribution_end_time: end_time_distribution.plus_days(2u64).clone(),
-                unclaimed_to_distributor: false,
+                unclaimed_policy: crate::state::UnclaimedPolicy::Rollover,
                 minimum_nfts_to_claim: 1,
+                start_time: None,
+                max_tokens_per_wallet: None,
+                max_amount_per_wallet: None,
+                max_token_id: None,
+                snapshot_id: None,
             },
             &[
                 Coin {
@@ -891,6 +1156,7 @@ mod tests {
             &crate::msg::ExecuteMsg::BulkClaim {
                 token_ids: (301..=800).map(|num| num.to_string()).collect(),
                 start_after: None,
+                dist_ids: None,
             },
             &[],
         );
@@ -917,6 +1183,7 @@ mod tests {
             &crate::msg::ExecuteMsg::BulkClaim {
                 token_ids: (801..=1000).map(|num| num.to_string()).collect(),
                 start_after: None,
+                dist_ids: None,
             },
             &[],
         );
@@ -942,6 +1209,7 @@ mod tests {
             nft_reward_dist_addr.clone(),
             &crate::msg::ExecuteMsg::ClaimFive {
                 token_ids: vec!["1000".to_string()],
+                dist_ids: None,
             },
             &[],
         );
@@ -954,6 +1222,7 @@ mod tests {
             &crate::msg::ExecuteMsg::BulkClaim {
                 token_ids: (800..=1000).map(|num| num.to_string()).collect(),
                 start_after: Some("799".to_string()),
+                dist_ids: None,
             },
             &[],
         );
@@ -980,6 +1249,7 @@ mod tests {
             &crate::msg::ExecuteMsg::BulkClaim {
                 token_ids: (1..=300).map(|num| num.to_string()).collect(),
                 start_after: None,
+                dist_ids: None,
             },
             &[],
         );
@@ -1044,28 +1314,25 @@ mod tests {
             assert!(bal.amount == Uint128::zero());
         }
 
-        // Try to distribute before the end of the current distribution
+        // Distributions can overlap, so adding one before the end of the current one only
+        // fails on the funds
         let execute_outcome = app.execute_contract(
             Addr::unchecked(DISTRIBUTOR.to_string()),
             nft_reward_dist_addr.clone(),
             &crate::msg::ExecuteMsg::Distribute {
                 assets_per_nft: vec![
-                    Coin {
-                        denom: NATIVE_DENOM.to_string(),
-                        amount: Uint128::new(100_000_000u128),
-                    },
-                    Coin {
-                        denom: NATIVE_DENOM2.to_string(),
-                        amount: Uint128::new(200_000_000u128),
-                    },
-                    Coin {
-                        denom: NATIVE_DENOM3.to_string(),
-                        amount: Uint128::new(300_000_000u128),
-                    },
+                    crate::state::Asset::native(NATIVE_DENOM, 100_000_000u128),
+                    crate::state::Asset::native(NATIVE_DENOM2, 200_000_000u128),
+                    crate::state::Asset::native(NATIVE_DENOM3, 300_000_000u128),
                 ],
                 distribution_end_time: end_time_distribution.plus_days(2u64).clone(),
-                unclaimed_to_distributor: false,
+                unclaimed_policy: crate::state::UnclaimedPolicy::Rollover,
                 minimum_nfts_to_claim: 1,
+                start_time: None,
+                max_tokens_per_wallet: None,
+                max_amount_per_wallet: None,
+                max_token_id: None,
+                snapshot_id: None,
             },
             &[
                 Coin {
@@ -1084,7 +1351,7 @@ mod tests {
         );
         validate_execution_outcome(
             execute_outcome,
-            Some("CurrentDistributionHasNotYetEnded"),
+            Some("InvalidDistributionInputs"),
         );
 
         // Reclaim should error
@@ -1094,6 +1361,7 @@ mod tests {
             &crate::msg::ExecuteMsg::BulkClaim {
                 token_ids: vec!["TOKEN".to_string()],
                 start_after: None,
+                dist_ids: None,
             },
             &[],
         );
@@ -1142,22 +1410,22 @@ mod tests {
             )
             .unwrap();
         for asset in query_result.assets.iter() {
-            if asset.denom == NATIVE_DENOM {
+            if asset.info == crate::state::AssetInfo::Native(NATIVE_DENOM.to_string()) {
                 assert_eq!(
                     asset.amount,
                     Uint128::new(100_000_000u128 + (895 * 100_000_000u128 / 1000u128))
                 );
             }
-            if asset.denom == NATIVE_DENOM1 {
+            if asset.info == crate::state::AssetInfo::Native(NATIVE_DENOM1.to_string()) {
                 assert_eq!(
                     asset.amount,
                     Uint128::new(895 * 200_000_000u128 / 1000u128)
                 );
             }
-            if asset.denom == NATIVE_DENOM2 {
+            if asset.info == crate::state::AssetInfo::Native(NATIVE_DENOM2.to_string()) {
                 assert_eq!(asset.amount, Uint128::new(200_000_000u128));
             }
-            if asset.denom == NATIVE_DENOM3 {
+            if asset.info == crate::state::AssetInfo::Native(NATIVE_DENOM3.to_string()) {
                 assert_eq!(asset.amount, Uint128::new(300_000_000u128));
             }
         }
@@ -1173,13 +1441,15 @@ mod tests {
             Addr::unchecked(DISTRIBUTOR.to_string()),
             nft_reward_dist_addr.clone(),
             &crate::msg::ExecuteMsg::Distribute {
-                assets_per_nft: vec![Coin {
-                    denom: NATIVE_DENOM4.to_string(),
-                    amount: Uint128::new(100_000_000u128),
-                }],
+                assets_per_nft: vec![crate::state::Asset::native(NATIVE_DENOM4, 100_000_000u128)],
                 distribution_end_time: end_time_distribution.plus_days(5u64).clone(),
-                unclaimed_to_distributor: true,
+                unclaimed_policy: crate::state::UnclaimedPolicy::Distributor,
                 minimum_nfts_to_claim: 1,
+                start_time: None,
+                max_tokens_per_wallet: None,
+                max_amount_per_wallet: None,
+                max_token_id: None,
+                snapshot_id: None,
             },
             &[Coin {
                 denom: NATIVE_DENOM4.to_string(),
@@ -1197,19 +1467,19 @@ mod tests {
             )
             .unwrap();
         for asset in query_result.assets.iter() {
-            if asset.denom == NATIVE_DENOM {
+            if asset.info == crate::state::AssetInfo::Native(NATIVE_DENOM.to_string()) {
                 assert_eq!(asset.amount, Uint128::zero());
             }
-            if asset.denom == NATIVE_DENOM1 {
+            if asset.info == crate::state::AssetInfo::Native(NATIVE_DENOM1.to_string()) {
                 assert_eq!(asset.amount, Uint128::zero());
             }
-            if asset.denom == NATIVE_DENOM2 {
+            if asset.info == crate::state::AssetInfo::Native(NATIVE_DENOM2.to_string()) {
                 assert_eq!(asset.amount, Uint128::zero());
             }
-            if asset.denom == NATIVE_DENOM3 {
+            if asset.info == crate::state::AssetInfo::Native(NATIVE_DENOM3.to_string()) {
                 assert_eq!(asset.amount, Uint128::zero());
             }
-            if asset.denom == NATIVE_DENOM4 {
+            if asset.info == crate::state::AssetInfo::Native(NATIVE_DENOM4.to_string()) {
                 assert_eq!(asset.amount, Uint128::new(100_000_000u128));
             }
         }
@@ -1230,6 +1500,7 @@ mod tests {
             &crate::msg::ExecuteMsg::BulkClaim {
                 token_ids: (1..=100).map(|num| num.to_string()).collect(),
                 start_after: None,
+                dist_ids: None,
             },
             &[],
         );
@@ -1259,7 +1530,7 @@ mod tests {
         let execute_outcome = app.execute_contract(
             Addr::unchecked(WALLET1.to_string()),
             nft_reward_dist_addr.clone(),
-            &crate::msg::ExecuteMsg::ReturnUnclaimed {},
+            &crate::msg::ExecuteMsg::ReturnUnclaimed { dist_id: None },
             &[],
         );
         validate_execution_outcome(execute_outcome, Some("Unauthorized"));
@@ -1268,7 +1539,7 @@ mod tests {
         let execute_outcome = app.execute_contract(
             Addr::unchecked(DISTRIBUTOR.to_string()),
             nft_reward_dist_addr.clone(),
-            &crate::msg::ExecuteMsg::ReturnUnclaimed {},
+            &crate::msg::ExecuteMsg::ReturnUnclaimed { dist_id: None },
             &[],
         );
         validate_execution_outcome(execute_outcome, None);
@@ -1294,7 +1565,7 @@ mod tests {
         let execute_outcome = app.execute_contract(
             Addr::unchecked(DISTRIBUTOR.to_string()),
             nft_reward_dist_addr.clone(),
-            &crate::msg::ExecuteMsg::ReturnUnclaimed {},
+            &crate::msg::ExecuteMsg::ReturnUnclaimed { dist_id: None },
             &[],
         );
         validate_execution_outcome(
@@ -1308,22 +1579,18 @@ mod tests {
             nft_reward_dist_addr.clone(),
             &crate::msg::ExecuteMsg::Distribute {
                 assets_per_nft: vec![
-                    Coin {
-                        denom: NATIVE_DENOM.to_string(),
-                        amount: Uint128::new(100_000_000u128),
-                    },
-                    Coin {
-                        denom: NATIVE_DENOM1.to_string(),
-                        amount: Uint128::new(200_000_000u128),
-                    },
-                    Coin {
-                        denom: NATIVE_DENOM2.to_string(),
-                        amount: Uint128::new(300_000_000u128),
-                    },
+                    crate::state::Asset::native(NATIVE_DENOM, 100_000_000u128),
+                    crate::state::Asset::native(NATIVE_DENOM1, 200_000_000u128),
+                    crate::state::Asset::native(NATIVE_DENOM2, 300_000_000u128),
                 ],
                 distribution_end_time: end_time_distribution.plus_days(15u64).clone(),
-                unclaimed_to_distributor: true,
+                unclaimed_policy: crate::state::UnclaimedPolicy::Distributor,
                 minimum_nfts_to_claim: 1,
+                start_time: None,
+                max_tokens_per_wallet: None,
+                max_amount_per_wallet: None,
+                max_token_id: None,
+                snapshot_id: None,
             },
             &[
                 Coin {
@@ -1352,28 +1619,19 @@ mod tests {
             .unwrap();
         assert_eq!(
             query_result.assets[0],
-            Coin {
-                denom: NATIVE_DENOM.to_string(),
-                amount: Uint128::new(100_000_000u128)
-            }
+            crate::state::Asset::native(NATIVE_DENOM, 100_000_000u128)
         );
         assert_eq!(
             query_result.assets[1],
-            Coin {
-                denom: NATIVE_DENOM1.to_string(),
-                amount: Uint128::new(200_000_000u128)
-            }
+            crate::state::Asset::native(NATIVE_DENOM1, 200_000_000u128)
         );
         assert_eq!(
             query_result.assets[2],
-            Coin {
-                denom: NATIVE_DENOM2.to_string(),
-                amount: Uint128::new(300_000_000u128)
-            }
+            crate::state::Asset::native(NATIVE_DENOM2, 300_000_000u128)
         );
         assert_eq!(query_result.claimed, 0);
-        assert_eq!(query_result.unclaimed_to_distributor, true);
-        assert_eq!(query_result.unclaimed_sent_to_distributor, Some(false));
+        assert_eq!(query_result.unclaimed_policy, crate::state::UnclaimedPolicy::Distributor);
+        assert_eq!(query_result.unclaimed_settled, false);
 
         // Also possible that the unclaimed is sent when adding a new dist without doing a return unclaimed
         app.set_block(BlockInfo {
@@ -1390,22 +1648,18 @@ mod tests {
             nft_reward_dist_addr.clone(),
             &crate::msg::ExecuteMsg::Distribute {
                 assets_per_nft: vec![
-                    Coin {
-                        denom: NATIVE_DENOM.to_string(),
-                        amount: Uint128::new(100_000_000u128),
-                    },
-                    Coin {
-                        denom: NATIVE_DENOM1.to_string(),
-                        amount: Uint128::new(200_000_000u128),
-                    },
-                    Coin {
-                        denom: NATIVE_DENOM2.to_string(),
-                        amount: Uint128::new(300_000_000u128),
-                    },
+                    crate::state::Asset::native(NATIVE_DENOM, 100_000_000u128),
+                    crate::state::Asset::native(NATIVE_DENOM1, 200_000_000u128),
+                    crate::state::Asset::native(NATIVE_DENOM2, 300_000_000u128),
                 ],
                 distribution_end_time: end_time_distribution.plus_days(21u64).clone(),
-                unclaimed_to_distributor: true,
+                unclaimed_policy: crate::state::UnclaimedPolicy::Distributor,
                 minimum_nfts_to_claim: 1,
+                start_time: None,
+                max_tokens_per_wallet: None,
+                max_amount_per_wallet: None,
+                max_token_id: None,
+                snapshot_id: None,
             },
             &[
                 Coin {
@@ -1446,27 +1700,4153 @@ mod tests {
             .unwrap();
         assert_eq!(
             query_result.assets[0],
-            Coin {
-                denom: NATIVE_DENOM.to_string(),
-                amount: Uint128::new(100_000_000u128)
-            }
+            crate::state::Asset::native(NATIVE_DENOM, 100_000_000u128)
         );
         assert_eq!(
             query_result.assets[1],
-            Coin {
-                denom: NATIVE_DENOM1.to_string(),
-                amount: Uint128::new(200_000_000u128)
-            }
+            crate::state::Asset::native(NATIVE_DENOM1, 200_000_000u128)
         );
         assert_eq!(
             query_result.assets[2],
-            Coin {
-                denom: NATIVE_DENOM2.to_string(),
-                amount: Uint128::new(300_000_000u128)
-            }
+            crate::state::Asset::native(NATIVE_DENOM2, 300_000_000u128)
         );
         assert_eq!(query_result.claimed, 0);
-        assert_eq!(query_result.unclaimed_to_distributor, true);
-        assert_eq!(query_result.unclaimed_sent_to_distributor, Some(false));
+        assert_eq!(query_result.unclaimed_policy, crate::state::UnclaimedPolicy::Distributor);
+        assert_eq!(query_result.unclaimed_settled, false);
+
+        // Past distributions are kept in the history
+        let query_result: crate::state::Distribution = app
+            .wrap()
+            .query_wasm_smart(
+                nft_reward_dist_addr.clone(),
+                &crate::msg::QueryMsg::GetDistribution { dist_id: 1 },
+            )
+            .unwrap();
+        assert_eq!(query_result.claimed, 105);
+        assert_eq!(query_result.end_time, end_time_distribution.clone());
+        assert_eq!(query_result.rollover, vec![]);
+        let query_result: crate::state::Distribution = app
+            .wrap()
+            .query_wasm_smart(
+                nft_reward_dist_addr.clone(),
+                &crate::msg::QueryMsg::GetDistribution { dist_id: 2 },
+            )
+            .unwrap();
+        assert_eq!(query_result.claimed, 1000);
+        assert_eq!(
+            query_result.rollover,
+            vec![
+                crate::state::Asset::native(NATIVE_DENOM, 895 * 100_000_000u128 / 1000u128),
+                crate::state::Asset::native(NATIVE_DENOM1, 895 * 200_000_000u128 / 1000u128)
+            ]
+        );
+        let query_result: crate::state::Distribution = app
+            .wrap()
+            .query_wasm_smart(
+                nft_reward_dist_addr.clone(),
+                &crate::msg::QueryMsg::GetDistribution { dist_id: 4 },
+            )
+            .unwrap();
+        assert!(query_result.unclaimed_settled);
+
+        // Claims of past distributions can still be checked
+        for (dist_id, tok_id, expected) in [
+            (1u64, "50", true),
+            (1u64, "500", false),
+            (2u64, "500", true),
+        ] {
+            let claimed: bool = app
+                .wrap()
+                .query_wasm_smart(
+                    nft_reward_dist_addr.to_string(),
+                    &crate::msg::QueryMsg::GetIfClaimedInDistribution {
+                        dist_id,
+                        token_id: tok_id.to_string(),
+                    },
+                )
+                .unwrap();
+            assert_eq!(claimed, expected);
+        }
+    }
+
+    #[test]
+    fn prune_claims_of_past_distributions() {
+        let (mut app, _addresses, nft_reward_dist_addr) = setup_reward_distribution(5, 5);
+        let end_time_distribution = app.block_info().time.plus_days(1u64);
+
+        let execute_outcome = app.execute_contract(
+            Addr::unchecked(DISTRIBUTOR),
+            nft_reward_dist_addr.clone(),
+            &crate::msg::ExecuteMsg::Distribute {
+                assets_per_nft: vec![crate::state::Asset::native(NATIVE_DENOM, 100u128)],
+                distribution_end_time: end_time_distribution,
+                unclaimed_policy: crate::state::UnclaimedPolicy::Distributor,
+                minimum_nfts_to_claim: 1,
+                start_time: None,
+                max_tokens_per_wallet: None,
+                max_amount_per_wallet: None,
+                max_token_id: None,
+                snapshot_id: None,
+            },
+            &[coin(100u128 * 10u128, NATIVE_DENOM)],
+        );
+        validate_execution_outcome(execute_outcome, None);
+        let execute_outcome = app.execute_contract(
+            Addr::unchecked(WALLET1),
+            nft_reward_dist_addr.clone(),
+            &crate::msg::ExecuteMsg::BulkClaim {
+                token_ids: (1..=5).map(|num| num.to_string()).collect(),
+                start_after: None,
+                dist_ids: None,
+            },
+            &[],
+        );
+        validate_execution_outcome(execute_outcome, None);
+
+        // The current distribution cannot be pruned
+        let execute_outcome = app.execute_contract(
+            Addr::unchecked(ADMIN),
+            nft_reward_dist_addr.clone(),
+            &crate::msg::ExecuteMsg::PruneClaims {
+                dist_id: 1,
+                limit: None,
+            },
+            &[],
+        );
+        validate_execution_outcome(execute_outcome, Some("CannotPruneCurrentDistribution"));
+
+        // A new distribution does not touch the claims of the previous one
+        app.set_block(BlockInfo {
+            height: 123456,
+            time: end_time_distribution.plus_days(1u64),
+            chain_id: "cosmos-testnet-14002".to_string(),
+        });
+        let execute_outcome = app.execute_contract(
+            Addr::unchecked(DISTRIBUTOR),
+            nft_reward_dist_addr.clone(),
+            &crate::msg::ExecuteMsg::Distribute {
+                assets_per_nft: vec![crate::state::Asset::native(NATIVE_DENOM, 100u128)],
+                distribution_end_time: end_time_distribution.plus_days(2u64),
+                unclaimed_policy: crate::state::UnclaimedPolicy::Distributor,
+                minimum_nfts_to_claim: 1,
+                start_time: None,
+                max_tokens_per_wallet: None,
+                max_amount_per_wallet: None,
+                max_token_id: None,
+                snapshot_id: None,
+            },
+            &[coin(100u128 * 10u128, NATIVE_DENOM)],
+        );
+        validate_execution_outcome(execute_outcome, None);
+        assert!(query_claimed_in_distribution(
+            &app,
+            &nft_reward_dist_addr,
+            1,
+            "1"
+        ));
+        assert!(!query_claimed_in_distribution(
+            &app,
+            &nft_reward_dist_addr,
+            2,
+            "1"
+        ));
+
+        // Only the admin can prune
+        let execute_outcome = app.execute_contract(
+            Addr::unchecked(WALLET1),
+            nft_reward_dist_addr.clone(),
+            &crate::msg::ExecuteMsg::PruneClaims {
+                dist_id: 1,
+                limit: None,
+            },
+            &[],
+        );
+        validate_execution_outcome(execute_outcome, Some("Unauthorized"));
+
+        // Paginated pruning
+        let execute_outcome = app.execute_contract(
+            Addr::unchecked(ADMIN),
+            nft_reward_dist_addr.clone(),
+            &crate::msg::ExecuteMsg::PruneClaims {
+                dist_id: 1,
+                limit: Some(3),
+            },
+            &[],
+        );
+        validate_execution_outcome(execute_outcome, None);
+        let claimed: Vec<bool> = (1..=5)
+            .map(|num| {
+                query_claimed_in_distribution(&app, &nft_reward_dist_addr, 1, &num.to_string())
+            })
+            .collect();
+        assert_eq!(claimed, vec![false, false, false, true, true]);
+        let execute_outcome = app.execute_contract(
+            Addr::unchecked(ADMIN),
+            nft_reward_dist_addr.clone(),
+            &crate::msg::ExecuteMsg::PruneClaims {
+                dist_id: 1,
+                limit: None,
+            },
+            &[],
+        );
+        validate_execution_outcome(execute_outcome, None);
+        assert!(!query_claimed_in_distribution(
+            &app,
+            &nft_reward_dist_addr,
+            1,
+            "5"
+        ));
+    }
+
+    #[test]
+    fn bitmap_claim_storage() {
+        // 300 tokens so the claims span two bitmap slots
+        let (mut app, _addresses, nft_reward_dist_addr) =
+            setup_reward_distribution_with(150, 150, |init_msg| {
+                init_msg.claim_storage = Some(crate::state::ClaimStorage::Bitmap)
+            });
+        let end_time_distribution = app.block_info().time.plus_days(1u64);
+
+        let execute_outcome = app.execute_contract(
+            Addr::unchecked(DISTRIBUTOR),
+            nft_reward_dist_addr.clone(),
+            &crate::msg::ExecuteMsg::Distribute {
+                assets_per_nft: vec![crate::state::Asset::native(NATIVE_DENOM, 100u128)],
+                distribution_end_time: end_time_distribution,
+                unclaimed_policy: crate::state::UnclaimedPolicy::Distributor,
+                minimum_nfts_to_claim: 1,
+                start_time: None,
+                max_tokens_per_wallet: None,
+                max_amount_per_wallet: None,
+                max_token_id: None,
+                snapshot_id: None,
+            },
+            &[coin(100u128 * 300u128, NATIVE_DENOM)],
+        );
+        validate_execution_outcome(execute_outcome, None);
+
+        // Duplicates in the same claim are only paid once
+        let before_claim_balance = app.wrap().query_balance(WALLET2, NATIVE_DENOM).unwrap();
+        let mut token_ids: Vec<String> = (151..=300).map(|num| num.to_string()).collect();
+        token_ids.push("151".to_string());
+        let execute_outcome = app.execute_contract(
+            Addr::unchecked(WALLET2),
+            nft_reward_dist_addr.clone(),
+            &crate::msg::ExecuteMsg::BulkClaim {
+                token_ids,
+                start_after: None,
+                dist_ids: None,
+            },
+            &[],
+        );
+        validate_execution_outcome(execute_outcome, None);
+        let after_claim_balance = app.wrap().query_balance(WALLET2, NATIVE_DENOM).unwrap();
+        assert_eq!(
+            after_claim_balance.amount - before_claim_balance.amount,
+            Uint128::new(150u128 * 100u128)
+        );
+
+        let execute_outcome = app.execute_contract(
+            Addr::unchecked(WALLET2),
+            nft_reward_dist_addr.clone(),
+            &crate::msg::ExecuteMsg::ClaimFive {
+                token_ids: vec!["255".to_string(), "256".to_string()],
+                dist_ids: None,
+            },
+            &[],
+        );
+        validate_execution_outcome(execute_outcome, Some("NothingToClaim"));
+
+        assert!(!query_claimed_in_distribution(
+            &app,
+            &nft_reward_dist_addr,
+            1,
+            "150"
+        ));
+        for tok_id in [151, 255, 256, 300] {
+            assert!(query_claimed_in_distribution(
+                &app,
+                &nft_reward_dist_addr,
+                1,
+                &tok_id.to_string()
+            ));
+        }
+        // Non canonical ids are not mixed with the numeric ones
+        assert!(!query_claimed_in_distribution(
+            &app,
+            &nft_reward_dist_addr,
+            1,
+            "0300"
+        ));
+    }
+
+    #[test]
+    fn overlapping_distributions() {
+        let (mut app, _addresses, nft_reward_dist_addr) = setup_reward_distribution(5, 4);
+        let start_time = app.block_info().time;
+
+        // Monthly distribution
+        let execute_outcome = app.execute_contract(
+            Addr::unchecked(DISTRIBUTOR),
+            nft_reward_dist_addr.clone(),
+            &crate::msg::ExecuteMsg::Distribute {
+                assets_per_nft: vec![crate::state::Asset::native(NATIVE_DENOM, 100u128)],
+                distribution_end_time: start_time.plus_days(30u64),
+                unclaimed_policy: crate::state::UnclaimedPolicy::Rollover,
+                minimum_nfts_to_claim: 1,
+                start_time: None,
+                max_tokens_per_wallet: None,
+                max_amount_per_wallet: None,
+                max_token_id: None,
+                snapshot_id: None,
+            },
+            &[coin(100u128 * 9u128, NATIVE_DENOM)],
+        );
+        validate_execution_outcome(execute_outcome, None);
+
+        // Airdrop on top of it for wallets holding at least 5 NFTs
+        let execute_outcome = app.execute_contract(
+            Addr::unchecked(DISTRIBUTOR),
+            nft_reward_dist_addr.clone(),
+            &crate::msg::ExecuteMsg::Distribute {
+                assets_per_nft: vec![crate::state::Asset::native(NATIVE_DENOM1, 50u128)],
+                distribution_end_time: start_time.plus_days(1u64),
+                unclaimed_policy: crate::state::UnclaimedPolicy::Distributor,
+                minimum_nfts_to_claim: 5,
+                start_time: None,
+                max_tokens_per_wallet: None,
+                max_amount_per_wallet: None,
+                max_token_id: None,
+                snapshot_id: None,
+            },
+            &[coin(50u128 * 9u128, NATIVE_DENOM1)],
+        );
+        validate_execution_outcome(execute_outcome, None);
+
+        let open_distributions: Vec<u64> = app
+            .wrap()
+            .query_wasm_smart(
+                nft_reward_dist_addr.clone(),
+                &crate::msg::QueryMsg::GetOpenDistributions {},
+            )
+            .unwrap();
+        assert_eq!(open_distributions, vec![1, 2]);
+
+        // Wallet 1 gets paid by both distributions at once
+        let execute_outcome = app.execute_contract(
+            Addr::unchecked(WALLET1),
+            nft_reward_dist_addr.clone(),
+            &crate::msg::ExecuteMsg::BulkClaim {
+                token_ids: (1..=5).map(|num| num.to_string()).collect(),
+                start_after: None,
+                dist_ids: None,
+            },
+            &[],
+        );
+        validate_execution_outcome(execute_outcome, None);
+        assert_eq!(
+            app.wrap().query_all_balances(WALLET1).unwrap(),
+            vec![
+                coin(50u128 * 5u128, NATIVE_DENOM1),
+                coin(5_000_000_000u128 + 100u128 * 5u128, NATIVE_DENOM)
+            ]
+        );
+
+        // Wallet 2 does not hold enough NFTs for the airdrop
+        let execute_outcome = app.execute_contract(
+            Addr::unchecked(WALLET2),
+            nft_reward_dist_addr.clone(),
+            &crate::msg::ExecuteMsg::ClaimFive {
+                token_ids: (6..=9).map(|num| num.to_string()).collect(),
+                dist_ids: Some(vec![2]),
+            },
+            &[],
+        );
+        validate_execution_outcome(execute_outcome, Some("NotEnoughNftsToClaim"));
+        let execute_outcome = app.execute_contract(
+            Addr::unchecked(WALLET2),
+            nft_reward_dist_addr.clone(),
+            &crate::msg::ExecuteMsg::ClaimFive {
+                token_ids: (6..=9).map(|num| num.to_string()).collect(),
+                dist_ids: None,
+            },
+            &[],
+        );
+        validate_execution_outcome(execute_outcome, None);
+        assert_eq!(
+            app.wrap().query_all_balances(WALLET2).unwrap(),
+            vec![coin(5_000_000_000u128 + 100u128 * 4u128, NATIVE_DENOM)]
+        );
+        assert!(query_claimed_in_distribution(
+            &app,
+            &nft_reward_dist_addr,
+            1,
+            "6"
+        ));
+        assert!(!query_claimed_in_distribution(
+            &app,
+            &nft_reward_dist_addr,
+            2,
+            "6"
+        ));
+
+        // The airdrop window closes while the monthly one stays open
+        app.set_block(BlockInfo {
+            height: 123456,
+            time: start_time.plus_days(2u64),
+            chain_id: "cosmos-testnet-14002".to_string(),
+        });
+        let open_distributions: Vec<u64> = app
+            .wrap()
+            .query_wasm_smart(
+                nft_reward_dist_addr.clone(),
+                &crate::msg::QueryMsg::GetOpenDistributions {},
+            )
+            .unwrap();
+        assert_eq!(open_distributions, vec![1]);
+        let execute_outcome = app.execute_contract(
+            Addr::unchecked(WALLET2),
+            nft_reward_dist_addr.clone(),
+            &crate::msg::ExecuteMsg::ClaimFive {
+                token_ids: (6..=9).map(|num| num.to_string()).collect(),
+                dist_ids: Some(vec![2]),
+            },
+            &[],
+        );
+        validate_execution_outcome(execute_outcome, Some("ClaimingWindowHasClosed"));
+
+        // The airdrop's unclaimed goes back to the distributor
+        let before_return = app
+            .wrap()
+            .query_balance(DISTRIBUTOR, NATIVE_DENOM1)
+            .unwrap();
+        let execute_outcome = app.execute_contract(
+            Addr::unchecked(DISTRIBUTOR),
+            nft_reward_dist_addr.clone(),
+            &crate::msg::ExecuteMsg::ReturnUnclaimed { dist_id: Some(1) },
+            &[],
+        );
+        validate_execution_outcome(execute_outcome, Some("CurrentDistributionHasNotYetEnded"));
+        let execute_outcome = app.execute_contract(
+            Addr::unchecked(DISTRIBUTOR),
+            nft_reward_dist_addr.clone(),
+            &crate::msg::ExecuteMsg::ReturnUnclaimed { dist_id: Some(2) },
+            &[],
+        );
+        validate_execution_outcome(execute_outcome, None);
+        let after_return = app
+            .wrap()
+            .query_balance(DISTRIBUTOR, NATIVE_DENOM1)
+            .unwrap();
+        assert_eq!(
+            after_return.amount - before_return.amount,
+            Uint128::new(50u128 * 4u128)
+        );
+    }
+
+    #[test]
+    fn queued_distributions() {
+        let (mut app, _addresses, nft_reward_dist_addr) = setup_reward_distribution(3, 2);
+        let start_time = app.block_info().time;
+
+        let execute_outcome = app.execute_contract(
+            Addr::unchecked(DISTRIBUTOR),
+            nft_reward_dist_addr.clone(),
+            &crate::msg::ExecuteMsg::Distribute {
+                assets_per_nft: vec![crate::state::Asset::native(NATIVE_DENOM, 100u128)],
+                distribution_end_time: start_time.plus_days(1u64),
+                unclaimed_policy: crate::state::UnclaimedPolicy::Rollover,
+                minimum_nfts_to_claim: 1,
+                start_time: None,
+                max_tokens_per_wallet: None,
+                max_amount_per_wallet: None,
+                max_token_id: None,
+                snapshot_id: None,
+            },
+            &[coin(100u128 * 5u128, NATIVE_DENOM)],
+        );
+        validate_execution_outcome(execute_outcome, None);
+
+        // Two rounds are queued after the current one
+        for (denom, start_day, unclaimed_policy) in [
+            (NATIVE_DENOM1, 2u64, crate::state::UnclaimedPolicy::Rollover),
+            (
+                NATIVE_DENOM2,
+                4u64,
+                crate::state::UnclaimedPolicy::Distributor,
+            ),
+        ] {
+            let execute_outcome = app.execute_contract(
+                Addr::unchecked(DISTRIBUTOR),
+                nft_reward_dist_addr.clone(),
+                &crate::msg::ExecuteMsg::Distribute {
+                    assets_per_nft: vec![crate::state::Asset::native(denom, 10u128)],
+                    distribution_end_time: start_time.plus_days(start_day + 1),
+                    unclaimed_policy,
+                    minimum_nfts_to_claim: 1,
+                    start_time: Some(start_time.plus_days(start_day)),
+                    max_tokens_per_wallet: None,
+                    max_amount_per_wallet: None,
+                    max_token_id: None,
+                    snapshot_id: None,
+                },
+                &[coin(10u128 * 5u128, denom)],
+            );
+            validate_execution_outcome(execute_outcome, None);
+        }
+
+        // A queued round cannot start before the previous scheduled window ends
+        let execute_outcome = app.execute_contract(
+            Addr::unchecked(DISTRIBUTOR),
+            nft_reward_dist_addr.clone(),
+            &crate::msg::ExecuteMsg::Distribute {
+                assets_per_nft: vec![crate::state::Asset::native(NATIVE_DENOM3, 10u128)],
+                distribution_end_time: start_time.plus_days(6u64),
+                unclaimed_policy: crate::state::UnclaimedPolicy::Rollover,
+                minimum_nfts_to_claim: 1,
+                start_time: Some(start_time.plus_days(3u64)),
+                max_tokens_per_wallet: None,
+                max_amount_per_wallet: None,
+                max_token_id: None,
+                snapshot_id: None,
+            },
+            &[coin(10u128 * 5u128, NATIVE_DENOM3)],
+        );
+        validate_execution_outcome(execute_outcome, Some("InvalidDistributionStartTime"));
+
+        let queued: Vec<(u64, crate::state::QueuedDistribution)> = app
+            .wrap()
+            .query_wasm_smart(
+                nft_reward_dist_addr.clone(),
+                &crate::msg::QueryMsg::GetQueuedDistributions {},
+            )
+            .unwrap();
+        assert_eq!(
+            queued
+                .iter()
+                .map(|(queue_id, _)| *queue_id)
+                .collect::<Vec<u64>>(),
+            vec![1, 2]
+        );
+        assert_eq!(queued[0].1.start_time, start_time.plus_days(2u64));
+
+        // Only the admin cancels a queued round, the funds go back to the distributor
+        let execute_outcome = app.execute_contract(
+            Addr::unchecked(DISTRIBUTOR),
+            nft_reward_dist_addr.clone(),
+            &crate::msg::ExecuteMsg::CancelQueuedDistribution { queue_id: 2 },
+            &[],
+        );
+        validate_execution_outcome(execute_outcome, Some("Unauthorized"));
+        let before_cancel = app
+            .wrap()
+            .query_balance(DISTRIBUTOR, NATIVE_DENOM2)
+            .unwrap();
+        let execute_outcome = app.execute_contract(
+            Addr::unchecked(ADMIN),
+            nft_reward_dist_addr.clone(),
+            &crate::msg::ExecuteMsg::CancelQueuedDistribution { queue_id: 2 },
+            &[],
+        );
+        validate_execution_outcome(execute_outcome, None);
+        let after_cancel = app
+            .wrap()
+            .query_balance(DISTRIBUTOR, NATIVE_DENOM2)
+            .unwrap();
+        assert_eq!(
+            after_cancel.amount - before_cancel.amount,
+            Uint128::new(10u128 * 5u128)
+        );
+        let execute_outcome = app.execute_contract(
+            Addr::unchecked(ADMIN),
+            nft_reward_dist_addr.clone(),
+            &crate::msg::ExecuteMsg::CancelQueuedDistribution { queue_id: 2 },
+            &[],
+        );
+        validate_execution_outcome(execute_outcome, Some("QueuedDistributionNotFound"));
+
+        // Only Wallet 1 claims the first round
+        let execute_outcome = app.execute_contract(
+            Addr::unchecked(WALLET1),
+            nft_reward_dist_addr.clone(),
+            &crate::msg::ExecuteMsg::BulkClaim {
+                token_ids: (1..=3).map(|num| num.to_string()).collect(),
+                start_after: None,
+                dist_ids: None,
+            },
+            &[],
+        );
+        validate_execution_outcome(execute_outcome, None);
+
+        // Claims are not open between the rounds
+        set_block_time(&mut app, start_time.plus_days(1u64).plus_seconds(1u64));
+        let execute_outcome = app.execute_contract(
+            Addr::unchecked(WALLET2),
+            nft_reward_dist_addr.clone(),
+            &crate::msg::ExecuteMsg::ClaimFive {
+                token_ids: vec!["4".to_string(), "5".to_string()],
+                dist_ids: None,
+            },
+            &[],
+        );
+        validate_execution_outcome(execute_outcome, Some("ClaimingWindowHasClosed"));
+
+        // The queued round becomes current with the unclaimed of the first one rolled over
+        set_block_time(&mut app, start_time.plus_days(2u64));
+        let execute_outcome = app.execute_contract(
+            Addr::unchecked(WALLET2),
+            nft_reward_dist_addr.clone(),
+            &crate::msg::ExecuteMsg::ClaimFive {
+                token_ids: vec!["4".to_string(), "5".to_string()],
+                dist_ids: None,
+            },
+            &[],
+        );
+        validate_execution_outcome(execute_outcome, None);
+        assert_eq!(
+            app.wrap().query_all_balances(WALLET2).unwrap(),
+            vec![
+                coin(2u128 * 10u128, NATIVE_DENOM1),
+                coin(
+                    5_000_000_000u128 + 2u128 * (2u128 * 100u128 / 5u128),
+                    NATIVE_DENOM
+                )
+            ]
+        );
+
+        let query_result: crate::state::Distribution = app
+            .wrap()
+            .query_wasm_smart(
+                nft_reward_dist_addr.clone(),
+                &crate::msg::QueryMsg::GetCurrentDistributionInfo {},
+            )
+            .unwrap();
+        assert_eq!(query_result.start_time, start_time.plus_days(2u64));
+        assert_eq!(
+            query_result.rollover,
+            vec![crate::state::Asset::native(NATIVE_DENOM, 40u128)]
+        );
+        let queued: Vec<(u64, crate::state::QueuedDistribution)> = app
+            .wrap()
+            .query_wasm_smart(
+                nft_reward_dist_addr.clone(),
+                &crate::msg::QueryMsg::GetQueuedDistributions {},
+            )
+            .unwrap();
+        assert!(queued.is_empty());
+    }
+
+    #[test]
+    fn cw20_distributions() {
+        let (mut app, _addresses, nft_reward_dist_addr) = setup_reward_distribution(3, 2);
+        let cw20_addr = instantiate_cw20(&mut app, 1_000_000u128);
+        let start_time = app.block_info().time;
+
+        // Only the distributor can deposit
+        let execute_outcome = app.execute_contract(
+            Addr::unchecked(DISTRIBUTOR),
+            cw20_addr.clone(),
+            &cw20::Cw20ExecuteMsg::Transfer {
+                recipient: WALLET1.to_string(),
+                amount: Uint128::new(10u128),
+            },
+            &[],
+        );
+        validate_execution_outcome(execute_outcome, None);
+        let execute_outcome = app.execute_contract(
+            Addr::unchecked(WALLET1),
+            cw20_addr.clone(),
+            &cw20::Cw20ExecuteMsg::Send {
+                contract: nft_reward_dist_addr.to_string(),
+                amount: Uint128::new(10u128),
+                msg: cosmwasm_std::to_binary(&crate::msg::ReceiveMsg::Deposit {}).unwrap(),
+            },
+            &[],
+        );
+        validate_execution_outcome(execute_outcome, Some(""));
+
+        let execute_outcome = app.execute_contract(
+            Addr::unchecked(DISTRIBUTOR),
+            cw20_addr.clone(),
+            &cw20::Cw20ExecuteMsg::Send {
+                contract: nft_reward_dist_addr.to_string(),
+                amount: Uint128::new(100u128 * 5u128),
+                msg: cosmwasm_std::to_binary(&crate::msg::ReceiveMsg::Deposit {}).unwrap(),
+            },
+            &[],
+        );
+        validate_execution_outcome(execute_outcome, None);
+        let deposits: Vec<crate::state::Asset> = app
+            .wrap()
+            .query_wasm_smart(
+                nft_reward_dist_addr.clone(),
+                &crate::msg::QueryMsg::GetDeposits {},
+            )
+            .unwrap();
+        assert_eq!(
+            deposits,
+            vec![crate::state::Asset::cw20(cw20_addr.clone(), 500u128)]
+        );
+
+        // Native funds and the CW20 deposit fund a mixed distribution
+        let execute_outcome = app.execute_contract(
+            Addr::unchecked(DISTRIBUTOR),
+            nft_reward_dist_addr.clone(),
+            &crate::msg::ExecuteMsg::Distribute {
+                assets_per_nft: vec![
+                    crate::state::Asset::native(NATIVE_DENOM1, 100u128),
+                    crate::state::Asset::cw20(cw20_addr.clone(), 100u128),
+                ],
+                distribution_end_time: start_time.plus_days(1u64),
+                unclaimed_policy: crate::state::UnclaimedPolicy::Distributor,
+                minimum_nfts_to_claim: 1,
+                start_time: None,
+                max_tokens_per_wallet: None,
+                max_amount_per_wallet: None,
+                max_token_id: None,
+                snapshot_id: None,
+            },
+            &[coin(100u128 * 5u128, NATIVE_DENOM1)],
+        );
+        validate_execution_outcome(execute_outcome, None);
+        let deposits: Vec<crate::state::Asset> = app
+            .wrap()
+            .query_wasm_smart(
+                nft_reward_dist_addr.clone(),
+                &crate::msg::QueryMsg::GetDeposits {},
+            )
+            .unwrap();
+        assert!(deposits.is_empty());
+
+        // Claims pay both assets
+        let execute_outcome = app.execute_contract(
+            Addr::unchecked(WALLET1),
+            nft_reward_dist_addr.clone(),
+            &crate::msg::ExecuteMsg::BulkClaim {
+                token_ids: (1..=3).map(|num| num.to_string()).collect(),
+                start_after: None,
+                dist_ids: None,
+            },
+            &[],
+        );
+        validate_execution_outcome(execute_outcome, None);
+        assert_eq!(
+            query_cw20_balance(&app, &cw20_addr, WALLET1),
+            Uint128::new(10u128 + 3u128 * 100u128)
+        );
+        assert_eq!(
+            app.wrap()
+                .query_balance(WALLET1, NATIVE_DENOM1)
+                .unwrap()
+                .amount,
+            Uint128::new(3u128 * 100u128)
+        );
+
+        // The unclaimed CW20 goes back to the distributor
+        set_block_time(&mut app, start_time.plus_days(1u64).plus_seconds(1u64));
+        let before_return = query_cw20_balance(&app, &cw20_addr, DISTRIBUTOR);
+        let execute_outcome = app.execute_contract(
+            Addr::unchecked(DISTRIBUTOR),
+            nft_reward_dist_addr.clone(),
+            &crate::msg::ExecuteMsg::ReturnUnclaimed { dist_id: None },
+            &[],
+        );
+        validate_execution_outcome(execute_outcome, None);
+        assert_eq!(
+            query_cw20_balance(&app, &cw20_addr, DISTRIBUTOR) - before_return,
+            Uint128::new(2u128 * 100u128)
+        );
+        assert_eq!(
+            query_cw20_balance(&app, &cw20_addr, nft_reward_dist_addr.as_str()),
+            Uint128::zero()
+        );
+
+        // Only a CW20 contract can call the hook, a wallet cannot pose as the distributor
+        let execute_outcome = app.execute_contract(
+            Addr::unchecked(WALLET1),
+            nft_reward_dist_addr.clone(),
+            &crate::msg::ExecuteMsg::Receive(cw20::Cw20ReceiveMsg {
+                sender: DISTRIBUTOR.to_string(),
+                amount: Uint128::new(1_000u128),
+                msg: cosmwasm_std::to_binary(&crate::msg::ReceiveMsg::Deposit {}).unwrap(),
+            }),
+            &[],
+        );
+        validate_execution_outcome(execute_outcome, Some("NotACw20Contract"));
+        let deposits: Vec<crate::state::Asset> = app
+            .wrap()
+            .query_wasm_smart(
+                nft_reward_dist_addr.clone(),
+                &crate::msg::QueryMsg::GetDeposits {},
+            )
+            .unwrap();
+        assert!(deposits.is_empty());
+
+        // Unused deposits can be withdrawn by the distributor
+        let execute_outcome = app.execute_contract(
+            Addr::unchecked(DISTRIBUTOR),
+            cw20_addr.clone(),
+            &cw20::Cw20ExecuteMsg::Send {
+                contract: nft_reward_dist_addr.to_string(),
+                amount: Uint128::new(7u128),
+                msg: cosmwasm_std::to_binary(&crate::msg::ReceiveMsg::Deposit {}).unwrap(),
+            },
+            &[],
+        );
+        validate_execution_outcome(execute_outcome, None);
+        let execute_outcome = app.execute_contract(
+            Addr::unchecked(WALLET1),
+            nft_reward_dist_addr.clone(),
+            &crate::msg::ExecuteMsg::WithdrawDeposits {},
+            &[],
+        );
+        validate_execution_outcome(execute_outcome, Some("Unauthorized"));
+        let before_withdraw = query_cw20_balance(&app, &cw20_addr, DISTRIBUTOR);
+        let execute_outcome = app.execute_contract(
+            Addr::unchecked(DISTRIBUTOR),
+            nft_reward_dist_addr.clone(),
+            &crate::msg::ExecuteMsg::WithdrawDeposits {},
+            &[],
+        );
+        validate_execution_outcome(execute_outcome, None);
+        assert_eq!(
+            query_cw20_balance(&app, &cw20_addr, DISTRIBUTOR) - before_withdraw,
+            Uint128::new(7u128)
+        );
+    }
+
+    #[test]
+    fn nft_distributions() {
+        let (mut app, _addresses, nft_reward_dist_addr) = setup_reward_distribution(3, 2);
+        let collection = instantiate_partner_collection(&mut app, 7);
+        let start_time = app.block_info().time;
+
+        // Only the distributor deposits NFTs
+        let execute_outcome = app.execute_contract(
+            Addr::unchecked(DISTRIBUTOR),
+            collection.clone(),
+            &cw721_base::ExecuteMsg::<cw721_base::Extension, cosmwasm_std::Empty>::TransferNft {
+                recipient: WALLET3.to_string(),
+                token_id: "7".to_string(),
+            },
+            &[],
+        );
+        validate_execution_outcome(execute_outcome, None);
+        let execute_outcome = deposit_nft(&mut app, WALLET3, &collection, &nft_reward_dist_addr, 7);
+        validate_execution_outcome(execute_outcome, Some(""));
+
+        for token_id in 1..=4 {
+            let execute_outcome = deposit_nft(
+                &mut app,
+                DISTRIBUTOR,
+                &collection,
+                &nft_reward_dist_addr,
+                token_id,
+            );
+            validate_execution_outcome(execute_outcome, None);
+        }
+
+        // One NFT is needed for each of the 5 tokens
+        let distribute_msg = crate::msg::ExecuteMsg::DistributeNfts {
+            collection: collection.to_string(),
+            distribution_end_time: start_time.plus_days(1u64),
+            minimum_nfts_to_claim: 1,
+            start_time: None,
+            snapshot_id: None,
+        };
+        let execute_outcome = app.execute_contract(
+            Addr::unchecked(DISTRIBUTOR),
+            nft_reward_dist_addr.clone(),
+            &distribute_msg,
+            &[],
+        );
+        validate_execution_outcome(execute_outcome, Some("InvalidNftCount"));
+
+        let execute_outcome =
+            deposit_nft(&mut app, DISTRIBUTOR, &collection, &nft_reward_dist_addr, 5);
+        validate_execution_outcome(execute_outcome, None);
+        let deposits: Vec<String> = app
+            .wrap()
+            .query_wasm_smart(
+                nft_reward_dist_addr.clone(),
+                &crate::msg::QueryMsg::GetNftDeposits {
+                    collection: collection.to_string(),
+                    start_after: Some("2".to_string()),
+                    limit: None,
+                },
+            )
+            .unwrap();
+        assert_eq!(deposits, vec!["3", "4", "5"]);
+
+        let execute_outcome = app.execute_contract(
+            Addr::unchecked(WALLET1),
+            nft_reward_dist_addr.clone(),
+            &distribute_msg,
+            &[],
+        );
+        validate_execution_outcome(execute_outcome, Some("Unauthorized"));
+        let execute_outcome = app.execute_contract(
+            Addr::unchecked(DISTRIBUTOR),
+            nft_reward_dist_addr.clone(),
+            &distribute_msg,
+            &[],
+        );
+        validate_execution_outcome(execute_outcome, None);
+        let deposits: Vec<String> = app
+            .wrap()
+            .query_wasm_smart(
+                nft_reward_dist_addr.clone(),
+                &crate::msg::QueryMsg::GetNftDeposits {
+                    collection: collection.to_string(),
+                    start_after: None,
+                    limit: None,
+                },
+            )
+            .unwrap();
+        assert!(deposits.is_empty());
+
+        // Each claimed token receives one NFT, once
+        let claim_msg = crate::msg::ExecuteMsg::BulkClaim {
+            token_ids: (1..=3).map(|num| num.to_string()).collect(),
+            start_after: None,
+            dist_ids: None,
+        };
+        let execute_outcome = app.execute_contract(
+            Addr::unchecked(WALLET1),
+            nft_reward_dist_addr.clone(),
+            &claim_msg,
+            &[],
+        );
+        validate_execution_outcome(execute_outcome, None);
+        assert_eq!(
+            query_partner_tokens(&app, &collection, WALLET1),
+            vec!["1", "2", "3"]
+        );
+        let execute_outcome = app.execute_contract(
+            Addr::unchecked(WALLET1),
+            nft_reward_dist_addr.clone(),
+            &claim_msg,
+            &[],
+        );
+        validate_execution_outcome(execute_outcome, Some("NothingToClaim"));
+
+        // The unclaimed NFTs go back to the distributor once the window closed
+        let execute_outcome = app.execute_contract(
+            Addr::unchecked(DISTRIBUTOR),
+            nft_reward_dist_addr.clone(),
+            &crate::msg::ExecuteMsg::ReturnUnclaimed { dist_id: None },
+            &[],
+        );
+        validate_execution_outcome(execute_outcome, Some("CurrentDistributionHasNotYetEnded"));
+        set_block_time(&mut app, start_time.plus_days(1u64).plus_seconds(1u64));
+        let execute_outcome = app.execute_contract(
+            Addr::unchecked(DISTRIBUTOR),
+            nft_reward_dist_addr.clone(),
+            &crate::msg::ExecuteMsg::ReturnUnclaimed { dist_id: None },
+            &[],
+        );
+        validate_execution_outcome(execute_outcome, None);
+        assert_eq!(
+            query_partner_tokens(&app, &collection, DISTRIBUTOR),
+            vec!["4", "5", "6"]
+        );
+
+        // Deposits not used by a distribution can be withdrawn
+        let execute_outcome =
+            deposit_nft(&mut app, DISTRIBUTOR, &collection, &nft_reward_dist_addr, 6);
+        validate_execution_outcome(execute_outcome, None);
+        let execute_outcome = app.execute_contract(
+            Addr::unchecked(DISTRIBUTOR),
+            nft_reward_dist_addr.clone(),
+            &crate::msg::ExecuteMsg::WithdrawDeposits {},
+            &[],
+        );
+        validate_execution_outcome(execute_outcome, None);
+        assert_eq!(
+            query_partner_tokens(&app, &collection, DISTRIBUTOR),
+            vec!["4", "5", "6"]
+        );
+    }
+
+    #[test]
+    fn merkle_distributions() {
+        let (mut app, _addresses, nft_reward_dist_addr) = setup_reward_distribution(3, 2);
+        let start_time = app.block_info().time;
+
+        // Token i gets i * 100 of the first denom, token 1 also gets some of the second one
+        let amounts: Vec<Vec<crate::state::Asset>> = (1..=5u128)
+            .map(|token_id| {
+                let mut amounts = vec![crate::state::Asset::native(NATIVE_DENOM, token_id * 100)];
+                if token_id == 1 {
+                    amounts.push(crate::state::Asset::native(NATIVE_DENOM1, 50u128));
+                }
+                amounts
+            })
+            .collect();
+        let leaves: Vec<String> = amounts
+            .iter()
+            .enumerate()
+            .map(|(index, amounts)| {
+                format!(
+                    "{}:{}",
+                    index + 1,
+                    amounts
+                        .iter()
+                        .map(|asset| asset.to_string())
+                        .collect::<Vec<String>>()
+                        .join(",")
+                )
+            })
+            .collect();
+        let (merkle_root, proofs) = merkle_tree(&leaves);
+        let claim = |token_id: usize| crate::msg::MerkleClaim {
+            token_id: token_id.to_string(),
+            amounts: amounts[token_id - 1].clone(),
+            proof: proofs[token_id - 1].clone(),
+        };
+
+        let distribute_msg = |merkle_root: &str| crate::msg::ExecuteMsg::DistributeMerkle {
+            merkle_root: merkle_root.to_string(),
+            total_assets: vec![
+                crate::state::Asset::native(NATIVE_DENOM, 1500u128),
+                crate::state::Asset::native(NATIVE_DENOM1, 50u128),
+            ],
+            distribution_end_time: start_time.plus_days(1u64),
+            unclaimed_policy: crate::state::UnclaimedPolicy::Distributor,
+            minimum_nfts_to_claim: 1,
+            start_time: None,
+        };
+        let execute_outcome = app.execute_contract(
+            Addr::unchecked(DISTRIBUTOR),
+            nft_reward_dist_addr.clone(),
+            &distribute_msg("not hex"),
+            &[coin(1500u128, NATIVE_DENOM), coin(50u128, NATIVE_DENOM1)],
+        );
+        validate_execution_outcome(execute_outcome, Some("InvalidMerkleRoot"));
+        // Funded with the committed totals, not per token
+        let execute_outcome = app.execute_contract(
+            Addr::unchecked(DISTRIBUTOR),
+            nft_reward_dist_addr.clone(),
+            &distribute_msg(&merkle_root),
+            &[
+                coin(1500u128 / 5, NATIVE_DENOM),
+                coin(50u128, NATIVE_DENOM1),
+            ],
+        );
+        validate_execution_outcome(execute_outcome, Some("InvalidDistributionInputs"));
+        let execute_outcome = app.execute_contract(
+            Addr::unchecked(DISTRIBUTOR),
+            nft_reward_dist_addr.clone(),
+            &distribute_msg(&merkle_root),
+            &[coin(1500u128, NATIVE_DENOM), coin(50u128, NATIVE_DENOM1)],
+        );
+        validate_execution_outcome(execute_outcome, None);
+
+        // A merkle distribution is only claimed with proofs
+        let execute_outcome = app.execute_contract(
+            Addr::unchecked(WALLET1),
+            nft_reward_dist_addr.clone(),
+            &crate::msg::ExecuteMsg::BulkClaim {
+                token_ids: vec!["1".to_string()],
+                start_after: None,
+                dist_ids: None,
+            },
+            &[],
+        );
+        validate_execution_outcome(execute_outcome, Some("ClaimingWindowHasClosed"));
+
+        let mut tampered = claim(2);
+        tampered.amounts = vec![crate::state::Asset::native(NATIVE_DENOM, 1000u128)];
+        let execute_outcome = app.execute_contract(
+            Addr::unchecked(WALLET1),
+            nft_reward_dist_addr.clone(),
+            &crate::msg::ExecuteMsg::ClaimWithProofs {
+                dist_id: 1,
+                claims: vec![claim(1), tampered],
+            },
+            &[],
+        );
+        validate_execution_outcome(execute_outcome, Some("InvalidMerkleProof"));
+
+        // Token 4 is not owned by Wallet 1 so it is not paid
+        let claim_msg = crate::msg::ExecuteMsg::ClaimWithProofs {
+            dist_id: 1,
+            claims: vec![claim(1), claim(2), claim(3), claim(4)],
+        };
+        let execute_outcome = app.execute_contract(
+            Addr::unchecked(WALLET1),
+            nft_reward_dist_addr.clone(),
+            &claim_msg,
+            &[],
+        );
+        validate_execution_outcome(execute_outcome, None);
+        assert_eq!(
+            app.wrap().query_all_balances(WALLET1).unwrap(),
+            vec![
+                coin(50u128, NATIVE_DENOM1),
+                coin(5_000_000_000u128 + 600u128, NATIVE_DENOM)
+            ]
+        );
+        let execute_outcome = app.execute_contract(
+            Addr::unchecked(WALLET1),
+            nft_reward_dist_addr.clone(),
+            &claim_msg,
+            &[],
+        );
+        validate_execution_outcome(execute_outcome, Some("NothingToClaim"));
+
+        let query_result: crate::state::Distribution = app
+            .wrap()
+            .query_wasm_smart(
+                nft_reward_dist_addr.clone(),
+                &crate::msg::QueryMsg::GetDistribution { dist_id: 1 },
+            )
+            .unwrap();
+        assert_eq!(query_result.claimed, 3);
+        assert_eq!(
+            query_result.paid,
+            vec![
+                crate::state::Asset::native(NATIVE_DENOM, 600u128),
+                crate::state::Asset::native(NATIVE_DENOM1, 50u128),
+            ]
+        );
+
+        // The committed total minus what was paid goes back
+        set_block_time(&mut app, start_time.plus_days(1u64).plus_seconds(1u64));
+        let before_return = app.wrap().query_balance(DISTRIBUTOR, NATIVE_DENOM).unwrap();
+        let execute_outcome = app.execute_contract(
+            Addr::unchecked(DISTRIBUTOR),
+            nft_reward_dist_addr.clone(),
+            &crate::msg::ExecuteMsg::ReturnUnclaimed { dist_id: None },
+            &[],
+        );
+        validate_execution_outcome(execute_outcome, None);
+        let after_return = app.wrap().query_balance(DISTRIBUTOR, NATIVE_DENOM).unwrap();
+        assert_eq!(
+            after_return.amount - before_return.amount,
+            Uint128::new(900u128)
+        );
+        assert!(app
+            .wrap()
+            .query_all_balances(nft_reward_dist_addr.to_string())
+            .unwrap()
+            .is_empty());
+    }
+
+    #[test]
+    fn weighted_distributions() {
+        let (mut app, _addresses, nft_reward_dist_addr) = setup_reward_distribution(3, 2);
+        let start_time = app.block_info().time;
+
+        // Tokens 1 and 2 weigh 3, token 3 weighs 1, token 5 weighs 2 and token 4 is missing
+        let upload_msg = crate::msg::ExecuteMsg::UploadWeights {
+            weights: vec![crate::msg::TokenWeight {
+                token_id: "3".to_string(),
+                weight: 1,
+            }],
+            ranges: vec![crate::msg::WeightRange {
+                start: 1,
+                end: 2,
+                weight: 3,
+            }],
+        };
+        let execute_outcome = app.execute_contract(
+            Addr::unchecked(DISTRIBUTOR),
+            nft_reward_dist_addr.clone(),
+            &upload_msg,
+            &[],
+        );
+        validate_execution_outcome(execute_outcome, Some("Unauthorized"));
+        let execute_outcome = app.execute_contract(
+            Addr::unchecked(ADMIN),
+            nft_reward_dist_addr.clone(),
+            &upload_msg,
+            &[],
+        );
+        validate_execution_outcome(execute_outcome, None);
+        let execute_outcome = app.execute_contract(
+            Addr::unchecked(ADMIN),
+            nft_reward_dist_addr.clone(),
+            &crate::msg::ExecuteMsg::UploadWeights {
+                weights: vec![],
+                ranges: vec![crate::msg::WeightRange {
+                    start: 2,
+                    end: 4,
+                    weight: 1,
+                }],
+            },
+            &[],
+        );
+        validate_execution_outcome(execute_outcome, Some("InvalidWeights"));
+        // A single token is replaced when uploaded again, a token inside a wider range is not
+        let upload_weight = |app: &mut StargazeApp, token_id: &str, weight: u64| {
+            app.execute_contract(
+                Addr::unchecked(ADMIN),
+                nft_reward_dist_addr.clone(),
+                &crate::msg::ExecuteMsg::UploadWeights {
+                    weights: vec![crate::msg::TokenWeight {
+                        token_id: token_id.to_string(),
+                        weight,
+                    }],
+                    ranges: vec![],
+                },
+                &[],
+            )
+        };
+        validate_execution_outcome(upload_weight(&mut app, "5", 4), None);
+        validate_execution_outcome(upload_weight(&mut app, "5", 2), None);
+        validate_execution_outcome(upload_weight(&mut app, "2", 1), Some("InvalidWeights"));
+
+        // The table must be frozen before distributing
+        let distribute_msg = crate::msg::ExecuteMsg::DistributeWeighted {
+            assets_per_weight: vec![crate::state::Asset::native(NATIVE_DENOM1, 10u128)],
+            distribution_end_time: start_time.plus_days(1u64),
+            unclaimed_policy: crate::state::UnclaimedPolicy::Distributor,
+            minimum_nfts_to_claim: 1,
+            start_time: None,
+            snapshot_id: None,
+        };
+        let execute_outcome = app.execute_contract(
+            Addr::unchecked(DISTRIBUTOR),
+            nft_reward_dist_addr.clone(),
+            &distribute_msg,
+            &[coin(10u128 * 9u128, NATIVE_DENOM1)],
+        );
+        validate_execution_outcome(execute_outcome, Some("WeightsNotFrozen"));
+        let execute_outcome = app.execute_contract(
+            Addr::unchecked(ADMIN),
+            nft_reward_dist_addr.clone(),
+            &crate::msg::ExecuteMsg::FreezeWeights {},
+            &[],
+        );
+        validate_execution_outcome(execute_outcome, None);
+        let execute_outcome = app.execute_contract(
+            Addr::unchecked(ADMIN),
+            nft_reward_dist_addr.clone(),
+            &upload_msg,
+            &[],
+        );
+        validate_execution_outcome(execute_outcome, Some("WeightsFrozen"));
+
+        let weight_table: crate::state::WeightTable = app
+            .wrap()
+            .query_wasm_smart(
+                nft_reward_dist_addr.clone(),
+                &crate::msg::QueryMsg::GetWeightTable {},
+            )
+            .unwrap();
+        assert_eq!(
+            weight_table,
+            crate::state::WeightTable {
+                total_weight: 9,
+                frozen: true,
+            }
+        );
+        for (token_id, weight) in [("1", 3u64), ("2", 3), ("3", 1), ("4", 0), ("5", 2)] {
+            let query_result: u64 = app
+                .wrap()
+                .query_wasm_smart(
+                    nft_reward_dist_addr.clone(),
+                    &crate::msg::QueryMsg::GetTokenWeight {
+                        token_id: token_id.to_string(),
+                    },
+                )
+                .unwrap();
+            assert_eq!(query_result, weight);
+        }
+
+        // Funds cover the total weight
+        let execute_outcome = app.execute_contract(
+            Addr::unchecked(DISTRIBUTOR),
+            nft_reward_dist_addr.clone(),
+            &distribute_msg,
+            &[coin(10u128 * 5u128, NATIVE_DENOM1)],
+        );
+        validate_execution_outcome(execute_outcome, Some("InvalidDistributionInputs"));
+        let execute_outcome = app.execute_contract(
+            Addr::unchecked(DISTRIBUTOR),
+            nft_reward_dist_addr.clone(),
+            &distribute_msg,
+            &[coin(10u128 * 9u128, NATIVE_DENOM1)],
+        );
+        validate_execution_outcome(execute_outcome, None);
+
+        let execute_outcome = app.execute_contract(
+            Addr::unchecked(WALLET1),
+            nft_reward_dist_addr.clone(),
+            &crate::msg::ExecuteMsg::BulkClaim {
+                token_ids: (1..=3).map(|num| num.to_string()).collect(),
+                start_after: None,
+                dist_ids: None,
+            },
+            &[],
+        );
+        validate_execution_outcome(execute_outcome, None);
+        assert_eq!(
+            app.wrap()
+                .query_balance(WALLET1, NATIVE_DENOM1)
+                .unwrap()
+                .amount,
+            Uint128::new(10u128 * 7u128)
+        );
+
+        // Wallet 2 only claims the token without weight
+        let execute_outcome = app.execute_contract(
+            Addr::unchecked(WALLET2),
+            nft_reward_dist_addr.clone(),
+            &crate::msg::ExecuteMsg::ClaimFive {
+                token_ids: vec!["4".to_string()],
+                dist_ids: None,
+            },
+            &[],
+        );
+        validate_execution_outcome(execute_outcome, None);
+        assert_eq!(
+            app.wrap()
+                .query_balance(WALLET2, NATIVE_DENOM1)
+                .unwrap()
+                .amount,
+            Uint128::zero()
+        );
+
+        // The weight of token 5 is returned
+        set_block_time(&mut app, start_time.plus_days(1u64).plus_seconds(1u64));
+        let execute_outcome = app.execute_contract(
+            Addr::unchecked(DISTRIBUTOR),
+            nft_reward_dist_addr.clone(),
+            &crate::msg::ExecuteMsg::ReturnUnclaimed { dist_id: None },
+            &[],
+        );
+        validate_execution_outcome(execute_outcome, None);
+        assert!(app
+            .wrap()
+            .query_all_balances(nft_reward_dist_addr.to_string())
+            .unwrap()
+            .is_empty());
+    }
+
+    #[derive(serde::Serialize, serde::Deserialize, Clone, Debug)]
+    struct TestMetadata {
+        attributes: Vec<TestTrait>,
+    }
+
+    #[derive(serde::Serialize, serde::Deserialize, Clone, Debug)]
+    struct TestTrait {
+        trait_type: String,
+        value: String,
+    }
+
+    type TraitCollection<'a> = cw721_base::Cw721Contract<
+        'a,
+        Option<TestMetadata>,
+        cosmwasm_std::Empty,
+        cosmwasm_std::Empty,
+        cosmwasm_std::Empty,
+    >;
+
+    fn instantiate_trait_collection(
+        app: &mut StargazeApp,
+        traits: Vec<(&str, Vec<(&str, &str)>)>,
+    ) -> Addr {
+        let code_id = app.store_code(Box::new(ContractWrapper::new_with_empty(
+            |deps,
+             env,
+             info,
+             msg: cw721_base::ExecuteMsg<Option<TestMetadata>, cosmwasm_std::Empty>| {
+                TraitCollection::default().execute(deps, env, info, msg)
+            },
+            |deps, env, info, msg: cw721_base::InstantiateMsg| {
+                TraitCollection::default().instantiate(deps, env, info, msg)
+            },
+            |deps, env, msg: cw721_base::QueryMsg<cosmwasm_std::Empty>| {
+                TraitCollection::default().query(deps, env, msg)
+            },
+        )));
+        let collection = app
+            .instantiate_contract(
+                code_id,
+                Addr::unchecked(ADMIN),
+                &cw721_base::InstantiateMsg {
+                    name: "Trait Collection".to_string(),
+                    symbol: "TRAIT".to_string(),
+                    minter: ADMIN.to_string(),
+                },
+                &[],
+                "traits",
+                None,
+            )
+            .unwrap();
+        for (token_id, attributes) in traits {
+            let owner = if token_id.parse::<u32>().unwrap() > 3 {
+                WALLET2
+            } else {
+                WALLET1
+            };
+            let execute_outcome = app.execute_contract(
+                Addr::unchecked(ADMIN),
+                collection.clone(),
+                &cw721_base::ExecuteMsg::<Option<TestMetadata>, cosmwasm_std::Empty>::Mint {
+                    token_id: token_id.to_string(),
+                    owner: owner.to_string(),
+                    token_uri: None,
+                    extension: Some(TestMetadata {
+                        attributes: attributes
+                            .into_iter()
+                            .map(|(trait_type, value)| TestTrait {
+                                trait_type: trait_type.to_string(),
+                                value: value.to_string(),
+                            })
+                            .collect(),
+                    }),
+                },
+                &[],
+            );
+            validate_execution_outcome(execute_outcome, None);
+        }
+        collection
+    }
+
+    #[test]
+    fn trait_distributions() {
+        let mut app = custom_mock_app();
+        // Tokens 1 to 3 belong to Wallet 1, 4 and 5 to Wallet 2
+        let collection = instantiate_trait_collection(
+            &mut app,
+            vec![
+                ("1", vec![("Background", "Gold")]),
+                ("2", vec![("Background", "Gold"), ("Eyes", "Laser")]),
+                ("3", vec![("Background", "Blue")]),
+                ("4", vec![("Eyes", "Laser")]),
+                ("5", vec![]),
+            ],
+        );
+        let (mut app, nft_reward_dist_addr) =
+            valid_instantiate_nft_reward_distribution(app, collection);
+        let mint_denom_outcome = app.sudo(SudoMsg::Bank(BankSudo::Mint {
+            to_address: DISTRIBUTOR.to_string(),
+            amount: vec![coin(1_000u128, NATIVE_DENOM1)],
+        }));
+        validate_execution_outcome(mint_denom_outcome, None);
+        let start_time = app.block_info().time;
+
+        let set_msg = crate::msg::ExecuteMsg::SetTraitMultipliers {
+            trait_multipliers: vec![
+                crate::state::TraitMultiplier {
+                    trait_type: "Background".to_string(),
+                    value: "Gold".to_string(),
+                    multiplier: cosmwasm_std::Decimal::percent(200),
+                },
+                crate::state::TraitMultiplier {
+                    trait_type: "Eyes".to_string(),
+                    value: "Laser".to_string(),
+                    multiplier: cosmwasm_std::Decimal::percent(150),
+                },
+            ],
+        };
+        let execute_outcome = app.execute_contract(
+            Addr::unchecked(DISTRIBUTOR),
+            nft_reward_dist_addr.clone(),
+            &set_msg,
+            &[],
+        );
+        validate_execution_outcome(execute_outcome, Some("Unauthorized"));
+        let execute_outcome = app.execute_contract(
+            Addr::unchecked(ADMIN),
+            nft_reward_dist_addr.clone(),
+            &set_msg,
+            &[],
+        );
+        validate_execution_outcome(execute_outcome, None);
+
+        // Multipliers of matching traits are multiplied together
+        for (token_id, percent) in [
+            ("1", 200u64),
+            ("2", 300),
+            ("3", 100),
+            ("4", 150),
+            ("5", 100),
+        ] {
+            let query_result: cosmwasm_std::Decimal = app
+                .wrap()
+                .query_wasm_smart(
+                    nft_reward_dist_addr.clone(),
+                    &crate::msg::QueryMsg::GetTokenMultiplier {
+                        token_id: token_id.to_string(),
+                    },
+                )
+                .unwrap();
+            assert_eq!(query_result, cosmwasm_std::Decimal::percent(percent));
+        }
+
+        // Funds cover the declared total weight
+        let distribute_msg = crate::msg::ExecuteMsg::DistributeByTraits {
+            assets_per_weight: vec![crate::state::Asset::native(NATIVE_DENOM1, 10u128)],
+            total_weight: 10,
+            distribution_end_time: start_time.plus_days(1u64),
+            unclaimed_policy: crate::state::UnclaimedPolicy::Distributor,
+            minimum_nfts_to_claim: 1,
+            start_time: None,
+            snapshot_id: None,
+        };
+        let execute_outcome = app.execute_contract(
+            Addr::unchecked(DISTRIBUTOR),
+            nft_reward_dist_addr.clone(),
+            &distribute_msg,
+            &[coin(10u128 * 5u128, NATIVE_DENOM1)],
+        );
+        validate_execution_outcome(execute_outcome, Some("InvalidDistributionInputs"));
+        let execute_outcome = app.execute_contract(
+            Addr::unchecked(DISTRIBUTOR),
+            nft_reward_dist_addr.clone(),
+            &distribute_msg,
+            &[coin(10u128 * 10u128, NATIVE_DENOM1)],
+        );
+        validate_execution_outcome(execute_outcome, None);
+
+        let execute_outcome = app.execute_contract(
+            Addr::unchecked(WALLET1),
+            nft_reward_dist_addr.clone(),
+            &crate::msg::ExecuteMsg::BulkClaim {
+                token_ids: (1..=3).map(|num| num.to_string()).collect(),
+                start_after: None,
+                dist_ids: None,
+            },
+            &[],
+        );
+        validate_execution_outcome(execute_outcome, None);
+        assert_eq!(
+            app.wrap()
+                .query_balance(WALLET1, NATIVE_DENOM1)
+                .unwrap()
+                .amount,
+            Uint128::new(20u128 + 30u128 + 10u128)
+        );
+        let execute_outcome = app.execute_contract(
+            Addr::unchecked(WALLET2),
+            nft_reward_dist_addr.clone(),
+            &crate::msg::ExecuteMsg::ClaimFive {
+                token_ids: vec!["4".to_string()],
+                dist_ids: None,
+            },
+            &[],
+        );
+        validate_execution_outcome(execute_outcome, None);
+        assert_eq!(
+            app.wrap()
+                .query_balance(WALLET2, NATIVE_DENOM1)
+                .unwrap()
+                .amount,
+            Uint128::new(15u128)
+        );
+
+        // What was not paid out of the declared total weight is returned
+        set_block_time(&mut app, start_time.plus_days(1u64).plus_seconds(1u64));
+        let execute_outcome = app.execute_contract(
+            Addr::unchecked(DISTRIBUTOR),
+            nft_reward_dist_addr.clone(),
+            &crate::msg::ExecuteMsg::ReturnUnclaimed { dist_id: None },
+            &[],
+        );
+        validate_execution_outcome(execute_outcome, None);
+        assert_eq!(
+            app.wrap()
+                .query_balance(DISTRIBUTOR, NATIVE_DENOM1)
+                .unwrap()
+                .amount,
+            Uint128::new(1_000u128 - 75u128)
+        );
+    }
+
+    #[test]
+    fn snapshot_distributions() {
+        let (mut app, addresses, nft_reward_dist_addr) = setup_reward_distribution(3, 2);
+        let snapshot_time = app.block_info().time;
+        set_block_time(&mut app, snapshot_time.plus_seconds(60u64));
+        let start_time = app.block_info().time;
+
+        // Owners at the snapshot are uploaded in two chunks
+        let owners = |token_ids: std::ops::RangeInclusive<u32>, owner: &str| {
+            token_ids
+                .map(|token_id| crate::msg::SnapshotOwner {
+                    token_id: token_id.to_string(),
+                    owner: owner.to_string(),
+                })
+                .collect::<Vec<crate::msg::SnapshotOwner>>()
+        };
+        let execute_outcome = app.execute_contract(
+            Addr::unchecked(DISTRIBUTOR),
+            nft_reward_dist_addr.clone(),
+            &crate::msg::ExecuteMsg::UploadSnapshot {
+                snapshot_time,
+                owners: owners(1..=3, WALLET1),
+                merkle_root: None,
+            },
+            &[],
+        );
+        validate_execution_outcome(execute_outcome, Some("Unauthorized"));
+        let execute_outcome = app.execute_contract(
+            Addr::unchecked(ADMIN),
+            nft_reward_dist_addr.clone(),
+            &crate::msg::ExecuteMsg::UploadSnapshot {
+                snapshot_time: start_time.plus_seconds(1u64),
+                owners: owners(1..=3, WALLET1),
+                merkle_root: None,
+            },
+            &[],
+        );
+        validate_execution_outcome(execute_outcome, Some("InvalidSnapshot"));
+        for (token_ids, owner) in [(1..=3, WALLET1), (4..=5, WALLET2)] {
+            let execute_outcome = app.execute_contract(
+                Addr::unchecked(ADMIN),
+                nft_reward_dist_addr.clone(),
+                &crate::msg::ExecuteMsg::UploadSnapshot {
+                    snapshot_time,
+                    owners: owners(token_ids, owner),
+                    merkle_root: None,
+                },
+                &[],
+            );
+            validate_execution_outcome(execute_outcome, None);
+        }
+        let snapshot: Option<crate::state::Snapshot> = app
+            .wrap()
+            .query_wasm_smart(
+                nft_reward_dist_addr.clone(),
+                &crate::msg::QueryMsg::GetSnapshot {},
+            )
+            .unwrap();
+        assert_eq!(
+            snapshot,
+            Some(crate::state::Snapshot {
+                snapshot_id: 1,
+                time: snapshot_time,
+                merkle_root: None,
+            })
+        );
+        let snapshot_owner: Option<Addr> = app
+            .wrap()
+            .query_wasm_smart(
+                nft_reward_dist_addr.clone(),
+                &crate::msg::QueryMsg::GetSnapshotOwner {
+                    snapshot_id: None,
+                    token_id: "4".to_string(),
+                },
+            )
+            .unwrap();
+        assert_eq!(snapshot_owner, Some(Addr::unchecked(WALLET2)));
+
+        // Wallet 1 sells token 1 after the snapshot
+        let execute_outcome = app.execute_contract(
+            Addr::unchecked(WALLET1),
+            addresses.nft_addr.clone(),
+            &cw721::Cw721ExecuteMsg::TransferNft {
+                recipient: WALLET3.to_string(),
+                token_id: "1".to_string(),
+            },
+            &[],
+        );
+        validate_execution_outcome(execute_outcome, None);
+
+        // Only the snapshot named by the distributor can be used
+        let snapshot_msg = |snapshot_id: Option<u64>| crate::msg::ExecuteMsg::Distribute {
+            assets_per_nft: vec![crate::state::Asset::native(NATIVE_DENOM1, 100u128)],
+            distribution_end_time: start_time.plus_days(1u64),
+            unclaimed_policy: crate::state::UnclaimedPolicy::Distributor,
+            minimum_nfts_to_claim: 1,
+            start_time: None,
+            max_tokens_per_wallet: None,
+            max_amount_per_wallet: None,
+            max_token_id: None,
+            snapshot_id,
+        };
+        let execute_outcome = app.execute_contract(
+            Addr::unchecked(DISTRIBUTOR),
+            nft_reward_dist_addr.clone(),
+            &snapshot_msg(Some(2)),
+            &[coin(100u128 * 5u128, NATIVE_DENOM1)],
+        );
+        validate_execution_outcome(execute_outcome, Some("UnknownSnapshot"));
+        let execute_outcome = app.execute_contract(
+            Addr::unchecked(DISTRIBUTOR),
+            nft_reward_dist_addr.clone(),
+            &snapshot_msg(Some(1)),
+            &[coin(100u128 * 5u128, NATIVE_DENOM1)],
+        );
+        validate_execution_outcome(execute_outcome, None);
+
+        // Snapshot distributions are not claimed by the current owners
+        let execute_outcome = app.execute_contract(
+            Addr::unchecked(WALLET3),
+            nft_reward_dist_addr.clone(),
+            &crate::msg::ExecuteMsg::ClaimFive {
+                token_ids: vec!["1".to_string()],
+                dist_ids: None,
+            },
+            &[],
+        );
+        validate_execution_outcome(execute_outcome, Some("ClaimingWindowHasClosed"));
+        let claims = |token_ids: std::ops::RangeInclusive<u32>| {
+            token_ids
+                .map(|token_id| crate::msg::SnapshotClaim {
+                    token_id: token_id.to_string(),
+                    proof: vec![],
+                })
+                .collect::<Vec<crate::msg::SnapshotClaim>>()
+        };
+        let execute_outcome = app.execute_contract(
+            Addr::unchecked(WALLET3),
+            nft_reward_dist_addr.clone(),
+            &crate::msg::ExecuteMsg::ClaimFromSnapshot {
+                dist_id: 1,
+                claims: claims(1..=1),
+            },
+            &[],
+        );
+        validate_execution_outcome(execute_outcome, Some("NothingToClaim"));
+
+        // The seller is paid for the token it held at the snapshot
+        let execute_outcome = app.execute_contract(
+            Addr::unchecked(WALLET1),
+            nft_reward_dist_addr.clone(),
+            &crate::msg::ExecuteMsg::ClaimFromSnapshot {
+                dist_id: 1,
+                claims: claims(1..=5),
+            },
+            &[],
+        );
+        validate_execution_outcome(execute_outcome, None);
+        assert_eq!(
+            app.wrap()
+                .query_balance(WALLET1, NATIVE_DENOM1)
+                .unwrap()
+                .amount,
+            Uint128::new(100u128 * 3u128)
+        );
+        let execute_outcome = app.execute_contract(
+            Addr::unchecked(WALLET1),
+            nft_reward_dist_addr.clone(),
+            &crate::msg::ExecuteMsg::ClaimFromSnapshot {
+                dist_id: 1,
+                claims: claims(1..=3),
+            },
+            &[],
+        );
+        validate_execution_outcome(execute_outcome, Some("NothingToClaim"));
+
+        // A merkle snapshot of the owners starts a new snapshot, owners claim with their proofs
+        let (merkle_root, proofs) = merkle_tree(
+            &(1..=5)
+                .map(|token_id| {
+                    format!(
+                        "{token_id}:{}",
+                        if token_id > 3 { WALLET2 } else { WALLET1 }
+                    )
+                })
+                .collect::<Vec<String>>(),
+        );
+        let execute_outcome = app.execute_contract(
+            Addr::unchecked(ADMIN),
+            nft_reward_dist_addr.clone(),
+            &crate::msg::ExecuteMsg::UploadSnapshot {
+                snapshot_time,
+                owners: vec![],
+                merkle_root: Some(merkle_root.clone()),
+            },
+            &[],
+        );
+        validate_execution_outcome(execute_outcome, None);
+        let execute_outcome = app.execute_contract(
+            Addr::unchecked(DISTRIBUTOR),
+            nft_reward_dist_addr.clone(),
+            &crate::msg::ExecuteMsg::Distribute {
+                assets_per_nft: vec![crate::state::Asset::native(NATIVE_DENOM2, 10u128)],
+                distribution_end_time: start_time.plus_days(1u64),
+                unclaimed_policy: crate::state::UnclaimedPolicy::Distributor,
+                minimum_nfts_to_claim: 2,
+                start_time: None,
+                max_tokens_per_wallet: None,
+                max_amount_per_wallet: None,
+                max_token_id: None,
+                snapshot_id: Some(2),
+            },
+            &[coin(10u128 * 5u128, NATIVE_DENOM2)],
+        );
+        validate_execution_outcome(execute_outcome, None);
+        let merkle_claims = |token_ids: std::ops::RangeInclusive<u32>| {
+            token_ids
+                .map(|token_id| crate::msg::SnapshotClaim {
+                    token_id: token_id.to_string(),
+                    proof: proofs[token_id as usize - 1].clone(),
+                })
+                .collect::<Vec<crate::msg::SnapshotClaim>>()
+        };
+        let execute_outcome = app.execute_contract(
+            Addr::unchecked(WALLET1),
+            nft_reward_dist_addr.clone(),
+            &crate::msg::ExecuteMsg::ClaimFromSnapshot {
+                dist_id: 2,
+                claims: merkle_claims(4..=5),
+            },
+            &[],
+        );
+        validate_execution_outcome(execute_outcome, Some("InvalidMerkleProof"));
+        let execute_outcome = app.execute_contract(
+            Addr::unchecked(WALLET2),
+            nft_reward_dist_addr.clone(),
+            &crate::msg::ExecuteMsg::ClaimFromSnapshot {
+                dist_id: 2,
+                claims: merkle_claims(4..=4),
+            },
+            &[],
+        );
+        validate_execution_outcome(execute_outcome, Some("NotEnoughNftsToClaim"));
+        let execute_outcome = app.execute_contract(
+            Addr::unchecked(WALLET2),
+            nft_reward_dist_addr.clone(),
+            &crate::msg::ExecuteMsg::ClaimFromSnapshot {
+                dist_id: 2,
+                claims: merkle_claims(4..=5),
+            },
+            &[],
+        );
+        validate_execution_outcome(execute_outcome, None);
+        assert_eq!(
+            app.wrap()
+                .query_balance(WALLET2, NATIVE_DENOM2)
+                .unwrap()
+                .amount,
+            Uint128::new(10u128 * 2u128)
+        );
+
+        // Rounds that do not name the snapshot pay the current owners
+        let execute_outcome = app.execute_contract(
+            Addr::unchecked(DISTRIBUTOR),
+            nft_reward_dist_addr.clone(),
+            &crate::msg::ExecuteMsg::Distribute {
+                assets_per_nft: vec![crate::state::Asset::native(NATIVE_DENOM3, 10u128)],
+                distribution_end_time: start_time.plus_days(1u64),
+                unclaimed_policy: crate::state::UnclaimedPolicy::Distributor,
+                minimum_nfts_to_claim: 1,
+                start_time: None,
+                max_tokens_per_wallet: None,
+                max_amount_per_wallet: None,
+                max_token_id: None,
+                snapshot_id: None,
+            },
+            &[coin(10u128 * 5u128, NATIVE_DENOM3)],
+        );
+        validate_execution_outcome(execute_outcome, None);
+        let execute_outcome = app.execute_contract(
+            Addr::unchecked(WALLET3),
+            nft_reward_dist_addr.clone(),
+            &crate::msg::ExecuteMsg::ClaimFive {
+                token_ids: vec!["1".to_string()],
+                dist_ids: Some(vec![3]),
+            },
+            &[],
+        );
+        validate_execution_outcome(execute_outcome, None);
+        assert_eq!(
+            app.wrap()
+                .query_balance(WALLET3, NATIVE_DENOM3)
+                .unwrap()
+                .amount,
+            Uint128::new(10u128)
+        );
+
+        // Once cleared, the snapshot cannot be named any more
+        let execute_outcome = app.execute_contract(
+            Addr::unchecked(ADMIN),
+            nft_reward_dist_addr.clone(),
+            &crate::msg::ExecuteMsg::ClearSnapshot {},
+            &[],
+        );
+        validate_execution_outcome(execute_outcome, None);
+        let execute_outcome = app.execute_contract(
+            Addr::unchecked(DISTRIBUTOR),
+            nft_reward_dist_addr.clone(),
+            &snapshot_msg(Some(2)),
+            &[coin(100u128 * 5u128, NATIVE_DENOM1)],
+        );
+        validate_execution_outcome(execute_outcome, Some("UnknownSnapshot"));
+    }
+
+    #[test]
+    fn nft_count_refresh() {
+        let (mut app, addresses, nft_reward_dist_addr) = setup_reward_distribution(3, 2);
+        let start_time = app.block_info().time;
+
+        // Token 3 is burned after the instantiation
+        let execute_outcome = app.execute_contract(
+            Addr::unchecked(WALLET1),
+            addresses.nft_addr.clone(),
+            &cw721::Cw721ExecuteMsg::Burn {
+                token_id: "3".to_string(),
+            },
+            &[],
+        );
+        validate_execution_outcome(execute_outcome, None);
+
+        let distribute_msg = crate::msg::ExecuteMsg::Distribute {
+            assets_per_nft: vec![crate::state::Asset::native(NATIVE_DENOM1, 100u128)],
+            distribution_end_time: start_time.plus_days(1u64),
+            unclaimed_policy: crate::state::UnclaimedPolicy::Distributor,
+            minimum_nfts_to_claim: 1,
+            start_time: None,
+            max_tokens_per_wallet: None,
+            max_amount_per_wallet: None,
+            max_token_id: Some(5),
+            snapshot_id: None,
+        };
+        let execute_outcome = app.execute_contract(
+            Addr::unchecked(DISTRIBUTOR),
+            nft_reward_dist_addr.clone(),
+            &distribute_msg,
+            &[coin(100u128 * 3u128, NATIVE_DENOM1)],
+        );
+        validate_execution_outcome(execute_outcome, Some("InvalidDistributionInputs"));
+        let execute_outcome = app.execute_contract(
+            Addr::unchecked(DISTRIBUTOR),
+            nft_reward_dist_addr.clone(),
+            &distribute_msg,
+            &[coin(100u128 * 4u128, NATIVE_DENOM1)],
+        );
+        validate_execution_outcome(execute_outcome, None);
+        let distribution: crate::state::Distribution = app
+            .wrap()
+            .query_wasm_smart(
+                nft_reward_dist_addr.clone(),
+                &crate::msg::QueryMsg::GetDistribution { dist_id: 1 },
+            )
+            .unwrap();
+        assert_eq!(distribution.nft_count, 4);
+        assert_eq!(distribution.max_token_id, Some(5));
+
+        // Token 6 is minted once the round has begun and is above the limit set by the distributor
+        let execute_outcome = app.execute_contract(
+            Addr::unchecked(ADMIN),
+            addresses.minter_addr.clone(),
+            &vending_minter::msg::ExecuteMsg::MintFor {
+                token_id: 6,
+                recipient: WALLET2.to_string(),
+            },
+            &[],
+        );
+        validate_execution_outcome(execute_outcome, None);
+        let execute_outcome = app.execute_contract(
+            Addr::unchecked(WALLET2),
+            nft_reward_dist_addr.clone(),
+            &crate::msg::ExecuteMsg::ClaimFive {
+                token_ids: vec!["6".to_string()],
+                dist_ids: None,
+            },
+            &[],
+        );
+        validate_execution_outcome(execute_outcome, Some("NothingToClaim"));
+        let execute_outcome = app.execute_contract(
+            Addr::unchecked(WALLET2),
+            nft_reward_dist_addr.clone(),
+            &crate::msg::ExecuteMsg::ClaimFive {
+                token_ids: vec!["4".to_string(), "6".to_string()],
+                dist_ids: None,
+            },
+            &[],
+        );
+        validate_execution_outcome(execute_outcome, None);
+        assert_eq!(
+            app.wrap()
+                .query_balance(WALLET2, NATIVE_DENOM1)
+                .unwrap()
+                .amount,
+            Uint128::new(100u128)
+        );
+
+        // The next distribution counts it and is limited to the highest id minted by default
+        let execute_outcome = app.execute_contract(
+            Addr::unchecked(DISTRIBUTOR),
+            nft_reward_dist_addr.clone(),
+            &crate::msg::ExecuteMsg::Distribute {
+                assets_per_nft: vec![crate::state::Asset::native(NATIVE_DENOM2, 10u128)],
+                distribution_end_time: start_time.plus_days(1u64),
+                unclaimed_policy: crate::state::UnclaimedPolicy::Distributor,
+                minimum_nfts_to_claim: 1,
+                start_time: None,
+                max_tokens_per_wallet: None,
+                max_amount_per_wallet: None,
+                max_token_id: None,
+                snapshot_id: None,
+            },
+            &[coin(10u128 * 5u128, NATIVE_DENOM2)],
+        );
+        validate_execution_outcome(execute_outcome, None);
+        let distribution: crate::state::Distribution = app
+            .wrap()
+            .query_wasm_smart(
+                nft_reward_dist_addr.clone(),
+                &crate::msg::QueryMsg::GetDistribution { dist_id: 2 },
+            )
+            .unwrap();
+        assert_eq!(distribution.nft_count, 5);
+        assert_eq!(distribution.max_token_id, Some(6));
+        let execute_outcome = app.execute_contract(
+            Addr::unchecked(ADMIN),
+            addresses.minter_addr.clone(),
+            &vending_minter::msg::ExecuteMsg::MintFor {
+                token_id: 7,
+                recipient: WALLET2.to_string(),
+            },
+            &[],
+        );
+        validate_execution_outcome(execute_outcome, None);
+        let execute_outcome = app.execute_contract(
+            Addr::unchecked(WALLET2),
+            nft_reward_dist_addr.clone(),
+            &crate::msg::ExecuteMsg::ClaimFive {
+                token_ids: vec!["7".to_string()],
+                dist_ids: Some(vec![2]),
+            },
+            &[],
+        );
+        validate_execution_outcome(execute_outcome, Some("NothingToClaim"));
+
+        // A limit set too high still cannot pay more tokens than funded
+        let execute_outcome = app.execute_contract(
+            Addr::unchecked(DISTRIBUTOR),
+            nft_reward_dist_addr.clone(),
+            &crate::msg::ExecuteMsg::Distribute {
+                assets_per_nft: vec![crate::state::Asset::native(NATIVE_DENOM3, 10u128)],
+                distribution_end_time: start_time.plus_days(1u64),
+                unclaimed_policy: crate::state::UnclaimedPolicy::Distributor,
+                minimum_nfts_to_claim: 1,
+                start_time: None,
+                max_tokens_per_wallet: None,
+                max_amount_per_wallet: None,
+                max_token_id: Some(100),
+                snapshot_id: None,
+            },
+            &[coin(10u128 * 6u128, NATIVE_DENOM3)],
+        );
+        validate_execution_outcome(execute_outcome, None);
+        let execute_outcome = app.execute_contract(
+            Addr::unchecked(ADMIN),
+            addresses.minter_addr.clone(),
+            &vending_minter::msg::ExecuteMsg::MintFor {
+                token_id: 8,
+                recipient: WALLET2.to_string(),
+            },
+            &[],
+        );
+        validate_execution_outcome(execute_outcome, None);
+        let execute_outcome = app.execute_contract(
+            Addr::unchecked(WALLET1),
+            nft_reward_dist_addr.clone(),
+            &crate::msg::ExecuteMsg::ClaimFive {
+                token_ids: vec!["1".to_string(), "2".to_string()],
+                dist_ids: Some(vec![3]),
+            },
+            &[],
+        );
+        validate_execution_outcome(execute_outcome, None);
+        let execute_outcome = app.execute_contract(
+            Addr::unchecked(WALLET2),
+            nft_reward_dist_addr.clone(),
+            &crate::msg::ExecuteMsg::ClaimFive {
+                token_ids: vec!["4".to_string(), "5".to_string(), "6".to_string(), "7".to_string(), "8".to_string()],
+                dist_ids: Some(vec![3]),
+            },
+            &[],
+        );
+        validate_execution_outcome(execute_outcome, Some("AllTokensClaimed"));
+        let execute_outcome = app.execute_contract(
+            Addr::unchecked(WALLET2),
+            nft_reward_dist_addr.clone(),
+            &crate::msg::ExecuteMsg::ClaimFive {
+                token_ids: vec!["4".to_string(), "5".to_string(), "6".to_string(), "7".to_string()],
+                dist_ids: Some(vec![3]),
+            },
+            &[],
+        );
+        validate_execution_outcome(execute_outcome, None);
+        let execute_outcome = app.execute_contract(
+            Addr::unchecked(WALLET2),
+            nft_reward_dist_addr.clone(),
+            &crate::msg::ExecuteMsg::ClaimFive {
+                token_ids: vec!["8".to_string()],
+                dist_ids: Some(vec![3]),
+            },
+            &[],
+        );
+        validate_execution_outcome(execute_outcome, Some("AllTokensClaimed"));
+        assert_eq!(
+            app.wrap()
+                .query_balance(WALLET2, NATIVE_DENOM3)
+                .unwrap()
+                .amount,
+            Uint128::new(10u128 * 4u128)
+        );
+    }
+
+    #[test]
+    fn multi_collection_distributions() {
+        let (mut app, addresses, nft_reward_dist_addr) = setup_reward_distribution(3, 2);
+        let start_time = app.block_info().time;
+
+        // Partner tokens 1 and 2 belong to Wallet 1, token 3 to Wallet 2
+        let partner_addr = instantiate_partner_collection(&mut app, 3);
+        for (token_id, recipient) in [("1", WALLET1), ("2", WALLET1), ("3", WALLET2)] {
+            let execute_outcome = app.execute_contract(
+                Addr::unchecked(DISTRIBUTOR),
+                partner_addr.clone(),
+                &cw721::Cw721ExecuteMsg::TransferNft {
+                    recipient: recipient.to_string(),
+                    token_id: token_id.to_string(),
+                },
+                &[],
+            );
+            validate_execution_outcome(execute_outcome, None);
+        }
+
+        let distribute_msg = crate::msg::ExecuteMsg::DistributeCollections {
+            collections: vec![
+                crate::msg::CollectionAssets {
+                    collection: addresses.nft_addr.to_string(),
+                    assets_per_nft: vec![crate::state::Asset::native(NATIVE_DENOM1, 100u128)],
+                    minimum_nfts_to_claim: 1,
+                    max_token_id: None,
+                },
+                crate::msg::CollectionAssets {
+                    collection: partner_addr.to_string(),
+                    assets_per_nft: vec![
+                        crate::state::Asset::native(NATIVE_DENOM1, 50u128),
+                        crate::state::Asset::native(NATIVE_DENOM2, 10u128),
+                    ],
+                    minimum_nfts_to_claim: 2,
+                    max_token_id: None,
+                },
+            ],
+            distribution_end_time: start_time.plus_days(1u64),
+            unclaimed_policy: crate::state::UnclaimedPolicy::Distributor,
+            start_time: None,
+        };
+        let funds = [
+            coin(100u128 * 5u128 + 50u128 * 3u128, NATIVE_DENOM1),
+            coin(10u128 * 3u128, NATIVE_DENOM2),
+        ];
+        let execute_outcome = app.execute_contract(
+            Addr::unchecked(DISTRIBUTOR),
+            nft_reward_dist_addr.clone(),
+            &distribute_msg,
+            &funds,
+        );
+        validate_execution_outcome(execute_outcome, Some("UnknownCollection"));
+        let add_collection_msg = crate::msg::ExecuteMsg::AddCollection {
+            collection: partner_addr.to_string(),
+        };
+        let execute_outcome = app.execute_contract(
+            Addr::unchecked(DISTRIBUTOR),
+            nft_reward_dist_addr.clone(),
+            &add_collection_msg,
+            &[],
+        );
+        validate_execution_outcome(execute_outcome, Some("Unauthorized"));
+        let execute_outcome = app.execute_contract(
+            Addr::unchecked(ADMIN),
+            nft_reward_dist_addr.clone(),
+            &add_collection_msg,
+            &[],
+        );
+        validate_execution_outcome(execute_outcome, None);
+
+        // Funds cover every collection
+        let execute_outcome = app.execute_contract(
+            Addr::unchecked(DISTRIBUTOR),
+            nft_reward_dist_addr.clone(),
+            &distribute_msg,
+            &[
+                coin(100u128 * 5u128, NATIVE_DENOM1),
+                coin(10u128 * 3u128, NATIVE_DENOM2),
+            ],
+        );
+        validate_execution_outcome(execute_outcome, Some("InvalidDistributionInputs"));
+        let execute_outcome = app.execute_contract(
+            Addr::unchecked(DISTRIBUTOR),
+            nft_reward_dist_addr.clone(),
+            &distribute_msg,
+            &funds,
+        );
+        validate_execution_outcome(execute_outcome, None);
+        let collections: Vec<(Addr, crate::state::CollectionInfo)> = app
+            .wrap()
+            .query_wasm_smart(
+                nft_reward_dist_addr.clone(),
+                &crate::msg::QueryMsg::GetCollections {},
+            )
+            .unwrap();
+        assert_eq!(
+            collections,
+            vec![(
+                partner_addr.clone(),
+                crate::state::CollectionInfo { nft_count: 3 }
+            )]
+        );
+
+        let collection_token = |collection: &Addr, token_id: &str| crate::msg::CollectionToken {
+            collection: collection.to_string(),
+            token_id: token_id.to_string(),
+        };
+        let execute_outcome = app.execute_contract(
+            Addr::unchecked(WALLET1),
+            nft_reward_dist_addr.clone(),
+            &crate::msg::ExecuteMsg::ClaimCollections {
+                tokens: vec![
+                    collection_token(&addresses.nft_addr, "1"),
+                    collection_token(&addresses.nft_addr, "2"),
+                    collection_token(&partner_addr, "1"),
+                    collection_token(&partner_addr, "2"),
+                ],
+                dist_ids: None,
+            },
+            &[],
+        );
+        validate_execution_outcome(execute_outcome, None);
+        assert_eq!(
+            app.wrap().query_all_balances(WALLET1).unwrap(),
+            vec![
+                coin(10u128 * 2u128, NATIVE_DENOM2),
+                coin(100u128 * 2u128 + 50u128 * 2u128, NATIVE_DENOM1),
+                coin(5_000_000_000u128, NATIVE_DENOM),
+            ]
+        );
+        let execute_outcome = app.execute_contract(
+            Addr::unchecked(WALLET1),
+            nft_reward_dist_addr.clone(),
+            &crate::msg::ExecuteMsg::ClaimCollections {
+                tokens: vec![collection_token(&partner_addr, "1")],
+                dist_ids: None,
+            },
+            &[],
+        );
+        validate_execution_outcome(execute_outcome, Some("NothingToClaim"));
+
+        // Wallet 2 holds a single partner token, below that collection's minimum
+        let execute_outcome = app.execute_contract(
+            Addr::unchecked(WALLET2),
+            nft_reward_dist_addr.clone(),
+            &crate::msg::ExecuteMsg::ClaimCollections {
+                tokens: vec![
+                    collection_token(&addresses.nft_addr, "4"),
+                    collection_token(&partner_addr, "3"),
+                ],
+                dist_ids: None,
+            },
+            &[],
+        );
+        validate_execution_outcome(execute_outcome, None);
+        assert_eq!(
+            app.wrap()
+                .query_balance(WALLET2, NATIVE_DENOM1)
+                .unwrap()
+                .amount,
+            Uint128::new(100u128)
+        );
+        for (token_id, claimed) in [("1", true), ("3", false)] {
+            let query_result: bool = app
+                .wrap()
+                .query_wasm_smart(
+                    nft_reward_dist_addr.clone(),
+                    &crate::msg::QueryMsg::GetIfClaimedInCollection {
+                        dist_id: 1,
+                        collection: partner_addr.to_string(),
+                        token_id: token_id.to_string(),
+                    },
+                )
+                .unwrap();
+            assert_eq!(query_result, claimed);
+        }
+
+        // The unclaimed of each collection goes back
+        set_block_time(&mut app, start_time.plus_days(1u64).plus_seconds(1u64));
+        let before_return = app.wrap().query_all_balances(DISTRIBUTOR).unwrap();
+        let execute_outcome = app.execute_contract(
+            Addr::unchecked(DISTRIBUTOR),
+            nft_reward_dist_addr.clone(),
+            &crate::msg::ExecuteMsg::ReturnUnclaimed { dist_id: None },
+            &[],
+        );
+        validate_execution_outcome(execute_outcome, None);
+        let after_return = app.wrap().query_all_balances(DISTRIBUTOR).unwrap();
+        for (denom, returned) in [
+            (NATIVE_DENOM1, 100u128 * 2u128 + 50u128),
+            (NATIVE_DENOM2, 10u128),
+        ] {
+            let balance = |balances: &Vec<Coin>| {
+                balances
+                    .iter()
+                    .find(|balance| balance.denom == denom)
+                    .unwrap()
+                    .amount
+            };
+            assert_eq!(
+                balance(&after_return) - balance(&before_return),
+                Uint128::new(returned)
+            );
+        }
+        assert!(app
+            .wrap()
+            .query_all_balances(nft_reward_dist_addr.to_string())
+            .unwrap()
+            .is_empty());
+    }
+
+    #[test]
+    fn set_distributions() {
+        let mut app = custom_mock_app();
+        // Tokens 1 to 3 belong to Wallet 1, 4 and 5 to Wallet 2
+        let collection = instantiate_trait_collection(
+            &mut app,
+            vec![
+                ("1", vec![("Background", "Gold")]),
+                ("2", vec![("Background", "Gold"), ("Eyes", "Laser")]),
+                ("3", vec![("Background", "Blue")]),
+                ("4", vec![("Background", "Gold")]),
+                ("5", vec![]),
+            ],
+        );
+        let (mut app, nft_reward_dist_addr) =
+            valid_instantiate_nft_reward_distribution(app, collection.clone());
+        let mint_denom_outcome = app.sudo(SudoMsg::Bank(BankSudo::Mint {
+            to_address: DISTRIBUTOR.to_string(),
+            amount: vec![coin(1_000u128, NATIVE_DENOM1)],
+        }));
+        validate_execution_outcome(mint_denom_outcome, None);
+        let start_time = app.block_info().time;
+
+        // Partner tokens 1 and 2 belong to Wallet 1
+        let partner_addr = instantiate_partner_collection(&mut app, 2);
+        for token_id in ["1", "2"] {
+            let execute_outcome = app.execute_contract(
+                Addr::unchecked(DISTRIBUTOR),
+                partner_addr.clone(),
+                &cw721::Cw721ExecuteMsg::TransferNft {
+                    recipient: WALLET1.to_string(),
+                    token_id: token_id.to_string(),
+                },
+                &[],
+            );
+            validate_execution_outcome(execute_outcome, None);
+        }
+
+        // A set is a gold background token and any partner token
+        let distribute_msg = crate::msg::ExecuteMsg::DistributeSets {
+            assets_per_set: vec![crate::state::Asset::native(NATIVE_DENOM1, 100u128)],
+            components: vec![
+                crate::msg::RequiredToken {
+                    collection: collection.to_string(),
+                    traits: vec![("Background".to_string(), "Gold".to_string())],
+                },
+                crate::msg::RequiredToken {
+                    collection: partner_addr.to_string(),
+                    traits: vec![],
+                },
+            ],
+            max_sets: 3,
+            distribution_end_time: start_time.plus_days(1u64),
+            unclaimed_policy: crate::state::UnclaimedPolicy::Distributor,
+            start_time: None,
+        };
+        let execute_outcome = app.execute_contract(
+            Addr::unchecked(DISTRIBUTOR),
+            nft_reward_dist_addr.clone(),
+            &distribute_msg,
+            &[coin(100u128 * 3u128, NATIVE_DENOM1)],
+        );
+        validate_execution_outcome(execute_outcome, Some("UnknownCollection"));
+        let execute_outcome = app.execute_contract(
+            Addr::unchecked(ADMIN),
+            nft_reward_dist_addr.clone(),
+            &crate::msg::ExecuteMsg::AddCollection {
+                collection: partner_addr.to_string(),
+            },
+            &[],
+        );
+        validate_execution_outcome(execute_outcome, None);
+        let execute_outcome = app.execute_contract(
+            Addr::unchecked(DISTRIBUTOR),
+            nft_reward_dist_addr.clone(),
+            &distribute_msg,
+            &[coin(100u128 * 3u128, NATIVE_DENOM1)],
+        );
+        validate_execution_outcome(execute_outcome, None);
+
+        // Sets are only claimed with ClaimSets
+        let execute_outcome = app.execute_contract(
+            Addr::unchecked(WALLET1),
+            nft_reward_dist_addr.clone(),
+            &crate::msg::ExecuteMsg::ClaimFive {
+                token_ids: vec!["1".to_string()],
+                dist_ids: None,
+            },
+            &[],
+        );
+        validate_execution_outcome(execute_outcome, Some("ClaimingWindowHasClosed"));
+
+        let set = |token_id: &str, partner_token_id: &str| {
+            vec![
+                crate::msg::CollectionToken {
+                    collection: collection.to_string(),
+                    token_id: token_id.to_string(),
+                },
+                crate::msg::CollectionToken {
+                    collection: partner_addr.to_string(),
+                    token_id: partner_token_id.to_string(),
+                },
+            ]
+        };
+        let execute_outcome = app.execute_contract(
+            Addr::unchecked(WALLET1),
+            nft_reward_dist_addr.clone(),
+            &crate::msg::ExecuteMsg::ClaimSets {
+                dist_id: 1,
+                sets: vec![set("1", "1")[..1].to_vec()],
+            },
+            &[],
+        );
+        validate_execution_outcome(execute_outcome, Some("InvalidSet"));
+
+        // Token 3 does not have the trait, the tokens of the first set are used
+        let execute_outcome = app.execute_contract(
+            Addr::unchecked(WALLET1),
+            nft_reward_dist_addr.clone(),
+            &crate::msg::ExecuteMsg::ClaimSets {
+                dist_id: 1,
+                sets: vec![set("1", "1"), set("3", "2")],
+            },
+            &[],
+        );
+        validate_execution_outcome(execute_outcome, None);
+        assert_eq!(
+            app.wrap()
+                .query_balance(WALLET1, NATIVE_DENOM1)
+                .unwrap()
+                .amount,
+            Uint128::new(100u128)
+        );
+        let execute_outcome = app.execute_contract(
+            Addr::unchecked(WALLET1),
+            nft_reward_dist_addr.clone(),
+            &crate::msg::ExecuteMsg::ClaimSets {
+                dist_id: 1,
+                sets: vec![set("2", "1")],
+            },
+            &[],
+        );
+        validate_execution_outcome(execute_outcome, Some("NothingToClaim"));
+        let execute_outcome = app.execute_contract(
+            Addr::unchecked(WALLET1),
+            nft_reward_dist_addr.clone(),
+            &crate::msg::ExecuteMsg::ClaimSets {
+                dist_id: 1,
+                sets: vec![set("2", "2")],
+            },
+            &[],
+        );
+        validate_execution_outcome(execute_outcome, None);
+        assert_eq!(
+            app.wrap()
+                .query_balance(WALLET1, NATIVE_DENOM1)
+                .unwrap()
+                .amount,
+            Uint128::new(100u128 * 2u128)
+        );
+        let query_result: bool = app
+            .wrap()
+            .query_wasm_smart(
+                nft_reward_dist_addr.clone(),
+                &crate::msg::QueryMsg::GetIfClaimedInCollection {
+                    dist_id: 1,
+                    collection: partner_addr.to_string(),
+                    token_id: "2".to_string(),
+                },
+            )
+            .unwrap();
+        assert!(query_result);
+
+        // The set that was not claimed goes back
+        set_block_time(&mut app, start_time.plus_days(1u64).plus_seconds(1u64));
+        let execute_outcome = app.execute_contract(
+            Addr::unchecked(DISTRIBUTOR),
+            nft_reward_dist_addr.clone(),
+            &crate::msg::ExecuteMsg::ReturnUnclaimed { dist_id: None },
+            &[],
+        );
+        validate_execution_outcome(execute_outcome, None);
+        assert_eq!(
+            app.wrap()
+                .query_balance(DISTRIBUTOR, NATIVE_DENOM1)
+                .unwrap()
+                .amount,
+            Uint128::new(1_000u128 - 200u128)
+        );
+    }
+
+    #[test]
+    fn tiered_distributions() {
+        let (mut app, _addresses, nft_reward_dist_addr) = setup_reward_distribution(5, 1);
+        let start_time = app.block_info().time;
+
+        let tiered_msg =
+            |tiers: Vec<crate::state::Tier>| crate::msg::ExecuteMsg::DistributeTiered {
+                assets_per_nft: vec![crate::state::Asset::native(NATIVE_DENOM1, 100u128)],
+                tiers,
+                distribution_end_time: start_time.plus_days(1u64),
+                unclaimed_policy: crate::state::UnclaimedPolicy::Distributor,
+                minimum_nfts_to_claim: 1,
+                start_time: None,
+            };
+        let execute_outcome = app.execute_contract(
+            Addr::unchecked(DISTRIBUTOR),
+            nft_reward_dist_addr.clone(),
+            &tiered_msg(vec![crate::state::Tier {
+                min_nfts: 2,
+                multiplier: cosmwasm_std::Decimal::one(),
+            }]),
+            &[coin(100u128 * 6u128, NATIVE_DENOM1)],
+        );
+        validate_execution_outcome(execute_outcome, Some("InvalidTiers"));
+
+        // Holders of 5 or more are paid 1.5x, funded for every token at the highest tier
+        let tiers = vec![
+            crate::state::Tier {
+                min_nfts: 1,
+                multiplier: cosmwasm_std::Decimal::one(),
+            },
+            crate::state::Tier {
+                min_nfts: 5,
+                multiplier: cosmwasm_std::Decimal::percent(150),
+            },
+        ];
+        let execute_outcome = app.execute_contract(
+            Addr::unchecked(DISTRIBUTOR),
+            nft_reward_dist_addr.clone(),
+            &tiered_msg(tiers.clone()),
+            &[coin(100u128 * 6u128, NATIVE_DENOM1)],
+        );
+        validate_execution_outcome(execute_outcome, Some("InvalidDistributionInputs"));
+        let execute_outcome = app.execute_contract(
+            Addr::unchecked(DISTRIBUTOR),
+            nft_reward_dist_addr.clone(),
+            &tiered_msg(tiers),
+            &[coin(150u128 * 6u128, NATIVE_DENOM1)],
+        );
+        validate_execution_outcome(execute_outcome, None);
+
+        let execute_outcome = app.execute_contract(
+            Addr::unchecked(WALLET1),
+            nft_reward_dist_addr.clone(),
+            &crate::msg::ExecuteMsg::ClaimFive {
+                token_ids: vec!["1".to_string(), "2".to_string()],
+                dist_ids: None,
+            },
+            &[],
+        );
+        validate_execution_outcome(execute_outcome, None);
+        assert_eq!(
+            app.wrap()
+                .query_balance(WALLET1, NATIVE_DENOM1)
+                .unwrap()
+                .amount,
+            Uint128::new(150u128 * 2u128)
+        );
+        let execute_outcome = app.execute_contract(
+            Addr::unchecked(WALLET2),
+            nft_reward_dist_addr.clone(),
+            &crate::msg::ExecuteMsg::ClaimFive {
+                token_ids: vec!["6".to_string()],
+                dist_ids: None,
+            },
+            &[],
+        );
+        validate_execution_outcome(execute_outcome, None);
+        assert_eq!(
+            app.wrap()
+                .query_balance(WALLET2, NATIVE_DENOM1)
+                .unwrap()
+                .amount,
+            Uint128::new(100u128)
+        );
+
+        // The highest tier amount of the unclaimed tokens and the difference of the lower tiers go back
+        let distributor_balance = app
+            .wrap()
+            .query_balance(DISTRIBUTOR, NATIVE_DENOM1)
+            .unwrap()
+            .amount;
+        set_block_time(&mut app, start_time.plus_days(1u64).plus_seconds(1u64));
+        let execute_outcome = app.execute_contract(
+            Addr::unchecked(DISTRIBUTOR),
+            nft_reward_dist_addr.clone(),
+            &crate::msg::ExecuteMsg::ReturnUnclaimed { dist_id: None },
+            &[],
+        );
+        validate_execution_outcome(execute_outcome, None);
+        assert_eq!(
+            app.wrap()
+                .query_balance(DISTRIBUTOR, NATIVE_DENOM1)
+                .unwrap()
+                .amount,
+            distributor_balance + Uint128::new(150u128 * 6u128 - 150u128 * 2u128 - 100u128)
+        );
+    }
+
+    #[test]
+    fn wallet_caps() {
+        let (mut app, addresses, nft_reward_dist_addr) = setup_reward_distribution(5, 1);
+        let start_time = app.block_info().time;
+
+        let capped_msg =
+            |denom: &str,
+             max_tokens_per_wallet: Option<u64>,
+             max_amount_per_wallet: Option<Vec<crate::state::Asset>>| {
+                crate::msg::ExecuteMsg::Distribute {
+                    assets_per_nft: vec![crate::state::Asset::native(denom, 100u128)],
+                    distribution_end_time: start_time.plus_days(1u64),
+                    unclaimed_policy: crate::state::UnclaimedPolicy::Distributor,
+                    minimum_nfts_to_claim: 1,
+                    start_time: None,
+                    max_tokens_per_wallet,
+                    max_amount_per_wallet,
+                    max_token_id: None,
+                    snapshot_id: None,
+                }
+            };
+        let execute_outcome = app.execute_contract(
+            Addr::unchecked(DISTRIBUTOR),
+            nft_reward_dist_addr.clone(),
+            &capped_msg(
+                NATIVE_DENOM1,
+                None,
+                Some(vec![crate::state::Asset::native(NATIVE_DENOM2, 250u128)]),
+            ),
+            &[coin(100u128 * 6u128, NATIVE_DENOM1)],
+        );
+        validate_execution_outcome(execute_outcome, Some("InvalidWalletCap"));
+
+        // Distribution 1 pays 3 tokens per wallet, distribution 2 up to 250 per wallet
+        let execute_outcome = app.execute_contract(
+            Addr::unchecked(DISTRIBUTOR),
+            nft_reward_dist_addr.clone(),
+            &capped_msg(NATIVE_DENOM1, Some(3), None),
+            &[coin(100u128 * 6u128, NATIVE_DENOM1)],
+        );
+        validate_execution_outcome(execute_outcome, None);
+        let execute_outcome = app.execute_contract(
+            Addr::unchecked(DISTRIBUTOR),
+            nft_reward_dist_addr.clone(),
+            &capped_msg(
+                NATIVE_DENOM2,
+                None,
+                Some(vec![crate::state::Asset::native(NATIVE_DENOM2, 250u128)]),
+            ),
+            &[coin(100u128 * 6u128, NATIVE_DENOM2)],
+        );
+        validate_execution_outcome(execute_outcome, None);
+
+        let claim = |app: &mut StargazeApp, token_ids: Vec<&str>, dist_id: u64| {
+            app.execute_contract(
+                Addr::unchecked(WALLET1),
+                nft_reward_dist_addr.clone(),
+                &crate::msg::ExecuteMsg::ClaimFive {
+                    token_ids: token_ids.into_iter().map(String::from).collect(),
+                    dist_ids: Some(vec![dist_id]),
+                },
+                &[],
+            )
+        };
+        validate_execution_outcome(claim(&mut app, vec!["1", "2"], 1), None);
+        validate_execution_outcome(claim(&mut app, vec!["2", "3", "4"], 1), None);
+        validate_execution_outcome(claim(&mut app, vec!["5"], 1), Some("WalletCapReached"));
+        // Claiming through the collections does not get around the cap
+        let execute_outcome = app.execute_contract(
+            Addr::unchecked(WALLET1),
+            nft_reward_dist_addr.clone(),
+            &crate::msg::ExecuteMsg::ClaimCollections {
+                tokens: vec![crate::msg::CollectionToken {
+                    collection: addresses.nft_addr.to_string(),
+                    token_id: "5".to_string(),
+                }],
+                dist_ids: Some(vec![1]),
+            },
+            &[],
+        );
+        validate_execution_outcome(execute_outcome, Some("WalletCapReached"));
+        assert_eq!(
+            app.wrap()
+                .query_balance(WALLET1, NATIVE_DENOM1)
+                .unwrap()
+                .amount,
+            Uint128::new(100u128 * 3u128)
+        );
+        let query_result: crate::state::WalletClaim = app
+            .wrap()
+            .query_wasm_smart(
+                nft_reward_dist_addr.clone(),
+                &crate::msg::QueryMsg::GetWalletClaim {
+                    dist_id: 1,
+                    wallet: WALLET1.to_string(),
+                },
+            )
+            .unwrap();
+        assert_eq!(
+            query_result,
+            crate::state::WalletClaim {
+                tokens: 3,
+                amounts: vec![crate::state::Asset::native(NATIVE_DENOM1, 300u128)],
+            }
+        );
+
+        // The third token is partially paid, the rest is withheld
+        validate_execution_outcome(claim(&mut app, vec!["1", "2", "3"], 2), None);
+        validate_execution_outcome(claim(&mut app, vec!["4"], 2), Some("WalletCapReached"));
+        assert_eq!(
+            app.wrap()
+                .query_balance(WALLET1, NATIVE_DENOM2)
+                .unwrap()
+                .amount,
+            Uint128::new(250u128)
+        );
+
+        // Tokens refused by the cap and the withheld amount go back to the distributor
+        let distributor_balance = app
+            .wrap()
+            .query_balance(DISTRIBUTOR, NATIVE_DENOM2)
+            .unwrap()
+            .amount;
+        set_block_time(&mut app, start_time.plus_days(1u64).plus_seconds(1u64));
+        let execute_outcome = app.execute_contract(
+            Addr::unchecked(DISTRIBUTOR),
+            nft_reward_dist_addr.clone(),
+            &crate::msg::ExecuteMsg::ReturnUnclaimed { dist_id: Some(2) },
+            &[],
+        );
+        validate_execution_outcome(execute_outcome, None);
+        assert_eq!(
+            app.wrap()
+                .query_balance(DISTRIBUTOR, NATIVE_DENOM2)
+                .unwrap()
+                .amount,
+            distributor_balance + Uint128::new(100u128 * 3u128 + 50u128)
+        );
+    }
+
+    #[test]
+    fn unclaimed_policies() {
+        let (mut app, _addresses, nft_reward_dist_addr) = setup_reward_distribution(3, 2);
+        let start_time = app.block_info().time;
+
+        let distribute = |app: &mut StargazeApp,
+                          denom: &str,
+                          unclaimed_policy: crate::state::UnclaimedPolicy,
+                          end_day: u64| {
+            app.execute_contract(
+                Addr::unchecked(DISTRIBUTOR),
+                nft_reward_dist_addr.clone(),
+                &crate::msg::ExecuteMsg::Distribute {
+                    assets_per_nft: vec![crate::state::Asset::native(denom, 100u128)],
+                    distribution_end_time: start_time.plus_days(end_day),
+                    unclaimed_policy,
+                    minimum_nfts_to_claim: 1,
+                    start_time: None,
+                    max_tokens_per_wallet: None,
+                    max_amount_per_wallet: None,
+                    max_token_id: None,
+                    snapshot_id: None,
+                },
+                &[coin(100u128 * 5u128, denom)],
+            )
+        };
+        let balance = |app: &StargazeApp, address: &str, denom: &str| {
+            app.wrap().query_balance(address, denom).unwrap().amount
+        };
+        validate_execution_outcome(
+            distribute(
+                &mut app,
+                NATIVE_DENOM1,
+                crate::state::UnclaimedPolicy::Address {
+                    address: "".to_string(),
+                },
+                1,
+            ),
+            Some(""),
+        );
+        for (denom, unclaimed_policy) in [
+            (NATIVE_DENOM1, crate::state::UnclaimedPolicy::ProRata),
+            (NATIVE_DENOM2, crate::state::UnclaimedPolicy::CommunityPool),
+            (
+                NATIVE_DENOM3,
+                crate::state::UnclaimedPolicy::Address {
+                    address: WALLET3.to_string(),
+                },
+            ),
+        ] {
+            validate_execution_outcome(distribute(&mut app, denom, unclaimed_policy, 1), None);
+        }
+
+        // Wallet 1 claims 2 tokens of the pro rata distribution and Wallet 2 one
+        for (wallet, token_ids) in [(WALLET1, vec!["1", "2"]), (WALLET2, vec!["4"])] {
+            let execute_outcome = app.execute_contract(
+                Addr::unchecked(wallet),
+                nft_reward_dist_addr.clone(),
+                &crate::msg::ExecuteMsg::ClaimFive {
+                    token_ids: token_ids.into_iter().map(String::from).collect(),
+                    dist_ids: Some(vec![1]),
+                },
+                &[],
+            );
+            validate_execution_outcome(execute_outcome, None);
+        }
+
+        // The unclaimed 200 is 66 per claimed token, the rounding goes back to the distributor
+        set_block_time(&mut app, start_time.plus_days(1u64).plus_seconds(1u64));
+        let distributor_balance = balance(&app, DISTRIBUTOR, NATIVE_DENOM1);
+        let execute_outcome = app.execute_contract(
+            Addr::unchecked(DISTRIBUTOR),
+            nft_reward_dist_addr.clone(),
+            &crate::msg::ExecuteMsg::ReturnUnclaimed { dist_id: Some(1) },
+            &[],
+        );
+        validate_execution_outcome(execute_outcome, None);
+        assert_eq!(
+            balance(&app, DISTRIBUTOR, NATIVE_DENOM1),
+            distributor_balance + Uint128::new(2u128)
+        );
+        let share: Vec<crate::state::Asset> = app
+            .wrap()
+            .query_wasm_smart(
+                nft_reward_dist_addr.clone(),
+                &crate::msg::QueryMsg::GetProRataShare {
+                    dist_id: 1,
+                    wallet: WALLET1.to_string(),
+                },
+            )
+            .unwrap();
+        assert_eq!(
+            share,
+            vec![crate::state::Asset::native(NATIVE_DENOM1, 132u128)]
+        );
+
+        // Each wallet pulls its share once
+        for (wallet, expected_error) in [
+            (WALLET1, None),
+            (WALLET2, None),
+            (WALLET1, Some("NothingToClaim")),
+            (WALLET3, Some("NothingToClaim")),
+        ] {
+            let execute_outcome = app.execute_contract(
+                Addr::unchecked(wallet),
+                nft_reward_dist_addr.clone(),
+                &crate::msg::ExecuteMsg::ClaimProRata { dist_id: 1 },
+                &[],
+            );
+            validate_execution_outcome(execute_outcome, expected_error);
+        }
+        assert_eq!(
+            balance(&app, WALLET1, NATIVE_DENOM1),
+            Uint128::new(200u128 + 132u128)
+        );
+        assert_eq!(
+            balance(&app, WALLET2, NATIVE_DENOM1),
+            Uint128::new(100u128 + 66u128)
+        );
+
+        let execute_outcome = app.execute_contract(
+            Addr::unchecked(DISTRIBUTOR),
+            nft_reward_dist_addr.clone(),
+            &crate::msg::ExecuteMsg::ReturnUnclaimed { dist_id: Some(2) },
+            &[],
+        );
+        validate_execution_outcome(execute_outcome, None);
+        assert_eq!(
+            balance(&app, "community_pool", NATIVE_DENOM2),
+            Uint128::new(100u128 * 5u128)
+        );
+
+        // The next distribution settles the ones that ended
+        validate_execution_outcome(
+            distribute(
+                &mut app,
+                NATIVE_DENOM4,
+                crate::state::UnclaimedPolicy::Burn,
+                3,
+            ),
+            None,
+        );
+        assert_eq!(
+            balance(&app, WALLET3, NATIVE_DENOM3),
+            Uint128::new(100u128 * 5u128)
+        );
+        let distribution: crate::state::Distribution = app
+            .wrap()
+            .query_wasm_smart(
+                nft_reward_dist_addr.clone(),
+                &crate::msg::QueryMsg::GetDistribution { dist_id: 3 },
+            )
+            .unwrap();
+        assert!(distribution.unclaimed_settled);
+
+        set_block_time(&mut app, start_time.plus_days(3u64).plus_seconds(1u64));
+        let distributor_balance = balance(&app, DISTRIBUTOR, NATIVE_DENOM4);
+        let execute_outcome = app.execute_contract(
+            Addr::unchecked(DISTRIBUTOR),
+            nft_reward_dist_addr.clone(),
+            &crate::msg::ExecuteMsg::ReturnUnclaimed { dist_id: Some(4) },
+            &[],
+        );
+        validate_execution_outcome(execute_outcome, None);
+        assert!(balance(&app, nft_reward_dist_addr.as_str(), NATIVE_DENOM4).is_zero());
+        assert_eq!(
+            balance(&app, DISTRIBUTOR, NATIVE_DENOM4),
+            distributor_balance
+        );
+    }
+
+    #[test]
+    fn bonus_rounds() {
+        let (mut app, _addresses, nft_reward_dist_addr) = setup_reward_distribution(3, 2);
+        let start_time = app.block_info().time;
+
+        let distribute_msg = |bonus_end_day: u64| crate::msg::ExecuteMsg::Distribute {
+            assets_per_nft: vec![crate::state::Asset::native(NATIVE_DENOM1, 100u128)],
+            distribution_end_time: start_time.plus_days(1u64),
+            unclaimed_policy: crate::state::UnclaimedPolicy::BonusRound {
+                end_time: start_time.plus_days(bonus_end_day),
+            },
+            minimum_nfts_to_claim: 1,
+            start_time: None,
+            max_tokens_per_wallet: None,
+            max_amount_per_wallet: None,
+            max_token_id: None,
+            snapshot_id: None,
+        };
+        let execute_outcome = app.execute_contract(
+            Addr::unchecked(DISTRIBUTOR),
+            nft_reward_dist_addr.clone(),
+            &distribute_msg(1),
+            &[coin(100u128 * 5u128, NATIVE_DENOM1)],
+        );
+        validate_execution_outcome(execute_outcome, Some("InvalidBonusRound"));
+        let execute_outcome = app.execute_contract(
+            Addr::unchecked(DISTRIBUTOR),
+            nft_reward_dist_addr.clone(),
+            &distribute_msg(2),
+            &[coin(100u128 * 5u128, NATIVE_DENOM1)],
+        );
+        validate_execution_outcome(execute_outcome, None);
+
+        let claim_bonus = |app: &mut StargazeApp, wallet: &str, token_ids: Vec<&str>| {
+            app.execute_contract(
+                Addr::unchecked(wallet),
+                nft_reward_dist_addr.clone(),
+                &crate::msg::ExecuteMsg::ClaimBonus {
+                    dist_id: 1,
+                    token_ids: token_ids.into_iter().map(String::from).collect(),
+                },
+                &[],
+            )
+        };
+        for (wallet, token_ids) in [(WALLET1, vec!["1", "2"]), (WALLET2, vec!["4"])] {
+            let execute_outcome = app.execute_contract(
+                Addr::unchecked(wallet),
+                nft_reward_dist_addr.clone(),
+                &crate::msg::ExecuteMsg::ClaimFive {
+                    token_ids: token_ids.into_iter().map(String::from).collect(),
+                    dist_ids: None,
+                },
+                &[],
+            );
+            validate_execution_outcome(execute_outcome, None);
+        }
+        validate_execution_outcome(
+            claim_bonus(&mut app, WALLET1, vec!["1"]),
+            Some("BonusRoundIsNotOpen"),
+        );
+
+        // The 200 left are split between the 3 tokens which claimed
+        set_block_time(&mut app, start_time.plus_days(1u64).plus_seconds(1u64));
+        let execute_outcome = app.execute_contract(
+            Addr::unchecked(DISTRIBUTOR),
+            nft_reward_dist_addr.clone(),
+            &crate::msg::ExecuteMsg::ReturnUnclaimed { dist_id: None },
+            &[],
+        );
+        validate_execution_outcome(execute_outcome, Some("BonusRoundHasNotYetEnded"));
+        validate_execution_outcome(claim_bonus(&mut app, WALLET1, vec!["1", "2", "3"]), None);
+        validate_execution_outcome(
+            claim_bonus(&mut app, WALLET1, vec!["1"]),
+            Some("NothingToClaim"),
+        );
+        assert_eq!(
+            app.wrap()
+                .query_balance(WALLET1, NATIVE_DENOM1)
+                .unwrap()
+                .amount,
+            Uint128::new(100u128 * 2u128 + 66u128 * 2u128)
+        );
+        let query_result: bool = app
+            .wrap()
+            .query_wasm_smart(
+                nft_reward_dist_addr.clone(),
+                &crate::msg::QueryMsg::GetIfClaimedBonus {
+                    dist_id: 1,
+                    token_id: "2".to_string(),
+                },
+            )
+            .unwrap();
+        assert!(query_result);
+
+        // What the bonus round did not pay goes back to the distributor
+        set_block_time(&mut app, start_time.plus_days(2u64).plus_seconds(1u64));
+        validate_execution_outcome(
+            claim_bonus(&mut app, WALLET2, vec!["4"]),
+            Some("BonusRoundIsNotOpen"),
+        );
+        let distributor_balance = app
+            .wrap()
+            .query_balance(DISTRIBUTOR, NATIVE_DENOM1)
+            .unwrap()
+            .amount;
+        let execute_outcome = app.execute_contract(
+            Addr::unchecked(DISTRIBUTOR),
+            nft_reward_dist_addr.clone(),
+            &crate::msg::ExecuteMsg::ReturnUnclaimed { dist_id: None },
+            &[],
+        );
+        validate_execution_outcome(execute_outcome, None);
+        assert_eq!(
+            app.wrap()
+                .query_balance(DISTRIBUTOR, NATIVE_DENOM1)
+                .unwrap()
+                .amount,
+            distributor_balance + Uint128::new(200u128 - 66u128 * 2u128)
+        );
+    }
+
+    #[test]
+    fn rollover_dust() {
+        let (mut app, _addresses, nft_reward_dist_addr) = setup_reward_distribution(3, 0);
+        let start_time = app.block_info().time;
+
+        // Each round pays 10 more per NFT and rolls over what was not claimed
+        let distribute = |app: &mut StargazeApp, amount: u128, end_day: u64| {
+            let execute_outcome = app.execute_contract(
+                Addr::unchecked(DISTRIBUTOR),
+                nft_reward_dist_addr.clone(),
+                &crate::msg::ExecuteMsg::Distribute {
+                    assets_per_nft: vec![crate::state::Asset::native(NATIVE_DENOM1, amount)],
+                    distribution_end_time: start_time.plus_days(end_day),
+                    unclaimed_policy: crate::state::UnclaimedPolicy::Rollover,
+                    minimum_nfts_to_claim: 1,
+                    start_time: None,
+                    max_tokens_per_wallet: None,
+                    max_amount_per_wallet: None,
+                    max_token_id: None,
+                    snapshot_id: None,
+                },
+                &[coin(amount * 3u128, NATIVE_DENOM1)],
+            );
+            validate_execution_outcome(execute_outcome, None);
+        };
+        let claim_token_1 = |app: &mut StargazeApp| {
+            let execute_outcome = app.execute_contract(
+                Addr::unchecked(WALLET1),
+                nft_reward_dist_addr.clone(),
+                &crate::msg::ExecuteMsg::ClaimFive {
+                    token_ids: vec!["1".to_string()],
+                    dist_ids: None,
+                },
+                &[],
+            );
+            validate_execution_outcome(execute_outcome, None);
+        };
+        let query_dust = |app: &StargazeApp| -> Vec<crate::state::Asset> {
+            app.wrap()
+                .query_wasm_smart(
+                    nft_reward_dist_addr.clone(),
+                    &crate::msg::QueryMsg::GetDust {},
+                )
+                .unwrap()
+        };
+        let query_assets = |app: &StargazeApp, dist_id: u64| -> Vec<crate::state::Asset> {
+            let distribution: crate::state::Distribution = app
+                .wrap()
+                .query_wasm_smart(
+                    nft_reward_dist_addr.clone(),
+                    &crate::msg::QueryMsg::GetDistribution { dist_id },
+                )
+                .unwrap();
+            distribution.assets
+        };
+        distribute(&mut app, 100u128, 1);
+        claim_token_1(&mut app);
+
+        // 200 over 3 tokens rolls 66 per NFT over and keeps 2 as dust
+        set_block_time(&mut app, start_time.plus_days(1u64).plus_seconds(1u64));
+        distribute(&mut app, 10u128, 2);
+        assert_eq!(
+            query_assets(&app, 2),
+            vec![crate::state::Asset::native(NATIVE_DENOM1, 10u128 + 66u128)]
+        );
+        assert_eq!(
+            query_dust(&app),
+            vec![crate::state::Asset::native(NATIVE_DENOM1, 2u128)]
+        );
+        claim_token_1(&mut app);
+
+        // The dust is added to the 152 left, 154 rolls 51 per NFT over instead of 50
+        set_block_time(&mut app, start_time.plus_days(2u64).plus_seconds(1u64));
+        distribute(&mut app, 10u128, 3);
+        assert_eq!(
+            query_assets(&app, 3),
+            vec![crate::state::Asset::native(NATIVE_DENOM1, 10u128 + 51u128)]
+        );
+        assert_eq!(
+            query_dust(&app),
+            vec![crate::state::Asset::native(NATIVE_DENOM1, 1u128)]
+        );
+
+        let execute_outcome = app.execute_contract(
+            Addr::unchecked(WALLET1),
+            nft_reward_dist_addr.clone(),
+            &crate::msg::ExecuteMsg::WithdrawDust {},
+            &[],
+        );
+        validate_execution_outcome(execute_outcome, Some("Unauthorized"));
+        let execute_outcome = app.execute_contract(
+            Addr::unchecked(ADMIN),
+            nft_reward_dist_addr.clone(),
+            &crate::msg::ExecuteMsg::WithdrawDust {},
+            &[],
+        );
+        validate_execution_outcome(execute_outcome, None);
+        assert_eq!(
+            app.wrap()
+                .query_balance(ADMIN, NATIVE_DENOM1)
+                .unwrap()
+                .amount,
+            Uint128::one()
+        );
+        assert!(query_dust(&app).is_empty());
+    }
+
+    #[test]
+    fn pool_distributions() {
+        let (mut app, _addresses, nft_reward_dist_addr) = setup_reward_distribution(3, 2);
+        let start_time = app.block_info().time;
+
+        let distribute_msg = crate::msg::ExecuteMsg::DistributePool {
+            distribution_end_time: start_time.plus_days(1u64),
+            unclaimed_policy: crate::state::UnclaimedPolicy::Distributor,
+            minimum_nfts_to_claim: 1,
+            start_time: None,
+            snapshot_id: None,
+        };
+        let execute_outcome = app.execute_contract(
+            Addr::unchecked(DISTRIBUTOR),
+            nft_reward_dist_addr.clone(),
+            &distribute_msg,
+            &[coin(4u128, NATIVE_DENOM3)],
+        );
+        validate_execution_outcome(execute_outcome, Some("InvalidFundsReceived"));
+
+        // 1003 and 20 over 5 tokens
+        let response = app
+            .execute_contract(
+                Addr::unchecked(DISTRIBUTOR),
+                nft_reward_dist_addr.clone(),
+                &distribute_msg,
+                &[coin(1_003u128, NATIVE_DENOM1), coin(20u128, NATIVE_DENOM2)],
+            )
+            .unwrap();
+        let response_data: crate::msg::DistributePoolResponse =
+            cosmwasm_std::from_binary(&response.data.unwrap()).unwrap();
+        assert_eq!(
+            response_data,
+            crate::msg::DistributePoolResponse {
+                assets_per_nft: vec![
+                    crate::state::Asset::native(NATIVE_DENOM1, 200u128),
+                    crate::state::Asset::native(NATIVE_DENOM2, 4u128),
+                ],
+                dust: vec![crate::state::Asset::native(NATIVE_DENOM1, 3u128)],
+            }
+        );
+        let query_result: Vec<crate::state::Asset> = app
+            .wrap()
+            .query_wasm_smart(
+                nft_reward_dist_addr.clone(),
+                &crate::msg::QueryMsg::GetDust {},
+            )
+            .unwrap();
+        assert_eq!(
+            query_result,
+            vec![crate::state::Asset::native(NATIVE_DENOM1, 3u128)]
+        );
+
+        let execute_outcome = app.execute_contract(
+            Addr::unchecked(WALLET1),
+            nft_reward_dist_addr.clone(),
+            &crate::msg::ExecuteMsg::ClaimFive {
+                token_ids: vec!["1".to_string()],
+                dist_ids: None,
+            },
+            &[],
+        );
+        validate_execution_outcome(execute_outcome, None);
+        assert_eq!(
+            app.wrap()
+                .query_balance(WALLET1, NATIVE_DENOM1)
+                .unwrap()
+                .amount,
+            Uint128::new(200u128)
+        );
+        assert_eq!(
+            app.wrap()
+                .query_balance(WALLET1, NATIVE_DENOM2)
+                .unwrap()
+                .amount,
+            Uint128::new(4u128)
+        );
+    }
+
+    #[test]
+    fn overfunded_distributions() {
+        let (mut app, _addresses, nft_reward_dist_addr) = setup_reward_distribution(3, 2);
+        let start_time = app.block_info().time;
+
+        let distribute_msg = crate::msg::ExecuteMsg::Distribute {
+            assets_per_nft: vec![crate::state::Asset::native(NATIVE_DENOM1, 100u128)],
+            distribution_end_time: start_time.plus_days(1u64),
+            unclaimed_policy: crate::state::UnclaimedPolicy::Distributor,
+            minimum_nfts_to_claim: 1,
+            start_time: None,
+            max_tokens_per_wallet: None,
+            max_amount_per_wallet: None,
+            max_token_id: None,
+            snapshot_id: None,
+        };
+        let execute_outcome = app.execute_contract(
+            Addr::unchecked(DISTRIBUTOR),
+            nft_reward_dist_addr.clone(),
+            &distribute_msg,
+            &[coin(499u128, NATIVE_DENOM1), coin(50u128, NATIVE_DENOM2)],
+        );
+        validate_execution_outcome(execute_outcome, Some("InvalidDistributionInputs"));
+
+        // 5 tokens: 500 is required, the other 100 and the denom not distributed are refunded
+        let balance = |app: &StargazeApp, denom: &str| {
+            app.wrap().query_balance(DISTRIBUTOR, denom).unwrap().amount
+        };
+        let denom1_before = balance(&app, NATIVE_DENOM1);
+        let denom2_before = balance(&app, NATIVE_DENOM2);
+        let response = app
+            .execute_contract(
+                Addr::unchecked(DISTRIBUTOR),
+                nft_reward_dist_addr.clone(),
+                &distribute_msg,
+                &[coin(600u128, NATIVE_DENOM1), coin(50u128, NATIVE_DENOM2)],
+            )
+            .unwrap();
+        let wasm_event = response
+            .events
+            .iter()
+            .find(|event| event.ty == "wasm")
+            .unwrap();
+        let attribute = |key: &str| {
+            wasm_event
+                .attributes
+                .iter()
+                .find(|attribute| attribute.key == key)
+                .unwrap()
+                .value
+                .clone()
+        };
+        assert_eq!(attribute("required"), format!("500{NATIVE_DENOM1}"));
+        assert_eq!(
+            attribute("received"),
+            format!("600{NATIVE_DENOM1}, 50{NATIVE_DENOM2}")
+        );
+        assert_eq!(
+            attribute("refunded"),
+            format!("100{NATIVE_DENOM1}, 50{NATIVE_DENOM2}")
+        );
+        assert_eq!(
+            balance(&app, NATIVE_DENOM1),
+            denom1_before - Uint128::new(500u128)
+        );
+        assert_eq!(balance(&app, NATIVE_DENOM2), denom2_before);
+        assert_eq!(
+            app.wrap()
+                .query_balance(nft_reward_dist_addr.clone(), NATIVE_DENOM1)
+                .unwrap()
+                .amount,
+            Uint128::new(500u128)
+        );
+    }
+
+    #[test]
+    fn top_ups() {
+        let (mut app, _addresses, nft_reward_dist_addr) = setup_reward_distribution(3, 2);
+        let start_time = app.block_info().time;
+
+        let execute_outcome = app.execute_contract(
+            Addr::unchecked(DISTRIBUTOR),
+            nft_reward_dist_addr.clone(),
+            &crate::msg::ExecuteMsg::Distribute {
+                assets_per_nft: vec![crate::state::Asset::native(NATIVE_DENOM1, 100u128)],
+                distribution_end_time: start_time.plus_days(1u64),
+                unclaimed_policy: crate::state::UnclaimedPolicy::Distributor,
+                minimum_nfts_to_claim: 1,
+                start_time: None,
+                max_tokens_per_wallet: None,
+                max_amount_per_wallet: None,
+                max_token_id: None,
+                snapshot_id: None,
+            },
+            &[coin(500u128, NATIVE_DENOM1)],
+        );
+        validate_execution_outcome(execute_outcome, None);
+        let claim = |app: &mut StargazeApp, token_ids: &[&str]| {
+            app.execute_contract(
+                Addr::unchecked(WALLET1),
+                nft_reward_dist_addr.clone(),
+                &crate::msg::ExecuteMsg::ClaimFive {
+                    token_ids: token_ids.iter().map(|id| id.to_string()).collect(),
+                    dist_ids: None,
+                },
+                &[],
+            )
+        };
+        let balance = |app: &StargazeApp, address: &str, denom: &str| {
+            app.wrap().query_balance(address, denom).unwrap().amount
+        };
+        validate_execution_outcome(claim(&mut app, &["1", "2"]), None);
+        assert_eq!(balance(&app, WALLET1, NATIVE_DENOM1), Uint128::new(200u128));
+
+        // Raises the amount and adds a denom
+        let top_up_msg = crate::msg::ExecuteMsg::TopUp {
+            assets_per_nft: vec![
+                crate::state::Asset::native(NATIVE_DENOM1, 50u128),
+                crate::state::Asset::native(NATIVE_DENOM2, 10u128),
+            ],
+        };
+        let top_up_funds = [coin(250u128, NATIVE_DENOM1), coin(50u128, NATIVE_DENOM2)];
+        let execute_outcome = app.execute_contract(
+            Addr::unchecked(WALLET1),
+            nft_reward_dist_addr.clone(),
+            &top_up_msg,
+            &[],
+        );
+        validate_execution_outcome(execute_outcome, Some("Unauthorized"));
+        let execute_outcome = app.execute_contract(
+            Addr::unchecked(DISTRIBUTOR),
+            nft_reward_dist_addr.clone(),
+            &top_up_msg,
+            &[coin(249u128, NATIVE_DENOM1), coin(50u128, NATIVE_DENOM2)],
+        );
+        validate_execution_outcome(execute_outcome, Some("InvalidDistributionInputs"));
+        let execute_outcome = app.execute_contract(
+            Addr::unchecked(DISTRIBUTOR),
+            nft_reward_dist_addr.clone(),
+            &top_up_msg,
+            &top_up_funds,
+        );
+        validate_execution_outcome(execute_outcome, None);
+        let query_result: Vec<crate::state::Asset> = app
+            .wrap()
+            .query_wasm_smart(
+                nft_reward_dist_addr.clone(),
+                &crate::msg::QueryMsg::GetTokenPaid {
+                    dist_id: 1,
+                    token_id: "1".to_string(),
+                },
+            )
+            .unwrap();
+        assert_eq!(
+            query_result,
+            vec![crate::state::Asset::native(NATIVE_DENOM1, 100u128)]
+        );
+
+        // The new token is paid the raised amounts, the others the difference
+        validate_execution_outcome(claim(&mut app, &["1", "2", "3"]), None);
+        assert_eq!(balance(&app, WALLET1, NATIVE_DENOM1), Uint128::new(450u128));
+        assert_eq!(balance(&app, WALLET1, NATIVE_DENOM2), Uint128::new(30u128));
+        validate_execution_outcome(claim(&mut app, &["1"]), Some("NothingToClaim"));
+
+        let execute_outcome = app.execute_contract(
+            Addr::unchecked(DISTRIBUTOR),
+            nft_reward_dist_addr.clone(),
+            &crate::msg::ExecuteMsg::TopUp {
+                assets_per_nft: vec![crate::state::Asset::native(NATIVE_DENOM1, 25u128)],
+            },
+            &[coin(125u128, NATIVE_DENOM1)],
+        );
+        validate_execution_outcome(execute_outcome, None);
+        validate_execution_outcome(claim(&mut app, &["1"]), None);
+        assert_eq!(balance(&app, WALLET1, NATIVE_DENOM1), Uint128::new(475u128));
+        let query_result: Vec<crate::state::Asset> = app
+            .wrap()
+            .query_wasm_smart(
+                nft_reward_dist_addr.clone(),
+                &crate::msg::QueryMsg::GetTokenPaid {
+                    dist_id: 1,
+                    token_id: "1".to_string(),
+                },
+            )
+            .unwrap();
+        assert_eq!(
+            query_result,
+            vec![
+                crate::state::Asset::native(NATIVE_DENOM1, 175u128),
+                crate::state::Asset::native(NATIVE_DENOM2, 10u128),
+            ]
+        );
+
+        // The unclaimed includes the differences not claimed yet
+        set_block_time(&mut app, start_time.plus_days(2u64));
+        let execute_outcome = app.execute_contract(
+            Addr::unchecked(DISTRIBUTOR),
+            nft_reward_dist_addr.clone(),
+            &top_up_msg,
+            &top_up_funds,
+        );
+        validate_execution_outcome(execute_outcome, Some("ClaimingWindowHasClosed"));
+        let distributor_before = balance(&app, DISTRIBUTOR, NATIVE_DENOM1);
+        let execute_outcome = app.execute_contract(
+            Addr::unchecked(DISTRIBUTOR),
+            nft_reward_dist_addr.clone(),
+            &crate::msg::ExecuteMsg::ReturnUnclaimed { dist_id: None },
+            &[],
+        );
+        validate_execution_outcome(execute_outcome, None);
+        assert_eq!(
+            balance(&app, DISTRIBUTOR, NATIVE_DENOM1),
+            distributor_before + Uint128::new(400u128)
+        );
+        assert_eq!(
+            balance(&app, nft_reward_dist_addr.as_str(), NATIVE_DENOM1),
+            Uint128::zero()
+        );
+        assert_eq!(
+            balance(&app, nft_reward_dist_addr.as_str(), NATIVE_DENOM2),
+            Uint128::zero()
+        );
+    }
+
+    #[test]
+    fn amended_distributions() {
+        let (mut app, _addresses, nft_reward_dist_addr) = setup_reward_distribution(3, 2);
+        let start_time = app.block_info().time;
+
+        let distribute_msg = |end_time: Timestamp, start_time: Option<Timestamp>| {
+            crate::msg::ExecuteMsg::Distribute {
+                assets_per_nft: vec![crate::state::Asset::native(NATIVE_DENOM1, 100u128)],
+                distribution_end_time: end_time,
+                unclaimed_policy: crate::state::UnclaimedPolicy::Distributor,
+                minimum_nfts_to_claim: 1,
+                start_time,
+                max_tokens_per_wallet: None,
+                max_amount_per_wallet: None,
+                max_token_id: None,
+                snapshot_id: None,
+            }
+        };
+        for (end_time, queued_start_time) in [
+            (start_time.plus_days(1u64), None),
+            (start_time.plus_days(4u64), Some(start_time.plus_days(3u64))),
+        ] {
+            let execute_outcome = app.execute_contract(
+                Addr::unchecked(DISTRIBUTOR),
+                nft_reward_dist_addr.clone(),
+                &distribute_msg(end_time, queued_start_time),
+                &[coin(500u128, NATIVE_DENOM1)],
+            );
+            validate_execution_outcome(execute_outcome, None);
+        }
+
+        let extend_msg =
+            |end_time: Timestamp| crate::msg::ExecuteMsg::ExtendDistributionWindow { end_time };
+        let execute_outcome = app.execute_contract(
+            Addr::unchecked(WALLET1),
+            nft_reward_dist_addr.clone(),
+            &extend_msg(start_time.plus_days(2u64)),
+            &[],
+        );
+        validate_execution_outcome(execute_outcome, Some("Unauthorized"));
+        // Not later than the current end time, or running into the queued round
+        for end_time in [
+            start_time.plus_days(1u64),
+            start_time.plus_days(3u64).plus_seconds(1u64),
+        ] {
+            let execute_outcome = app.execute_contract(
+                Addr::unchecked(ADMIN),
+                nft_reward_dist_addr.clone(),
+                &extend_msg(end_time),
+                &[],
+            );
+            validate_execution_outcome(execute_outcome, Some("InvalidDistributionEndTime"));
+        }
+        let response = app
+            .execute_contract(
+                Addr::unchecked(ADMIN),
+                nft_reward_dist_addr.clone(),
+                &extend_msg(start_time.plus_days(2u64)),
+                &[],
+            )
+            .unwrap();
+        let event = response
+            .events
+            .iter()
+            .find(|event| event.ty == "wasm-amend_distribution")
+            .unwrap();
+        let attributes: Vec<(String, String)> = event
+            .attributes
+            .iter()
+            .filter(|attribute| attribute.key != "_contract_addr")
+            .map(|attribute| (attribute.key.clone(), attribute.value.clone()))
+            .collect();
+        assert_eq!(
+            attributes,
+            vec![
+                ("dist_id".to_string(), "1".to_string()),
+                ("field".to_string(), "end_time".to_string()),
+                (
+                    "previous".to_string(),
+                    start_time.plus_days(1u64).to_string()
+                ),
+                ("value".to_string(), start_time.plus_days(2u64).to_string()),
+            ]
+        );
+        let config: crate::state::Config = app
+            .wrap()
+            .query_wasm_smart(
+                nft_reward_dist_addr.clone(),
+                &crate::msg::QueryMsg::GetConfig {},
+            )
+            .unwrap();
+        assert_eq!(
+            config.current_dist_end_time,
+            Some(start_time.plus_days(2u64))
+        );
+
+        // Wallet2 holds 2 NFTs
+        let execute_outcome = app.execute_contract(
+            Addr::unchecked(ADMIN),
+            nft_reward_dist_addr.clone(),
+            &crate::msg::ExecuteMsg::SetMinimumNftsToClaim {
+                minimum_nfts_to_claim: 3,
+            },
+            &[],
+        );
+        validate_execution_outcome(execute_outcome, None);
+        let claim = |app: &mut StargazeApp, wallet: &str, token_id: &str| {
+            app.execute_contract(
+                Addr::unchecked(wallet),
+                nft_reward_dist_addr.clone(),
+                &crate::msg::ExecuteMsg::ClaimFive {
+                    token_ids: vec![token_id.to_string()],
+                    dist_ids: None,
+                },
+                &[],
+            )
+        };
+        validate_execution_outcome(claim(&mut app, WALLET2, "4"), Some("NotEnoughNftsToClaim"));
+        validate_execution_outcome(claim(&mut app, WALLET1, "1"), None);
+
+        // Closing never goes before now
+        set_block_time(&mut app, start_time.plus_hours(1u64));
+        let close_msg = |end_time: Option<Timestamp>| {
+            crate::msg::ExecuteMsg::CloseDistributionWindow { end_time }
+        };
+        let execute_outcome = app.execute_contract(
+            Addr::unchecked(ADMIN),
+            nft_reward_dist_addr.clone(),
+            &close_msg(Some(start_time)),
+            &[],
+        );
+        validate_execution_outcome(execute_outcome, Some("InvalidDistributionEndTime"));
+        let execute_outcome = app.execute_contract(
+            Addr::unchecked(ADMIN),
+            nft_reward_dist_addr.clone(),
+            &close_msg(None),
+            &[],
+        );
+        validate_execution_outcome(execute_outcome, None);
+        set_block_time(&mut app, start_time.plus_hours(1u64).plus_seconds(1u64));
+        validate_execution_outcome(
+            claim(&mut app, WALLET1, "2"),
+            Some("ClaimingWindowHasClosed"),
+        );
+        let execute_outcome = app.execute_contract(
+            Addr::unchecked(ADMIN),
+            nft_reward_dist_addr.clone(),
+            &extend_msg(start_time.plus_days(2u64)),
+            &[],
+        );
+        validate_execution_outcome(execute_outcome, Some("ClaimingWindowHasClosed"));
+    }
+
+    #[test]
+    fn cancelled_distributions() {
+        let (mut app, _addresses, nft_reward_dist_addr) = setup_reward_distribution(3, 2);
+        let start_time = app.block_info().time;
+
+        // Refunded to the distributor whatever the unclaimed policy
+        let distribute_msg = crate::msg::ExecuteMsg::Distribute {
+            assets_per_nft: vec![crate::state::Asset::native(NATIVE_DENOM1, 100u128)],
+            distribution_end_time: start_time.plus_days(1u64),
+            unclaimed_policy: crate::state::UnclaimedPolicy::Rollover,
+            minimum_nfts_to_claim: 1,
+            start_time: None,
+            max_tokens_per_wallet: None,
+            max_amount_per_wallet: None,
+            max_token_id: None,
+            snapshot_id: None,
+        };
+        let execute_outcome = app.execute_contract(
+            Addr::unchecked(DISTRIBUTOR),
+            nft_reward_dist_addr.clone(),
+            &distribute_msg,
+            &[coin(500u128, NATIVE_DENOM1)],
+        );
+        validate_execution_outcome(execute_outcome, None);
+        let claim = |app: &mut StargazeApp, token_ids: &[&str]| {
+            app.execute_contract(
+                Addr::unchecked(WALLET1),
+                nft_reward_dist_addr.clone(),
+                &crate::msg::ExecuteMsg::ClaimFive {
+                    token_ids: token_ids.iter().map(|id| id.to_string()).collect(),
+                    dist_ids: None,
+                },
+                &[],
+            )
+        };
+        let balance = |app: &StargazeApp, address: &str| {
+            app.wrap()
+                .query_balance(address, NATIVE_DENOM1)
+                .unwrap()
+                .amount
+        };
+        validate_execution_outcome(claim(&mut app, &["1", "2"]), None);
+
+        let cancel_msg = crate::msg::ExecuteMsg::CancelDistribution { dist_id: None };
+        let execute_outcome = app.execute_contract(
+            Addr::unchecked(WALLET1),
+            nft_reward_dist_addr.clone(),
+            &cancel_msg,
+            &[],
+        );
+        validate_execution_outcome(execute_outcome, Some("Unauthorized"));
+        let distributor_before = balance(&app, DISTRIBUTOR);
+        let execute_outcome = app.execute_contract(
+            Addr::unchecked(ADMIN),
+            nft_reward_dist_addr.clone(),
+            &cancel_msg,
+            &[],
+        );
+        validate_execution_outcome(execute_outcome, None);
+        assert_eq!(
+            balance(&app, DISTRIBUTOR),
+            distributor_before + Uint128::new(300u128)
+        );
+        assert_eq!(
+            balance(&app, nft_reward_dist_addr.as_str()),
+            Uint128::zero()
+        );
+        validate_execution_outcome(claim(&mut app, &["3"]), Some("ClaimingWindowHasClosed"));
+        let execute_outcome = app.execute_contract(
+            Addr::unchecked(ADMIN),
+            nft_reward_dist_addr.clone(),
+            &cancel_msg,
+            &[],
+        );
+        validate_execution_outcome(execute_outcome, Some("NoActiveDistribution"));
+
+        // The claims stay in the history
+        let distribution: crate::state::Distribution = app
+            .wrap()
+            .query_wasm_smart(
+                nft_reward_dist_addr.clone(),
+                &crate::msg::QueryMsg::GetDistribution { dist_id: 1 },
+            )
+            .unwrap();
+        assert_eq!(distribution.claimed, 2);
+        assert_eq!(distribution.cancelled_at, Some(start_time));
+        let claimed: bool = app
+            .wrap()
+            .query_wasm_smart(
+                nft_reward_dist_addr.clone(),
+                &crate::msg::QueryMsg::GetIfClaimedInDistribution {
+                    dist_id: 1,
+                    token_id: "1".to_string(),
+                },
+            )
+            .unwrap();
+        assert!(claimed);
+        let config: crate::state::Config = app
+            .wrap()
+            .query_wasm_smart(
+                nft_reward_dist_addr.clone(),
+                &crate::msg::QueryMsg::GetConfig {},
+            )
+            .unwrap();
+        assert_eq!(config.current_dist_end_time, None);
+        let open_distributions: Vec<u64> = app
+            .wrap()
+            .query_wasm_smart(
+                nft_reward_dist_addr.clone(),
+                &crate::msg::QueryMsg::GetOpenDistributions {},
+            )
+            .unwrap();
+        assert!(open_distributions.is_empty());
+
+        // Nothing is rolled over from the cancelled distribution
+        let execute_outcome = app.execute_contract(
+            Addr::unchecked(DISTRIBUTOR),
+            nft_reward_dist_addr.clone(),
+            &distribute_msg,
+            &[coin(500u128, NATIVE_DENOM1)],
+        );
+        validate_execution_outcome(execute_outcome, None);
+        let distribution: crate::state::Distribution = app
+            .wrap()
+            .query_wasm_smart(
+                nft_reward_dist_addr.clone(),
+                &crate::msg::QueryMsg::GetDistribution { dist_id: 2 },
+            )
+            .unwrap();
+        assert_eq!(
+            distribution.assets,
+            vec![crate::state::Asset::native(NATIVE_DENOM1, 100u128)]
+        );
+        validate_execution_outcome(claim(&mut app, &["1"]), None);
+    }
+
+    #[test]
+    fn pause_scopes() {
+        let (mut app, _addresses, nft_reward_dist_addr) =
+            setup_reward_distribution_with(3, 2, |init_msg| {
+                init_msg.pauser = Some(WALLET3.to_string())
+            });
+        let start_time = app.block_info().time;
+
+        let pause = |app: &mut StargazeApp,
+                     sender: &str,
+                     scope: crate::state::PauseScope,
+                     reason: &str,
+                     until: Option<Timestamp>| {
+            app.execute_contract(
+                Addr::unchecked(sender),
+                nft_reward_dist_addr.clone(),
+                &crate::msg::ExecuteMsg::Pause {
+                    scope,
+                    reason: reason.to_string(),
+                    until,
+                },
+                &[],
+            )
+        };
+        let distribute = |app: &mut StargazeApp| {
+            app.execute_contract(
+                Addr::unchecked(DISTRIBUTOR),
+                nft_reward_dist_addr.clone(),
+                &crate::msg::ExecuteMsg::Distribute {
+                    assets_per_nft: vec![crate::state::Asset::native(NATIVE_DENOM1, 100u128)],
+                    distribution_end_time: start_time.plus_days(1u64),
+                    unclaimed_policy: crate::state::UnclaimedPolicy::Distributor,
+                    minimum_nfts_to_claim: 1,
+                    start_time: None,
+                    max_tokens_per_wallet: None,
+                    max_amount_per_wallet: None,
+                    max_token_id: None,
+                    snapshot_id: None,
+                },
+                &[coin(500u128, NATIVE_DENOM1)],
+            )
+        };
+        let claim = |app: &mut StargazeApp| {
+            app.execute_contract(
+                Addr::unchecked(WALLET1),
+                nft_reward_dist_addr.clone(),
+                &crate::msg::ExecuteMsg::ClaimFive {
+                    token_ids: vec!["1".to_string()],
+                    dist_ids: None,
+                },
+                &[],
+            )
+        };
+
+        // Only the pauser, with a reason and an unpause time to come
+        validate_execution_outcome(
+            pause(
+                &mut app,
+                ADMIN,
+                crate::state::PauseScope::Claim,
+                "incident",
+                None,
+            ),
+            Some("Unauthorized"),
+        );
+        for (reason, until) in [("", None), ("incident", Some(start_time))] {
+            validate_execution_outcome(
+                pause(
+                    &mut app,
+                    WALLET3,
+                    crate::state::PauseScope::Claim,
+                    reason,
+                    until,
+                ),
+                Some("InvalidPause"),
+            );
+        }
+        validate_execution_outcome(distribute(&mut app), None);
+        validate_execution_outcome(
+            pause(
+                &mut app,
+                WALLET3,
+                crate::state::PauseScope::Claim,
+                "incident",
+                Some(start_time.plus_hours(1u64)),
+            ),
+            None,
+        );
+        validate_execution_outcome(claim(&mut app), Some("ClaimsPaused"));
+        let config: crate::state::Config = app
+            .wrap()
+            .query_wasm_smart(
+                nft_reward_dist_addr.clone(),
+                &crate::msg::QueryMsg::GetConfig {},
+            )
+            .unwrap();
+        assert_eq!(
+            config.pauses,
+            crate::state::Pauses {
+                claim: Some(crate::state::Pause {
+                    reason: "incident".to_string(),
+                    until: Some(start_time.plus_hours(1u64)),
+                }),
+                fund: None,
+                r#return: None,
+            }
+        );
+
+        // Funding is paused separately
+        validate_execution_outcome(
+            pause(
+                &mut app,
+                WALLET3,
+                crate::state::PauseScope::Fund,
+                "migration",
+                None,
+            ),
+            None,
+        );
+        validate_execution_outcome(distribute(&mut app), Some("FundingPaused"));
+
+        // The claims pause is lifted at its end time
+        set_block_time(&mut app, start_time.plus_hours(1u64));
+        validate_execution_outcome(claim(&mut app), None);
+
+        // Returning is possible while the claims are paused
+        validate_execution_outcome(
+            pause(
+                &mut app,
+                WALLET3,
+                crate::state::PauseScope::Claim,
+                "incident",
+                None,
+            ),
+            None,
+        );
+        validate_execution_outcome(
+            pause(
+                &mut app,
+                WALLET3,
+                crate::state::PauseScope::Return,
+                "audit",
+                None,
+            ),
+            None,
+        );
+        set_block_time(&mut app, start_time.plus_days(2u64));
+        let return_unclaimed = |app: &mut StargazeApp| {
+            app.execute_contract(
+                Addr::unchecked(DISTRIBUTOR),
+                nft_reward_dist_addr.clone(),
+                &crate::msg::ExecuteMsg::ReturnUnclaimed { dist_id: Some(1) },
+                &[],
+            )
+        };
+        validate_execution_outcome(return_unclaimed(&mut app), Some("ReturnsPaused"));
+
+        // A new round does not settle the ended one while returns are paused
+        let execute_outcome = app.execute_contract(
+            Addr::unchecked(WALLET3),
+            nft_reward_dist_addr.clone(),
+            &crate::msg::ExecuteMsg::Unpause {
+                scope: crate::state::PauseScope::Fund,
+            },
+            &[],
+        );
+        validate_execution_outcome(execute_outcome, None);
+        let execute_outcome = app.execute_contract(
+            Addr::unchecked(DISTRIBUTOR),
+            nft_reward_dist_addr.clone(),
+            &crate::msg::ExecuteMsg::Distribute {
+                assets_per_nft: vec![crate::state::Asset::native(NATIVE_DENOM2, 10u128)],
+                distribution_end_time: start_time.plus_days(3u64),
+                unclaimed_policy: crate::state::UnclaimedPolicy::Distributor,
+                minimum_nfts_to_claim: 1,
+                start_time: None,
+                max_tokens_per_wallet: None,
+                max_amount_per_wallet: None,
+                max_token_id: None,
+                snapshot_id: None,
+            },
+            &[coin(10u128 * 5u128, NATIVE_DENOM2)],
+        );
+        validate_execution_outcome(execute_outcome, None);
+        let distribution: crate::state::Distribution = app
+            .wrap()
+            .query_wasm_smart(
+                nft_reward_dist_addr.clone(),
+                &crate::msg::QueryMsg::GetDistribution { dist_id: 1 },
+            )
+            .unwrap();
+        assert!(!distribution.unclaimed_settled);
+        assert_eq!(
+            app.wrap()
+                .query_balance(nft_reward_dist_addr.to_string(), NATIVE_DENOM1)
+                .unwrap()
+                .amount,
+            Uint128::new(100u128 * 4u128)
+        );
+        let execute_outcome = app.execute_contract(
+            Addr::unchecked(WALLET3),
+            nft_reward_dist_addr.clone(),
+            &crate::msg::ExecuteMsg::Unpause {
+                scope: crate::state::PauseScope::Return,
+            },
+            &[],
+        );
+        validate_execution_outcome(execute_outcome, None);
+        validate_execution_outcome(return_unclaimed(&mut app), None);
+        assert_eq!(
+            app.wrap()
+                .query_balance(nft_reward_dist_addr.to_string(), NATIVE_DENOM1)
+                .unwrap()
+                .amount,
+            Uint128::zero()
+        );
+
+        // The admin rotates the pauser, then revokes it
+        let set_pauser = |app: &mut StargazeApp, sender: &str, pauser: Option<&str>| {
+            app.execute_contract(
+                Addr::unchecked(sender),
+                nft_reward_dist_addr.clone(),
+                &crate::msg::ExecuteMsg::SetPauser {
+                    pauser: pauser.map(String::from),
+                },
+                &[],
+            )
+        };
+        let unpause_claims = |app: &mut StargazeApp, sender: &str| {
+            app.execute_contract(
+                Addr::unchecked(sender),
+                nft_reward_dist_addr.clone(),
+                &crate::msg::ExecuteMsg::Unpause {
+                    scope: crate::state::PauseScope::Claim,
+                },
+                &[],
+            )
+        };
+        validate_execution_outcome(
+            set_pauser(&mut app, WALLET3, Some(WALLET2)),
+            Some("Unauthorized"),
+        );
+        validate_execution_outcome(set_pauser(&mut app, ADMIN, Some(WALLET2)), None);
+        validate_execution_outcome(unpause_claims(&mut app, WALLET3), Some("Unauthorized"));
+        validate_execution_outcome(unpause_claims(&mut app, WALLET2), None);
+        validate_execution_outcome(set_pauser(&mut app, ADMIN, None), None);
+        validate_execution_outcome(
+            pause(
+                &mut app,
+                WALLET2,
+                crate::state::PauseScope::Claim,
+                "incident",
+                None,
+            ),
+            Some("Unauthorized"),
+        );
+        let query_result: crate::state::Config = app
+            .wrap()
+            .query_wasm_smart(
+                nft_reward_dist_addr.clone(),
+                &crate::msg::QueryMsg::GetConfig {},
+            )
+            .unwrap();
+        assert_eq!(query_result.pauser, None);
+    }
+
+    #[test]
+    fn return_unreached_tier_surplus() {
+        let (mut app, _addresses, nft_reward_dist_addr) = setup_reward_distribution(3, 2);
+        let start_time = app.block_info().time;
+
+        // Funded for the 2x tier, which nobody reaches
+        let execute_outcome = app.execute_contract(
+            Addr::unchecked(DISTRIBUTOR),
+            nft_reward_dist_addr.clone(),
+            &crate::msg::ExecuteMsg::DistributeTiered {
+                assets_per_nft: vec![crate::state::Asset::native(NATIVE_DENOM1, 100u128)],
+                tiers: vec![
+                    crate::state::Tier {
+                        min_nfts: 1,
+                        multiplier: cosmwasm_std::Decimal::one(),
+                    },
+                    crate::state::Tier {
+                        min_nfts: 4,
+                        multiplier: cosmwasm_std::Decimal::percent(200),
+                    },
+                ],
+                distribution_end_time: start_time.plus_days(1u64),
+                unclaimed_policy: crate::state::UnclaimedPolicy::Distributor,
+                minimum_nfts_to_claim: 1,
+                start_time: None,
+            },
+            &[coin(200u128 * 5u128, NATIVE_DENOM1)],
+        );
+        validate_execution_outcome(execute_outcome, None);
+
+        // Every token claims at the 1x tier
+        for (wallet, token_ids) in [(WALLET1, vec!["1", "2", "3"]), (WALLET2, vec!["4", "5"])] {
+            let execute_outcome = app.execute_contract(
+                Addr::unchecked(wallet),
+                nft_reward_dist_addr.clone(),
+                &crate::msg::ExecuteMsg::ClaimFive {
+                    token_ids: token_ids.into_iter().map(String::from).collect(),
+                    dist_ids: None,
+                },
+                &[],
+            );
+            validate_execution_outcome(execute_outcome, None);
+        }
+
+        // The surplus of the unreached tier still goes back
+        let distributor_balance = app
+            .wrap()
+            .query_balance(DISTRIBUTOR, NATIVE_DENOM1)
+            .unwrap()
+            .amount;
+        set_block_time(&mut app, start_time.plus_days(1u64).plus_seconds(1u64));
+        let execute_outcome = app.execute_contract(
+            Addr::unchecked(DISTRIBUTOR),
+            nft_reward_dist_addr.clone(),
+            &crate::msg::ExecuteMsg::ReturnUnclaimed { dist_id: None },
+            &[],
+        );
+        validate_execution_outcome(execute_outcome, None);
+        assert_eq!(
+            app.wrap()
+                .query_balance(DISTRIBUTOR, NATIVE_DENOM1)
+                .unwrap()
+                .amount,
+            distributor_balance + Uint128::new(100u128 * 5u128)
+        );
+    }
+
+    #[test]
+    fn return_trait_surplus() {
+        let mut app = custom_mock_app();
+        // Tokens 1 to 3 belong to Wallet 1, 4 and 5 to Wallet 2
+        let collection = instantiate_trait_collection(
+            &mut app,
+            vec![
+                ("1", vec![("Background", "Gold")]),
+                ("2", vec![]),
+                ("3", vec![]),
+                ("4", vec![]),
+                ("5", vec![]),
+            ],
+        );
+        let (mut app, nft_reward_dist_addr) =
+            valid_instantiate_nft_reward_distribution(app, collection);
+        let mint_denom_outcome = app.sudo(SudoMsg::Bank(BankSudo::Mint {
+            to_address: DISTRIBUTOR.to_string(),
+            amount: vec![coin(1_000u128, NATIVE_DENOM1)],
+        }));
+        validate_execution_outcome(mint_denom_outcome, None);
+        let start_time = app.block_info().time;
+        let execute_outcome = app.execute_contract(
+            Addr::unchecked(ADMIN),
+            nft_reward_dist_addr.clone(),
+            &crate::msg::ExecuteMsg::SetTraitMultipliers {
+                trait_multipliers: vec![crate::state::TraitMultiplier {
+                    trait_type: "Background".to_string(),
+                    value: "Gold".to_string(),
+                    multiplier: cosmwasm_std::Decimal::percent(200),
+                }],
+            },
+            &[],
+        );
+        validate_execution_outcome(execute_outcome, None);
+
+        // Funded for a total weight of 10 while the tokens only add up to 6
+        let execute_outcome = app.execute_contract(
+            Addr::unchecked(DISTRIBUTOR),
+            nft_reward_dist_addr.clone(),
+            &crate::msg::ExecuteMsg::DistributeByTraits {
+                assets_per_weight: vec![crate::state::Asset::native(NATIVE_DENOM1, 10u128)],
+                total_weight: 10,
+                distribution_end_time: start_time.plus_days(1u64),
+                unclaimed_policy: crate::state::UnclaimedPolicy::Distributor,
+                minimum_nfts_to_claim: 1,
+                start_time: None,
+                snapshot_id: None,
+            },
+            &[coin(10u128 * 10u128, NATIVE_DENOM1)],
+        );
+        validate_execution_outcome(execute_outcome, None);
+        for (wallet, token_ids) in [(WALLET1, vec!["1", "2", "3"]), (WALLET2, vec!["4", "5"])] {
+            let execute_outcome = app.execute_contract(
+                Addr::unchecked(wallet),
+                nft_reward_dist_addr.clone(),
+                &crate::msg::ExecuteMsg::ClaimFive {
+                    token_ids: token_ids.into_iter().map(String::from).collect(),
+                    dist_ids: None,
+                },
+                &[],
+            );
+            validate_execution_outcome(execute_outcome, None);
+        }
+
+        // Every token claimed, the weight nobody had still goes back
+        let distributor_balance = app
+            .wrap()
+            .query_balance(DISTRIBUTOR, NATIVE_DENOM1)
+            .unwrap()
+            .amount;
+        set_block_time(&mut app, start_time.plus_days(1u64).plus_seconds(1u64));
+        let execute_outcome = app.execute_contract(
+            Addr::unchecked(DISTRIBUTOR),
+            nft_reward_dist_addr.clone(),
+            &crate::msg::ExecuteMsg::ReturnUnclaimed { dist_id: None },
+            &[],
+        );
+        validate_execution_outcome(execute_outcome, None);
+        assert_eq!(
+            app.wrap()
+                .query_balance(DISTRIBUTOR, NATIVE_DENOM1)
+                .unwrap()
+                .amount,
+            distributor_balance + Uint128::new(10u128 * 4u128)
+        );
     }
 }